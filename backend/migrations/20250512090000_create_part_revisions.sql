@@ -0,0 +1,20 @@
+CREATE TABLE part_revisions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    part_id UUID NOT NULL REFERENCES parts(id) ON DELETE CASCADE,
+    revision TEXT NOT NULL,
+    part_number TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    kind TEXT,
+    status TEXT NOT NULL DEFAULT 'working' CHECK (status IN ('working', 'released')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    created_by UUID REFERENCES users(id),
+    released_at TIMESTAMP WITH TIME ZONE,
+    released_by UUID REFERENCES users(id),
+    UNIQUE (part_id, revision)
+);
+
+-- 1部品につき作業中リビジョンは1つまで
+CREATE UNIQUE INDEX part_revisions_one_working_per_part
+    ON part_revisions (part_id)
+    WHERE status = 'working';
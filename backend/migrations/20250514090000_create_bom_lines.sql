@@ -0,0 +1,17 @@
+CREATE TABLE bom_lines (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    parent_id UUID NOT NULL REFERENCES parts(id) ON DELETE CASCADE,
+    -- 子部品を完全削除しても、他の部品の構成が一緒に消えないようにする
+    child_id UUID NOT NULL REFERENCES parts(id) ON DELETE RESTRICT,
+    quantity DOUBLE PRECISION NOT NULL CHECK (quantity > 0),
+    unit_of_measure TEXT NOT NULL DEFAULT 'EA',
+    find_number INTEGER,
+    reference_designators TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    created_by UUID REFERENCES users(id),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    CHECK (parent_id <> child_id),
+    UNIQUE (parent_id, child_id)
+);
+
+CREATE INDEX bom_lines_child_id_idx ON bom_lines (child_id);
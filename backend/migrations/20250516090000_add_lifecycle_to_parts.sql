@@ -0,0 +1,31 @@
+CREATE TABLE lifecycle_states (
+    name TEXT PRIMARY KEY,
+    label TEXT NOT NULL,
+    -- 一般ユーザーによる編集・削除を禁止する状態
+    locked BOOLEAN NOT NULL DEFAULT FALSE,
+    -- この状態に遷移したとき作業中リビジョンをリリースする
+    releases_revision BOOLEAN NOT NULL DEFAULT FALSE,
+    sort_order INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE lifecycle_transitions (
+    from_state TEXT NOT NULL REFERENCES lifecycle_states(name) ON DELETE CASCADE,
+    to_state TEXT NOT NULL REFERENCES lifecycle_states(name) ON DELETE CASCADE,
+    -- 遷移に必要なロール (users.role と同じ値)。admin は常に遷移可能
+    required_role TEXT NOT NULL DEFAULT 'admin',
+    PRIMARY KEY (from_state, to_state)
+);
+
+INSERT INTO lifecycle_states (name, label, locked, releases_revision, sort_order) VALUES
+    ('draft', 'Draft', FALSE, FALSE, 10),
+    ('in_review', 'In Review', FALSE, FALSE, 20),
+    ('released', 'Released', TRUE, TRUE, 30),
+    ('obsolete', 'Obsolete', TRUE, FALSE, 40);
+
+INSERT INTO lifecycle_transitions (from_state, to_state, required_role) VALUES
+    ('draft', 'in_review', 'user'),
+    ('in_review', 'draft', 'user'),
+    ('in_review', 'released', 'admin'),
+    ('released', 'obsolete', 'admin');
+
+ALTER TABLE parts ADD COLUMN status TEXT NOT NULL DEFAULT 'draft' REFERENCES lifecycle_states(name);
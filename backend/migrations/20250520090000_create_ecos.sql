@@ -0,0 +1,45 @@
+CREATE SEQUENCE eco_number_seq;
+
+CREATE TABLE ecos (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    number TEXT UNIQUE NOT NULL DEFAULT 'ECO-' || lpad(nextval('eco_number_seq')::text, 6, '0'),
+    title TEXT NOT NULL,
+    description TEXT,
+    status TEXT NOT NULL DEFAULT 'draft' CHECK (status IN ('draft', 'in_review', 'approved', 'rejected')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    created_by UUID REFERENCES users(id),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    decided_at TIMESTAMP WITH TIME ZONE
+);
+
+-- 変更対象の部品と、リリースする新リビジョンの内容
+CREATE TABLE eco_items (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    eco_id UUID NOT NULL REFERENCES ecos(id) ON DELETE CASCADE,
+    -- 部品を完全削除しても ECO の記録は残す
+    part_id UUID NOT NULL REFERENCES parts(id) ON DELETE RESTRICT,
+    part_number TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    kind TEXT,
+    released_revision TEXT,
+    -- 未完了 (draft / in_review) の ECO の変更内容。ECO を閉じたときに FALSE にする
+    open BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (eco_id, part_id)
+);
+
+CREATE INDEX eco_items_part_id_idx ON eco_items (part_id);
+
+-- 1 つの部品を含む未完了の ECO は 1 つまで
+CREATE UNIQUE INDEX eco_items_open_part_id_idx ON eco_items (part_id) WHERE open;
+
+CREATE TABLE eco_approvals (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    eco_id UUID NOT NULL REFERENCES ecos(id) ON DELETE CASCADE,
+    approver_id UUID NOT NULL REFERENCES users(id),
+    decision TEXT NOT NULL DEFAULT 'pending' CHECK (decision IN ('pending', 'approved', 'rejected')),
+    comment TEXT,
+    decided_at TIMESTAMP WITH TIME ZONE,
+    UNIQUE (eco_id, approver_id)
+);
@@ -0,0 +1,24 @@
+CREATE SEQUENCE ecr_number_seq;
+
+CREATE TABLE ecrs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    number TEXT UNIQUE NOT NULL DEFAULT 'ECR-' || lpad(nextval('ecr_number_seq')::text, 6, '0'),
+    title TEXT NOT NULL,
+    description TEXT NOT NULL,
+    reason_code TEXT NOT NULL CHECK (reason_code IN (
+        'defect', 'cost_reduction', 'obsolescence', 'regulatory', 'customer_request', 'improvement', 'other'
+    )),
+    priority TEXT NOT NULL DEFAULT 'medium' CHECK (priority IN ('low', 'medium', 'high', 'critical')),
+    status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'under_review', 'accepted', 'rejected')),
+    eco_id UUID REFERENCES ecos(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    created_by UUID REFERENCES users(id),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE ecr_parts (
+    ecr_id UUID NOT NULL REFERENCES ecrs(id) ON DELETE CASCADE,
+    -- 部品を完全削除しても ECR の記録は残す
+    part_id UUID NOT NULL REFERENCES parts(id) ON DELETE RESTRICT,
+    PRIMARY KEY (ecr_id, part_id)
+);
@@ -0,0 +1,15 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- 言語非依存の simple 辞書で品番・名前・説明を重み付けして索引化する
+ALTER TABLE parts ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', coalesce(part_number, '')), 'A') ||
+    setweight(to_tsvector('simple', coalesce(name, '')), 'B') ||
+    setweight(to_tsvector('simple', coalesce(description, '')), 'C')
+) STORED;
+
+CREATE INDEX parts_search_vector_idx ON parts USING GIN (search_vector);
+
+-- 品番の部分一致や分かち書きされない日本語 (CJK) 向けのトライグラム索引
+CREATE INDEX parts_part_number_trgm_idx ON parts USING GIN (part_number gin_trgm_ops);
+CREATE INDEX parts_name_trgm_idx ON parts USING GIN (name gin_trgm_ops);
+CREATE INDEX parts_description_trgm_idx ON parts USING GIN (description gin_trgm_ops);
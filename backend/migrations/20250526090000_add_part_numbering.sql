@@ -0,0 +1,44 @@
+-- 既存の重複品番は自動で書き換えず、一覧を出して中止する (解消してから再実行する)
+DO $$
+DECLARE
+    duplicates TEXT;
+BEGIN
+    SELECT string_agg(format('%s (%s parts)', part_number, count), ', ' ORDER BY part_number)
+    INTO duplicates
+    FROM (
+        SELECT part_number, COUNT(*) AS count
+        FROM parts
+        GROUP BY part_number
+        HAVING COUNT(*) > 1
+    ) d;
+
+    IF duplicates IS NOT NULL THEN
+        RAISE EXCEPTION 'Duplicate part numbers found: %', duplicates
+            USING HINT = 'Rename or delete the duplicate parts, then run the migration again.';
+    END IF;
+END $$;
+
+ALTER TABLE parts ADD CONSTRAINT parts_part_number_key UNIQUE (part_number);
+
+-- kind ごとの採番ルール。該当する kind がなければ 'default' を使う
+CREATE TABLE part_number_schemes (
+    kind TEXT PRIMARY KEY,
+    prefix TEXT NOT NULL DEFAULT '',
+    padding INTEGER NOT NULL DEFAULT 6 CHECK (padding BETWEEN 1 AND 18),
+    check_digit BOOLEAN NOT NULL DEFAULT FALSE,
+    next_value BIGINT NOT NULL DEFAULT 1 CHECK (next_value > 0),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO part_number_schemes (kind, prefix, padding, check_digit)
+VALUES ('default', 'P-', 6, FALSE);
+
+-- 予約済みの品番。部品作成時に予約者本人のみが使用できる
+CREATE TABLE part_number_reservations (
+    part_number TEXT PRIMARY KEY,
+    kind TEXT,
+    reserved_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reserved_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    part_id UUID REFERENCES parts(id) ON DELETE SET NULL,
+    consumed_at TIMESTAMP WITH TIME ZONE
+);
@@ -0,0 +1,13 @@
+-- 楽観的排他制御用のバージョン。更新のたびにトリガーで加算する
+CREATE OR REPLACE FUNCTION bump_version() RETURNS trigger AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE parts ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
+
+CREATE TRIGGER parts_bump_version
+    BEFORE UPDATE ON parts
+    FOR EACH ROW EXECUTE FUNCTION bump_version();
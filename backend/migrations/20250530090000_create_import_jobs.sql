@@ -0,0 +1,35 @@
+CREATE TABLE import_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    target TEXT NOT NULL CHECK (target IN ('parts', 'bom')),
+    format TEXT NOT NULL CHECK (format IN ('csv', 'xlsx')),
+    file_name TEXT NOT NULL,
+    -- 取り込み先の項目 -> ファイルの列名
+    mapping JSONB NOT NULL DEFAULT '{}',
+    status TEXT NOT NULL DEFAULT 'validated' CHECK (status IN ('validated', 'invalid', 'committed', 'failed')),
+    total_rows INTEGER NOT NULL DEFAULT 0,
+    error_rows INTEGER NOT NULL DEFAULT 0,
+    committed_rows INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    created_by UUID REFERENCES users(id),
+    committed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX import_jobs_created_by_idx ON import_jobs (created_by, created_at DESC);
+
+-- 列の割り当てを済ませた各行の値 (項目名 -> 文字列)
+CREATE TABLE import_job_rows (
+    job_id UUID NOT NULL REFERENCES import_jobs(id) ON DELETE CASCADE,
+    row_number INTEGER NOT NULL,
+    data JSONB NOT NULL,
+    PRIMARY KEY (job_id, row_number)
+);
+
+CREATE TABLE import_job_errors (
+    id BIGSERIAL PRIMARY KEY,
+    job_id UUID NOT NULL REFERENCES import_jobs(id) ON DELETE CASCADE,
+    row_number INTEGER NOT NULL,
+    field TEXT NOT NULL,
+    message TEXT NOT NULL
+);
+
+CREATE INDEX import_job_errors_job_id_idx ON import_job_errors (job_id, row_number);
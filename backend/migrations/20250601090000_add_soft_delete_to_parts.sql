@@ -0,0 +1,7 @@
+-- 削除した部品はゴミ箱に残し、保持期間を過ぎてから管理者が完全削除する。
+-- 品番の一意制約は削除済みの部品にも効くため、復元時に品番が衝突することはない。
+ALTER TABLE parts
+    ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN deleted_by UUID REFERENCES users(id);
+
+CREATE INDEX parts_deleted_at_idx ON parts (deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,14 @@
+-- リフレッシュトークン。値そのものは保存せず SHA-256 のハッシュのみを持つ。
+-- 更新のたびに同じ family_id で新しいトークンを発行し、使用済みのトークンが再び使われたら family ごと失効させる。
+CREATE TABLE refresh_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    family_id UUID NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX refresh_tokens_family_idx ON refresh_tokens (family_id);
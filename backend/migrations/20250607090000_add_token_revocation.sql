@@ -0,0 +1,14 @@
+-- アクセストークンの jti をリフレッシュトークンと一緒に記録し、セッション (family) の失効時に失効リストへ載せる
+ALTER TABLE refresh_tokens ADD COLUMN access_jti UUID;
+
+CREATE INDEX refresh_tokens_access_jti_idx ON refresh_tokens (access_jti);
+CREATE INDEX refresh_tokens_user_idx ON refresh_tokens (user_id);
+
+-- 失効したアクセストークン。exp を過ぎた行は検査の対象外になる
+CREATE TABLE revoked_tokens (
+    jti UUID PRIMARY KEY,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX revoked_tokens_expires_at_idx ON revoked_tokens (expires_at);
@@ -0,0 +1,29 @@
+-- OIDC でログインするユーザーはローカルのパスワードを持たない
+ALTER TABLE users ALTER COLUMN password_hash DROP NOT NULL;
+
+-- IdP のユーザー (issuer + sub) とローカルユーザーの対応
+CREATE TABLE user_identities (
+    issuer TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_login_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (issuer, subject)
+);
+
+CREATE INDEX user_identities_user_idx ON user_identities (user_id);
+
+-- 認可リクエストごとの state / nonce / PKCE の code_verifier。コールバックで 1 回だけ使う
+CREATE TABLE oidc_login_states (
+    state TEXT PRIMARY KEY,
+    nonce TEXT NOT NULL,
+    code_verifier TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- OIDC ログイン後に SPA へ渡す 1 回限りのコード。SPA が POST /oidc/token でトークンと交換する
+CREATE TABLE oidc_login_codes (
+    code_hash TEXT PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
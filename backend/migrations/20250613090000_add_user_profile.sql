@@ -0,0 +1,4 @@
+-- 外部の認証基盤 (LDAP / OIDC) から同期する表示名とメールアドレス
+ALTER TABLE users
+    ADD COLUMN display_name TEXT,
+    ADD COLUMN email TEXT;
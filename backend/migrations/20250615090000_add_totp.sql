@@ -0,0 +1,39 @@
+-- TOTP (RFC 6238) の二要素認証。enabled_at が NULL の間は登録の確認待ち
+CREATE TABLE user_totp (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    -- base32 の共有鍵
+    secret TEXT NOT NULL,
+    enabled_at TIMESTAMP WITH TIME ZONE,
+    -- 最後に受け付けたコードのステップ。同じコードの再利用を防ぐ
+    last_used_step BIGINT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 認証アプリを使えないときのリカバリーコード (SHA-256 ハッシュ)。各コードは 1 回だけ使える
+CREATE TABLE totp_recovery_codes (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash TEXT NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    PRIMARY KEY (user_id, code_hash)
+);
+
+-- パスワード確認後、二要素認証のコードを待っているログイン
+CREATE TABLE mfa_challenges (
+    token_hash TEXT PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    attempts INT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 管理者が設定する認証のポリシー (1 行のみ)
+CREATE TABLE security_policy (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    -- 監査ログでポリシーの行を特定する ID
+    policy_id UUID NOT NULL DEFAULT gen_random_uuid() UNIQUE,
+    -- admin ロールのユーザーに二要素認証を必須にする
+    require_admin_mfa BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL
+);
+
+INSERT INTO security_policy DEFAULT VALUES;
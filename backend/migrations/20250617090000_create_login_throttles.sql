@@ -0,0 +1,12 @@
+-- ログインの連続失敗回数。アカウント (小文字にしたログイン名) と接続元 IP ごとに数える。
+-- 存在しないログイン名も同じように数え、ユーザーの有無が応答から分からないようにする
+CREATE TABLE login_throttles (
+    -- 監査ログからロックの記録を参照するための id
+    id UUID NOT NULL DEFAULT gen_random_uuid() UNIQUE,
+    kind TEXT NOT NULL CHECK (kind IN ('account', 'ip')),
+    key TEXT NOT NULL,
+    failures INT NOT NULL DEFAULT 0,
+    last_failure_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    locked_until TIMESTAMP WITH TIME ZONE,
+    PRIMARY KEY (kind, key)
+);
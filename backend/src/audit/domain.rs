@@ -0,0 +1,127 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// 監査ログの対象
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEntity {
+    Part,
+    PartRevision,
+    /// ECO の変更内容
+    EcoItem,
+    User,
+    ApiKey,
+    /// ユーザーの TOTP 設定 (id はユーザーの id)
+    Totp,
+    LoginThrottle,
+    /// 認証のポリシー (id は `policy_id`)
+    SecurityPolicy,
+}
+
+impl AuditEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEntity::Part => "part",
+            AuditEntity::PartRevision => "part_revision",
+            AuditEntity::EcoItem => "eco_item",
+            AuditEntity::User => "user",
+            AuditEntity::ApiKey => "api_key",
+            AuditEntity::Totp => "totp",
+            AuditEntity::LoginThrottle => "login_throttle",
+            AuditEntity::SecurityPolicy => "security_policy",
+        }
+    }
+
+    pub fn table(&self) -> &'static str {
+        match self {
+            AuditEntity::Part => "parts",
+            AuditEntity::PartRevision => "part_revisions",
+            AuditEntity::EcoItem => "eco_items",
+            AuditEntity::User => "users",
+            AuditEntity::ApiKey => "api_keys",
+            AuditEntity::Totp => "user_totp",
+            AuditEntity::LoginThrottle => "login_throttles",
+            AuditEntity::SecurityPolicy => "security_policy",
+        }
+    }
+
+    /// 行を特定する列
+    pub fn id_column(&self) -> &'static str {
+        match self {
+            AuditEntity::Totp => "user_id",
+            AuditEntity::SecurityPolicy => "policy_id",
+            _ => "id",
+        }
+    }
+
+    /// スナップショットに含めない列
+    pub fn hidden_columns(&self) -> &'static [&'static str] {
+        match self {
+            AuditEntity::Part => &["search_vector"],
+            AuditEntity::User => &["password_hash"],
+            AuditEntity::ApiKey => &["key_hash"],
+            AuditEntity::Totp => &["secret", "last_used_step"],
+            AuditEntity::PartRevision
+            | AuditEntity::EcoItem
+            | AuditEntity::LoginThrottle
+            | AuditEntity::SecurityPolicy => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+    Purge,
+    Transition,
+    Release,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+            AuditAction::Restore => "restore",
+            AuditAction::Purge => "purge",
+            AuditAction::Transition => "transition",
+            AuditAction::Release => "release",
+        }
+    }
+}
+
+#[derive(Serialize, sqlx::FromRow, ToSchema)]
+pub struct AuditLog {
+    pub id: i64,
+    pub entity: String,
+    pub entity_id: Uuid,
+    pub action: String,
+    /// 操作したユーザー (未認証のサインアップや起動時の初期化では null)
+    pub actor_id: Option<Uuid>,
+    pub request_id: Option<String>,
+    /// 変更前の行 (作成時は null)
+    pub before: Option<Value>,
+    /// 変更後の行 (完全削除時は null)
+    pub after: Option<Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, IntoParams, Default)]
+pub struct AuditQuery {
+    pub entity: Option<AuditEntity>,
+    /// 対象の id
+    pub id: Option<Uuid>,
+    pub action: Option<AuditAction>,
+    pub actor_id: Option<Uuid>,
+    pub request_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
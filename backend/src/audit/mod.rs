@@ -0,0 +1,4 @@
+pub mod domain;
+pub mod request_id;
+pub mod route;
+pub mod service;
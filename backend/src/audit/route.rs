@@ -0,0 +1,30 @@
+use crate::audit::domain::{AuditLog, AuditQuery};
+use crate::audit::service::get_audit_logs as service_get_audit_logs;
+use crate::auth::domain::Claims;
+use crate::errors::app_error::AppError;
+use crate::responses::error::ErrorResponse;
+use crate::responses::pagination::{PageQuery, Pagination};
+use crate::responses::success::SuccessResponse;
+
+use axum::Extension;
+use axum::{Json, extract::Query, extract::RawQuery, extract::State};
+use sqlx::PgPool;
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/audit", params(PageQuery, AuditQuery), responses(
+    (status = 200, description = "Fetched audit logs successfully (paginated, newest first)", body = SuccessResponse<Vec<AuditLog>>),
+    (status = 400, description = "Invalid query parameters"),
+    (status = 401, description = "Unauthorized error (admin only)", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["audit"], security(("bearerAuth" = [])))]
+pub async fn get_audit_logs(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Query(page): Query<PageQuery>,
+    Query(filter): Query<AuditQuery>,
+    RawQuery(raw_query): RawQuery,
+) -> Result<Json<SuccessResponse<Vec<AuditLog>>>, AppError> {
+    let (logs, total) = service_get_audit_logs(claims, &pool, &filter, &page).await?;
+    let pagination = Pagination::new(&page, total, "/audit", raw_query.as_deref());
+    Ok(Json(SuccessResponse::paginated(logs, pagination)))
+}
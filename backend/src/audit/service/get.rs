@@ -0,0 +1,83 @@
+use crate::audit::domain::{AuditLog, AuditQuery};
+use crate::auth::domain::{Claims, Role};
+use crate::errors::app_error::AppError;
+use crate::responses::pagination::PageQuery;
+
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use tracing::{error, info};
+
+fn push_filters(builder: &mut QueryBuilder<'_, Postgres>, filter: &AuditQuery) {
+    builder.push(" WHERE TRUE");
+    if let Some(entity) = filter.entity {
+        builder.push(" AND entity = ").push_bind(entity.as_str());
+    }
+    if let Some(id) = filter.id {
+        builder.push(" AND entity_id = ").push_bind(id);
+    }
+    if let Some(action) = filter.action {
+        builder.push(" AND action = ").push_bind(action.as_str());
+    }
+    if let Some(actor_id) = filter.actor_id {
+        builder.push(" AND actor_id = ").push_bind(actor_id);
+    }
+    if let Some(request_id) = &filter.request_id {
+        builder
+            .push(" AND request_id = ")
+            .push_bind(request_id.clone());
+    }
+    if let Some(from) = filter.from {
+        builder.push(" AND created_at >= ").push_bind(from);
+    }
+    if let Some(to) = filter.to {
+        builder.push(" AND created_at < ").push_bind(to);
+    }
+}
+
+/// 監査ログを新しい順に取得する (管理者のみ)
+pub async fn get_audit_logs(
+    claims: Claims,
+    pool: &PgPool,
+    filter: &AuditQuery,
+    page: &PageQuery,
+) -> Result<(Vec<AuditLog>, i64), AppError> {
+    if claims.role != Role::Admin {
+        info!("User {} is not allowed to read audit logs", claims.sub);
+        return Err(AppError::Unauthorized(
+            "Only admins can read audit logs.".to_string(),
+        ));
+    }
+
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM audit_logs");
+    push_filters(&mut count, filter);
+
+    let total: i64 = count
+        .build_query_scalar()
+        .fetch_one(pool)
+        .await
+        .map_err(|e| {
+            error!("DB error during counting audit logs: {}", e);
+            AppError::DatabaseError("DB select failed".to_string())
+        })?;
+
+    let mut select = QueryBuilder::new(
+        "SELECT id, entity, entity_id, action, actor_id, request_id, before, after, created_at FROM audit_logs",
+    );
+    push_filters(&mut select, filter);
+    select
+        .push(" ORDER BY id DESC LIMIT ")
+        .push_bind(page.per_page())
+        .push(" OFFSET ")
+        .push_bind(page.offset());
+
+    let logs = select
+        .build_query_as::<AuditLog>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| {
+            error!("DB error during fetching audit logs: {}", e);
+            AppError::DatabaseError("DB select failed".to_string())
+        })?;
+
+    info!("Fetched {} of {} audit logs", logs.len(), total);
+    Ok((logs, total))
+}
@@ -0,0 +1,5 @@
+pub mod get;
+pub mod record;
+
+pub use get::get_audit_logs;
+pub use record::{record_audit, snapshot};
@@ -0,0 +1,62 @@
+use crate::audit::domain::{AuditAction, AuditEntity};
+use crate::audit::request_id::current_request_id;
+use crate::errors::app_error::AppError;
+
+use serde_json::Value;
+use sqlx::PgConnection;
+use tracing::error;
+use uuid::Uuid;
+
+/// 監査ログ用に行を JSON として取得する (行がなければ None)
+pub async fn snapshot(
+    conn: &mut PgConnection,
+    entity: AuditEntity,
+    id: Uuid,
+) -> Result<Option<Value>, AppError> {
+    let sql = format!(
+        "SELECT to_jsonb(t) - $2::text[] FROM {} t WHERE {} = $1",
+        entity.table(),
+        entity.id_column()
+    );
+    sqlx::query_scalar::<_, Value>(&sql)
+        .bind(id)
+        .bind(entity.hidden_columns())
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| {
+            error!("DB error during taking audit snapshot: {}", e);
+            AppError::DatabaseError("Failed to write audit log".to_string())
+        })
+}
+
+/// 変更後の行を取得して監査ログに追記する。変更と同じトランザクション内で呼ぶこと。
+pub async fn record_audit(
+    conn: &mut PgConnection,
+    entity: AuditEntity,
+    entity_id: Uuid,
+    action: AuditAction,
+    actor_id: Option<Uuid>,
+    before: Option<Value>,
+) -> Result<(), AppError> {
+    let after = snapshot(&mut *conn, entity, entity_id).await?;
+
+    sqlx::query!(
+        r#"INSERT INTO audit_logs (entity, entity_id, action, actor_id, request_id, before, after)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        entity.as_str(),
+        entity_id,
+        action.as_str(),
+        actor_id,
+        current_request_id(),
+        before,
+        after
+    )
+    .execute(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during writing audit log: {}", e);
+        AppError::DatabaseError("Failed to write audit log".to_string())
+    })?;
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::HeaderMap;
+use axum::http::request::Parts;
+
+/// リクエストの接続元 IP アドレス。
+/// `TRUST_X_FORWARDED_FOR=true` のときはリバースプロキシが付けた `X-Forwarded-For` の最後のアドレスを使う。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+fn trust_forwarded_for() -> bool {
+    std::env::var("TRUST_X_FORWARDED_FOR").is_ok_and(|value| value == "true")
+}
+
+/// プロキシが追記した末尾のアドレスだけを信用する (先頭側はクライアントが偽装できる)
+fn forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .next_back()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if trust_forwarded_for()
+            && let Some(ip) = forwarded_for(&parts.headers)
+        {
+            return Ok(ClientIp(Some(ip)));
+        }
+        Ok(ClientIp(
+            parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::forwarded_for;
+    use axum::http::{HeaderMap, HeaderValue};
+
+    #[test]
+    fn test_forwarded_for() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(forwarded_for(&headers), None);
+        headers.append(
+            "x-forwarded-for",
+            HeaderValue::from_static("1.1.1.1, 10.0.0.2"),
+        );
+        assert_eq!(forwarded_for(&headers), Some("10.0.0.2".parse().unwrap()));
+        headers.append("x-forwarded-for", HeaderValue::from_static("192.0.2.7"));
+        assert_eq!(forwarded_for(&headers), Some("192.0.2.7".parse().unwrap()));
+    }
+}
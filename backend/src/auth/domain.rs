@@ -1,11 +1,20 @@
+use axum::http::Method;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use validator::Validate;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Claims {
     pub sub: String,
     pub role: Role,
     pub exp: usize,
+    /// トークンごとの ID。失効リストの照合に使う
+    pub jti: Uuid,
+    /// 二要素認証が必須なのに未登録。`/mfa` 以外のエンドポイントは使えない
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mfa_enrollment_required: bool,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -27,7 +36,17 @@ pub struct LoginRequest {
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct LoginResponse {
+    /// アクセストークン (JWT)
     pub token: String,
+    /// `token` の有効期間 (秒)
+    pub expires_in: u64,
+    /// `/token/refresh` で新しいトークンと交換する。使えるのは 1 回だけ
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
@@ -46,3 +65,314 @@ impl From<&str> for Role {
         }
     }
 }
+
+impl Role {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Role::Admin => "admin",
+            Role::User => "user",
+            Role::Unknown(other) => other,
+        }
+    }
+
+    /// `required` のロールを要求する操作を実行できるか (Admin は常に実行可能)
+    pub fn satisfies(&self, required: &Role) -> bool {
+        *self == Role::Admin || self == required
+    }
+}
+
+/// ログインごとのセッション (同じログインから更新されたトークンの系列)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Session {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    /// 最後にトークンを更新した日時
+    pub last_used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// このリクエストのトークンが属するセッションか
+    pub current: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RevokedSessions {
+    pub user_id: Uuid,
+    pub revoked: i64,
+    /// 合わせて失効させた API キーの数
+    pub revoked_api_keys: i64,
+}
+
+/// API キーに許可する操作。`read` は GET のみ、`*:write` は対象リソースの作成・更新・削除
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub enum ApiKeyScope {
+    #[serde(rename = "read")]
+    Read,
+    #[serde(rename = "parts:write")]
+    PartsWrite,
+    #[serde(rename = "bom:write")]
+    BomWrite,
+    #[serde(rename = "ecos:write")]
+    EcosWrite,
+    #[serde(rename = "ecrs:write")]
+    EcrsWrite,
+    #[serde(rename = "imports:write")]
+    ImportsWrite,
+}
+
+impl ApiKeyScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyScope::Read => "read",
+            ApiKeyScope::PartsWrite => "parts:write",
+            ApiKeyScope::BomWrite => "bom:write",
+            ApiKeyScope::EcosWrite => "ecos:write",
+            ApiKeyScope::EcrsWrite => "ecrs:write",
+            ApiKeyScope::ImportsWrite => "imports:write",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "read" => Some(ApiKeyScope::Read),
+            "parts:write" => Some(ApiKeyScope::PartsWrite),
+            "bom:write" => Some(ApiKeyScope::BomWrite),
+            "ecos:write" => Some(ApiKeyScope::EcosWrite),
+            "ecrs:write" => Some(ApiKeyScope::EcrsWrite),
+            "imports:write" => Some(ApiKeyScope::ImportsWrite),
+            _ => None,
+        }
+    }
+
+    /// リクエストに必要なスコープ。アカウント管理 (API キー・セッションなど) は API キーでは操作できないため None
+    pub fn required_for(method: &Method, path: &str) -> Option<Self> {
+        let mut segments = path.trim_start_matches('/').split('/');
+        let resource = segments.next().unwrap_or_default();
+        if matches!(
+            resource,
+            "api-keys" | "sessions" | "users" | "mfa" | "security-policy" | "login-lockouts" | "me"
+        ) {
+            return None;
+        }
+        if matches!(*method, Method::GET | Method::HEAD) {
+            return Some(ApiKeyScope::Read);
+        }
+        match resource {
+            "parts" if segments.nth(1) == Some("bom") => Some(ApiKeyScope::BomWrite),
+            "parts" | "part-numbers" | "trash" => Some(ApiKeyScope::PartsWrite),
+            "ecos" => Some(ApiKeyScope::EcosWrite),
+            "ecrs" => Some(ApiKeyScope::EcrsWrite),
+            "imports" => Some(ApiKeyScope::ImportsWrite),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    /// キーの先頭部分 (識別用)
+    pub prefix: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 最後に使われた日時 (更新は 1 分に 1 回まで)
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// 作成した API キー。`key` はこのレスポンスでしか返さない
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct NewApiKey {
+    #[validate(length(min = 1, max = 100, message = "name must be 1 to 100 characters"))]
+    pub name: String,
+    #[validate(length(min = 1, message = "scopes must not be empty"))]
+    pub scopes: Vec<ApiKeyScope>,
+    /// 省略時は無期限
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// IdP からのリダイレクトで渡されるパラメーター
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct OidcCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    /// IdP 側でログインが拒否・中断された場合のエラーコード
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct OidcCodeRequest {
+    /// `/oidc/callback` から SPA へのリダイレクトに付いている 1 回限りのコード
+    pub code: String,
+}
+
+/// ログインの結果。二要素認証が有効なユーザーにはトークンの代わりに `MfaChallenge` を返す
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum LoginOutcome {
+    Tokens(LoginResponse),
+    MfaRequired(MfaChallenge),
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MfaChallenge {
+    #[schema(example = true)]
+    pub mfa_required: bool,
+    /// `POST /login/mfa` に認証アプリのコードと一緒に送るトークン
+    pub mfa_token: String,
+    /// `mfa_token` の有効期間 (秒)
+    pub expires_in: u64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MfaLoginRequest {
+    pub mfa_token: String,
+    /// 認証アプリの 6 桁のコード、またはリカバリーコード
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MfaStatus {
+    pub enabled: bool,
+    pub enabled_at: Option<DateTime<Utc>>,
+    /// 未使用のリカバリーコードの数
+    pub recovery_codes_remaining: i64,
+    /// ポリシーにより二要素認証が必須か
+    pub required: bool,
+}
+
+/// 登録を始めた TOTP の共有鍵。`POST /mfa/totp/confirm` でコードを確認するまで有効にならない
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TotpEnrollment {
+    /// base32 の共有鍵 (手入力用)
+    pub secret: String,
+    /// 認証アプリに QR コードとして読み取らせる `otpauth://` URI
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TotpCode {
+    /// 認証アプリの 6 桁のコード (リカバリーコードを受け付ける操作もある)
+    pub code: String,
+}
+
+/// リカバリーコード。このレスポンスでしか返さない
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SecurityPolicy {
+    /// admin ロールのユーザーに二要素認証を必須にする
+    pub require_admin_mfa: bool,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateSecurityPolicy {
+    pub require_admin_mfa: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LoginThrottleKind {
+    /// ログイン名ごと
+    Account,
+    /// 接続元 IP アドレスごと
+    Ip,
+}
+
+impl LoginThrottleKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoginThrottleKind::Account => "account",
+            LoginThrottleKind::Ip => "ip",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "account" => Some(LoginThrottleKind::Account),
+            "ip" => Some(LoginThrottleKind::Ip),
+            _ => None,
+        }
+    }
+}
+
+/// ログインの失敗が記録されているアカウント・接続元 IP
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LoginLockout {
+    pub kind: LoginThrottleKind,
+    /// 小文字にしたログイン名、または IP アドレス
+    pub key: String,
+    /// 連続して失敗した回数
+    pub failures: i32,
+    pub last_failure_at: DateTime<Utc>,
+    /// この日時までログインできない
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// 管理者が発行したパスワードの再設定トークン。`token` はこのレスポンスでしか返さない
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PasswordResetToken {
+    pub user_id: Uuid,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ApiKeyScope;
+    use axum::http::Method;
+
+    #[test]
+    fn test_required_scope() {
+        let required = |method: Method, path: &str| ApiKeyScope::required_for(&method, path);
+        assert_eq!(required(Method::GET, "/parts"), Some(ApiKeyScope::Read));
+        assert_eq!(required(Method::GET, "/audit"), Some(ApiKeyScope::Read));
+        assert_eq!(
+            required(Method::POST, "/parts"),
+            Some(ApiKeyScope::PartsWrite)
+        );
+        assert_eq!(
+            required(Method::PUT, "/parts/1/bom/2"),
+            Some(ApiKeyScope::BomWrite)
+        );
+        assert_eq!(
+            required(Method::POST, "/parts/1/transition"),
+            Some(ApiKeyScope::PartsWrite)
+        );
+        assert_eq!(
+            required(Method::POST, "/ecos/1/approve"),
+            Some(ApiKeyScope::EcosWrite)
+        );
+        assert_eq!(required(Method::GET, "/api-keys"), None);
+        assert_eq!(required(Method::DELETE, "/users/1/sessions"), None);
+        assert_eq!(required(Method::GET, "/mfa"), None);
+        assert_eq!(required(Method::GET, "/security-policy"), None);
+        assert_eq!(required(Method::GET, "/login-lockouts"), None);
+        assert_eq!(required(Method::POST, "/me/password"), None);
+    }
+}
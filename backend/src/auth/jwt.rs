@@ -1,23 +1,55 @@
 use axum::{
     body::Body,
-    http::{Request, StatusCode, header::AUTHORIZATION},
+    extract::State,
+    http::{Request, header::AUTHORIZATION},
     middleware::Next,
     response::Response,
 };
 use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
-use tracing::error;
+use sqlx::PgPool;
+use tracing::{error, info};
 
 use crate::errors::app_error::AppError;
 
-use super::domain::Claims;
+use super::domain::{ApiKeyScope, Claims};
+use super::revocation::is_revoked;
+use super::service::api_key::authenticate_api_key;
 
-pub async fn jwt_auth(mut req: Request<Body>, next: Next) -> Result<Response, StatusCode> {
-    let token = req
+/// `Authorization: Bearer <JWT>` または `Authorization: ApiKey <key>` を検証し、`Claims` をリクエストに載せる。
+/// API キーの場合は、キーのスコープでリクエストが許可されるかも確認する。
+pub async fn jwt_auth(
+    State(pool): State<PgPool>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    let authorization = req
         .headers()
         .get(AUTHORIZATION)
         .and_then(|v| v.to_str().ok())
-        .and_then(|v| v.strip_prefix("Bearer "))
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+        .ok_or_else(|| AppError::Unauthorized("Missing Authorization header.".to_string()))?;
+
+    if let Some(key) = authorization.strip_prefix("ApiKey ") {
+        let (claims, scopes) = authenticate_api_key(&pool, key).await?;
+        let required = ApiKeyScope::required_for(req.method(), req.uri().path());
+        if !required.is_some_and(|required| scopes.contains(&required)) {
+            info!(
+                "API key {} is not allowed to {} {}",
+                claims.jti,
+                req.method(),
+                req.uri().path()
+            );
+            return Err(AppError::Unauthorized(match required {
+                Some(required) => format!("API key requires the '{}' scope.", required.as_str()),
+                None => "This endpoint cannot be used with an API key.".to_string(),
+            }));
+        }
+        req.extensions_mut().insert(claims);
+        return Ok(next.run(req).await);
+    }
+
+    let token = authorization
+        .strip_prefix("Bearer ")
+        .ok_or_else(|| AppError::Unauthorized("Missing Authorization header.".to_string()))?;
 
     let secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "secret".into());
 
@@ -29,12 +61,30 @@ pub async fn jwt_auth(mut req: Request<Body>, next: Next) -> Result<Response, St
         &validation,
     ) {
         Ok(token_data) => {
+            if is_revoked(&pool, token_data.claims.jti).await? {
+                info!("Rejected revoked token {}", token_data.claims.jti);
+                return Err(AppError::Unauthorized(
+                    "Token has been revoked.".to_string(),
+                ));
+            }
+            if token_data.claims.mfa_enrollment_required && !req.uri().path().starts_with("/mfa") {
+                info!(
+                    "User {} must enroll in two-factor authentication",
+                    token_data.claims.sub
+                );
+                return Err(AppError::Unauthorized(
+                    "Two-factor authentication is required. Set it up with POST /mfa/totp."
+                        .to_string(),
+                ));
+            }
             req.extensions_mut().insert(token_data.claims);
             Ok(next.run(req).await)
         }
         Err(err) => {
             error!("Invalid JWT: {}", err);
-            Err(StatusCode::UNAUTHORIZED)
+            Err(AppError::Unauthorized(
+                "Invalid or expired token.".to_string(),
+            ))
         }
     }
 }
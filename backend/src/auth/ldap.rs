@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use ldap3::{LdapConnAsync, LdapConnSettings, Scope, SearchEntry, dn_escape, ldap_escape};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::auth::domain::Role;
+use crate::errors::app_error::AppError;
+
+/// LDAP サーバーへの接続・操作のタイムアウト
+const LDAP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// LDAP の設定。`LDAP_URL` / `LDAP_BIND_DN` / `LDAP_BASE_DN` がすべて設定されている場合のみ有効
+#[derive(Debug, Clone)]
+pub struct LdapConfig {
+    pub url: String,
+    /// ディレクトリの識別子。ユーザーの対応付けに使うので、サーバーの URL が変わっても同じ値のままにする
+    pub directory_id: String,
+    /// ユーザー本人としてバインドする DN。`{username}` をログイン名に置き換える
+    pub bind_dn: String,
+    pub base_dn: String,
+    /// ユーザーのエントリを探すフィルター。`{username}` をログイン名に置き換える
+    pub user_filter: String,
+    pub starttls: bool,
+    pub display_name_attr: String,
+    pub email_attr: String,
+    /// 所属グループの DN を持つ属性
+    pub group_attr: String,
+    /// このグループのいずれかに所属するユーザーを admin にする
+    pub admin_groups: Vec<String>,
+    /// 設定されていれば、このグループか `admin_groups` に所属するユーザーだけがログインできる
+    pub user_groups: Vec<String>,
+}
+
+/// バインドに成功したユーザーのエントリ
+#[derive(Debug)]
+pub struct LdapEntry {
+    /// エントリの不変の ID (`entryUUID`、Active Directory では `objectGUID`)
+    pub id: String,
+    pub display_name: Option<String>,
+    pub email: Option<String>,
+    pub groups: Vec<String>,
+}
+
+impl LdapConfig {
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let list = |name: &str| -> Vec<String> {
+            var(name)
+                .map(|values| {
+                    values
+                        .split(';')
+                        .map(|value| value.trim().to_string())
+                        .filter(|value| !value.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Some(LdapConfig {
+            url: var("LDAP_URL")?,
+            directory_id: var("LDAP_DIRECTORY_ID").unwrap_or_else(|| "default".to_string()),
+            bind_dn: var("LDAP_BIND_DN")?,
+            base_dn: var("LDAP_BASE_DN")?,
+            user_filter: var("LDAP_USER_FILTER").unwrap_or_else(|| "(uid={username})".to_string()),
+            starttls: var("LDAP_STARTTLS").is_some_and(|value| value == "true"),
+            display_name_attr: var("LDAP_DISPLAY_NAME_ATTR")
+                .unwrap_or_else(|| "displayName".to_string()),
+            email_attr: var("LDAP_EMAIL_ATTR").unwrap_or_else(|| "mail".to_string()),
+            group_attr: var("LDAP_GROUP_ATTR").unwrap_or_else(|| "memberOf".to_string()),
+            admin_groups: list("LDAP_ADMIN_GROUPS"),
+            user_groups: list("LDAP_USER_GROUPS"),
+        })
+    }
+
+    /// 所属グループからロールを決める。グループの対応が未設定なら None (ロールを同期しない)。
+    /// `user_groups` が設定されていて、どのグループにも所属していなければログインを拒否する。
+    pub fn role_from_groups(&self, groups: &[String]) -> Result<Option<Role>, AppError> {
+        if self.admin_groups.is_empty() && self.user_groups.is_empty() {
+            return Ok(None);
+        }
+        let member_of = |configured: &[String]| {
+            groups.iter().any(|group| {
+                configured.iter().any(|name| {
+                    name.eq_ignore_ascii_case(group) || name.eq_ignore_ascii_case(group_name(group))
+                })
+            })
+        };
+        if member_of(&self.admin_groups) {
+            Ok(Some(Role::Admin))
+        } else if self.user_groups.is_empty() || member_of(&self.user_groups) {
+            Ok(Some(Role::User))
+        } else {
+            Err(AppError::Unauthorized(
+                "You are not a member of a group allowed to use PLM.".to_string(),
+            ))
+        }
+    }
+
+    /// ユーザー本人の資格情報でバインドし、エントリを取得する。資格情報が正しくなければ None
+    pub async fn authenticate(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<Option<LdapEntry>, AppError> {
+        // 空のパスワードは匿名バインドとして成功してしまうため、サーバーに送らない
+        if username.is_empty() || password.is_empty() {
+            return Ok(None);
+        }
+
+        let settings = LdapConnSettings::new()
+            .set_conn_timeout(LDAP_TIMEOUT)
+            .set_starttls(self.starttls);
+        let (conn, mut ldap) = LdapConnAsync::with_settings(settings, &self.url)
+            .await
+            .map_err(|e| ldap_error("connect", e))?;
+        ldap3::drive!(conn);
+
+        let bind_dn = self.bind_dn.replace("{username}", &dn_escape(username));
+        let bind = ldap
+            .with_timeout(LDAP_TIMEOUT)
+            .simple_bind(&bind_dn, password)
+            .await
+            .map_err(|e| ldap_error("bind", e))?;
+        match bind.rc {
+            0 => {}
+            // invalidCredentials
+            49 => {
+                info!("LDAP bind failed for {}", username);
+                return Ok(None);
+            }
+            _ => return Err(ldap_error("bind", bind)),
+        }
+
+        let filter = self
+            .user_filter
+            .replace("{username}", &ldap_escape(username));
+        let attrs = vec![
+            self.display_name_attr.as_str(),
+            self.email_attr.as_str(),
+            self.group_attr.as_str(),
+            "entryUUID",
+            "objectGUID",
+            "userPrincipalName",
+        ];
+        // DN でバインドした場合はそのエントリだけを読む。
+        // UPN (Active Directory) でバインドした場合は、userPrincipalName が一致するエントリだけを本人とみなす
+        let bound_by_dn = bind_dn.contains('=');
+        let (base, scope) = if bound_by_dn {
+            (bind_dn.as_str(), Scope::Base)
+        } else {
+            (self.base_dn.as_str(), Scope::Subtree)
+        };
+        let (entries, _) = ldap
+            .with_timeout(LDAP_TIMEOUT)
+            .search(base, scope, &filter, attrs)
+            .await
+            .and_then(|result| result.success())
+            .map_err(|e| ldap_error("search", e))?;
+        let _ = ldap.unbind().await;
+
+        let mut entries: Vec<SearchEntry> = entries
+            .into_iter()
+            .map(SearchEntry::construct)
+            .filter(|entry| {
+                bound_by_dn
+                    || attribute(&entry.attrs, "userPrincipalName")
+                        .iter()
+                        .any(|upn| upn.eq_ignore_ascii_case(&bind_dn))
+            })
+            .collect();
+        if entries.len() != 1 {
+            info!(
+                "LDAP entry of {} not found or ambiguous ({} entries)",
+                username,
+                entries.len()
+            );
+            return Ok(None);
+        }
+        let entry = entries.remove(0);
+
+        let Some(id) = entry_id(&entry) else {
+            error!("LDAP entry {} has no entryUUID or objectGUID", entry.dn);
+            return Err(AppError::InternalError(
+                "The LDAP entry has no entryUUID or objectGUID".to_string(),
+            ));
+        };
+        let first = |attr: &str| attribute(&entry.attrs, attr).first().cloned();
+        Ok(Some(LdapEntry {
+            id,
+            display_name: first(&self.display_name_attr),
+            email: first(&self.email_attr),
+            groups: attribute(&entry.attrs, &self.group_attr).to_vec(),
+        }))
+    }
+}
+
+/// `entryUUID`、なければ `objectGUID` を UUID の文字列にして返す
+fn entry_id(entry: &SearchEntry) -> Option<String> {
+    if let Some(id) = attribute(&entry.attrs, "entryUUID").first() {
+        return Some(id.to_ascii_lowercase());
+    }
+    // objectGUID はバイナリ属性だが、UTF-8 として読めた場合は attrs に入る
+    let text = attribute(&entry.attrs, "objectGUID")
+        .first()
+        .map(|value| value.as_bytes());
+    let binary = entry
+        .bin_attrs
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("objectGUID"))
+        .and_then(|(_, values)| values.first())
+        .map(Vec::as_slice);
+    object_guid(binary.or(text)?)
+}
+
+/// Active Directory の objectGUID (先頭 3 フィールドがリトルエンディアンの 16 バイト) を UUID の文字列にする
+fn object_guid(bytes: &[u8]) -> Option<String> {
+    Uuid::from_slice_le(bytes).ok().map(|id| id.to_string())
+}
+
+fn ldap_error(context: &str, e: impl std::fmt::Display) -> AppError {
+    error!("LDAP error during {}: {}", context, e);
+    AppError::InternalError(format!("Failed to reach the LDAP server ({})", context))
+}
+
+/// 属性名は大文字・小文字を区別しない
+fn attribute<'a>(attrs: &'a HashMap<String, Vec<String>>, name: &str) -> &'a [String] {
+    attrs
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, values)| values.as_slice())
+        .unwrap_or_default()
+}
+
+/// `cn=plm-admins,ou=groups,dc=example,dc=org` から `plm-admins` を取り出す
+fn group_name(dn: &str) -> &str {
+    let rdn = dn.split(',').next().unwrap_or(dn);
+    rdn.split_once('=').map_or(rdn, |(_, value)| value.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LdapConfig, group_name, object_guid};
+    use crate::auth::domain::Role;
+
+    fn config(admin_groups: &[&str], user_groups: &[&str]) -> LdapConfig {
+        LdapConfig {
+            url: "ldap://localhost:389".to_string(),
+            directory_id: "default".to_string(),
+            bind_dn: "uid={username},ou=people,dc=example,dc=org".to_string(),
+            base_dn: "dc=example,dc=org".to_string(),
+            user_filter: "(uid={username})".to_string(),
+            starttls: false,
+            display_name_attr: "displayName".to_string(),
+            email_attr: "mail".to_string(),
+            group_attr: "memberOf".to_string(),
+            admin_groups: admin_groups.iter().map(|g| g.to_string()).collect(),
+            user_groups: user_groups.iter().map(|g| g.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_group_name() {
+        assert_eq!(
+            group_name("cn=plm-admins,ou=groups,dc=example,dc=org"),
+            "plm-admins"
+        );
+        assert_eq!(group_name("plm-admins"), "plm-admins");
+    }
+
+    #[test]
+    fn test_object_guid() {
+        let bytes = [
+            0x33, 0x22, 0x11, 0x00, 0x55, 0x44, 0x77, 0x66, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ];
+        assert_eq!(
+            object_guid(&bytes).as_deref(),
+            Some("00112233-4455-6677-8899-aabbccddeeff")
+        );
+        assert_eq!(object_guid(&bytes[..15]), None);
+    }
+
+    #[test]
+    fn test_role_from_groups() {
+        let groups = vec!["CN=PLM-Admins,OU=Groups,DC=example,DC=org".to_string()];
+        assert_eq!(config(&[], &[]).role_from_groups(&groups).unwrap(), None);
+        assert_eq!(
+            config(&["plm-admins"], &[])
+                .role_from_groups(&groups)
+                .unwrap(),
+            Some(Role::Admin)
+        );
+        assert_eq!(
+            config(&["cn=plm-admins,ou=groups,dc=example,dc=org"], &[])
+                .role_from_groups(&groups)
+                .unwrap(),
+            Some(Role::Admin)
+        );
+        assert_eq!(
+            config(&["other"], &[]).role_from_groups(&groups).unwrap(),
+            Some(Role::User)
+        );
+        assert!(
+            config(&["other"], &["plm-users"])
+                .role_from_groups(&groups)
+                .is_err()
+        );
+        assert_eq!(
+            config(&[], &["plm-admins"])
+                .role_from_groups(&groups)
+                .unwrap(),
+            Some(Role::User)
+        );
+    }
+}
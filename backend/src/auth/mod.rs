@@ -1,5 +1,11 @@
+pub mod client_ip;
 pub mod domain;
 pub mod jwt;
+pub mod ldap;
+pub mod oidc;
 pub mod password;
+pub mod password_policy;
+pub mod revocation;
 pub mod route;
 pub mod service;
+pub mod totp;
@@ -0,0 +1,280 @@
+use std::sync::LazyLock;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use hmac::{Hmac, Mac};
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::sync::{OnceCell, RwLock};
+use tracing::error;
+
+use crate::errors::app_error::AppError;
+
+/// OIDC の設定。`OIDC_ISSUER` / `OIDC_CLIENT_ID` / `OIDC_REDIRECT_URI` / `OIDC_APP_REDIRECT_URI` が
+/// すべて設定されている場合のみ有効
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub redirect_uri: String,
+    /// ログイン後にリダイレクトする SPA の URL。1 回限りのコードを `code` パラメーターで渡す
+    pub app_redirect_uri: String,
+    pub scopes: String,
+    /// ローカルユーザーのログイン名に使うクレーム
+    pub username_claim: String,
+    /// ロールを決めるクレーム (未設定ならロールを同期しない)
+    pub role_claim: Option<String>,
+    /// `role_claim` にこの値のいずれかが含まれるユーザーを admin にする
+    pub admin_values: Vec<String>,
+}
+
+impl OidcConfig {
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Some(OidcConfig {
+            issuer: var("OIDC_ISSUER")?.trim_end_matches('/').to_string(),
+            client_id: var("OIDC_CLIENT_ID")?,
+            client_secret: var("OIDC_CLIENT_SECRET"),
+            redirect_uri: var("OIDC_REDIRECT_URI")?,
+            app_redirect_uri: var("OIDC_APP_REDIRECT_URI")?,
+            scopes: var("OIDC_SCOPES").unwrap_or_else(|| "openid profile email".to_string()),
+            username_claim: var("OIDC_USERNAME_CLAIM")
+                .unwrap_or_else(|| "preferred_username".to_string()),
+            role_claim: var("OIDC_ROLE_CLAIM"),
+            admin_values: var("OIDC_ADMIN_VALUES")
+                .map(|values| {
+                    values
+                        .split(',')
+                        .map(|value| value.trim().to_string())
+                        .filter(|value| !value.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    /// ID トークンのクレームからロールを決める。ロールを同期しない設定なら None
+    pub fn role_from_claims(&self, claims: &Value) -> Option<&'static str> {
+        let claim = claims.get(self.role_claim.as_ref()?);
+        let values: Vec<&str> = match claim {
+            Some(Value::String(value)) => vec![value.as_str()],
+            Some(Value::Array(values)) => values.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let is_admin = values
+            .iter()
+            .any(|value| self.admin_values.iter().any(|admin| admin == value));
+        Some(if is_admin { "admin" } else { "user" })
+    }
+
+    /// 認可リクエストを始めたブラウザに渡す state の Cookie。IdP からのリダイレクトで送られるよう SameSite=Lax にする
+    pub fn state_cookie(&self, value: &str, max_age_secs: i64) -> String {
+        let secure = if self.redirect_uri.starts_with("https://") {
+            "; Secure"
+        } else {
+            ""
+        };
+        format!(
+            "{}={}; Path=/oidc; Max-Age={}; HttpOnly; SameSite=Lax{}",
+            STATE_COOKIE, value, max_age_secs, secure
+        )
+    }
+}
+
+/// 認可リクエストの state を入れる Cookie の名前
+pub const STATE_COOKIE: &str = "plm_oidc_state";
+
+/// state の署名に使う鍵。JWT の署名と同じ鍵を使わないよう、秘密鍵から用途別に導出する
+pub fn derive_state_key(secret: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(b"oidc-state");
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// state に HMAC-SHA256 の署名を付けた Cookie の値 (`<state>.<署名>`)
+pub fn sign_state(state: &str, key: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(state.as_bytes());
+    format!(
+        "{}.{}",
+        state,
+        URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes())
+    )
+}
+
+/// Cookie の署名を検証して state を取り出す。署名が合わなければ None
+pub fn verify_state<'a>(value: &'a str, key: &[u8]) -> Option<&'a str> {
+    let (state, signature) = value.rsplit_once('.')?;
+    let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(state.as_bytes());
+    mac.verify_slice(&signature).ok()?;
+    Some(state)
+}
+
+/// `Cookie` ヘッダーから `name` の値を取り出す
+pub fn find_cookie<'a>(header: &'a str, name: &str) -> Option<&'a str> {
+    header
+        .split(';')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// Discovery ドキュメント (`/.well-known/openid-configuration`) のうち使う項目
+#[derive(Debug, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenResponse {
+    pub id_token: String,
+}
+
+static HTTP: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+static METADATA: OnceCell<ProviderMetadata> = OnceCell::const_new();
+static JWKS: LazyLock<RwLock<Option<JwkSet>>> = LazyLock::new(|| RwLock::new(None));
+
+pub fn http_client() -> &'static reqwest::Client {
+    &HTTP
+}
+
+fn idp_error(context: &str, e: impl std::fmt::Display) -> AppError {
+    error!("OIDC provider error during {}: {}", context, e);
+    AppError::InternalError(format!(
+        "Failed to reach the identity provider ({})",
+        context
+    ))
+}
+
+/// Discovery ドキュメントを取得する。取得に成功した内容はプロセス内でキャッシュする
+pub async fn provider_metadata(config: &OidcConfig) -> Result<&'static ProviderMetadata, AppError> {
+    METADATA
+        .get_or_try_init(|| async {
+            let url = format!("{}/.well-known/openid-configuration", config.issuer);
+            HTTP.get(&url)
+                .send()
+                .await
+                .and_then(|res| res.error_for_status())
+                .map_err(|e| idp_error("discovery", e))?
+                .json::<ProviderMetadata>()
+                .await
+                .map_err(|e| idp_error("discovery", e))
+                .and_then(|metadata| {
+                    // 別の issuer を名乗るドキュメントは使わない (OpenID Connect Discovery 4.3)
+                    if metadata.issuer.trim_end_matches('/') != config.issuer {
+                        error!(
+                            "OIDC issuer mismatch: configured {}, discovered {}",
+                            config.issuer, metadata.issuer
+                        );
+                        return Err(AppError::InternalError(
+                            "The identity provider reported a different issuer".to_string(),
+                        ));
+                    }
+                    Ok(metadata)
+                })
+        })
+        .await
+}
+
+/// `kid` の署名鍵を返す。見つからなければ鍵の更新 (ローテーション) とみなして JWKS を取り直す
+pub async fn find_jwk(metadata: &ProviderMetadata, kid: Option<&str>) -> Result<Jwk, AppError> {
+    let find = |jwks: &JwkSet| match kid {
+        Some(kid) => jwks.find(kid).cloned(),
+        None => jwks.keys.first().cloned(),
+    };
+
+    if let Some(jwk) = JWKS.read().await.as_ref().and_then(find) {
+        return Ok(jwk);
+    }
+
+    let jwks = HTTP
+        .get(&metadata.jwks_uri)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .map_err(|e| idp_error("jwks", e))?
+        .json::<JwkSet>()
+        .await
+        .map_err(|e| idp_error("jwks", e))?;
+    let jwk = find(&jwks);
+    *JWKS.write().await = Some(jwks);
+
+    jwk.ok_or_else(|| AppError::Unauthorized("ID token is signed with an unknown key.".to_string()))
+}
+
+/// PKCE の code_challenge (S256)
+pub fn pkce_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        OidcConfig, derive_state_key, find_cookie, pkce_challenge, sign_state, verify_state,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn test_pkce_challenge() {
+        // RFC 7636 Appendix B
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn test_state_cookie() {
+        let value = sign_state("abc", b"key");
+        assert_eq!(verify_state(&value, b"key"), Some("abc"));
+        assert_eq!(verify_state(&value, b"other"), None);
+        assert_eq!(verify_state(&value.replacen("abc", "abd", 1), b"key"), None);
+        assert_eq!(verify_state("abc", b"key"), None);
+        assert_ne!(derive_state_key(b"key"), b"key");
+
+        let header = format!("theme=dark; plm_oidc_state={}", value);
+        assert_eq!(find_cookie(&header, "plm_oidc_state"), Some(value.as_str()));
+        assert_eq!(find_cookie(&header, "missing"), None);
+    }
+
+    #[test]
+    fn test_role_from_claims() {
+        let config = OidcConfig {
+            issuer: "http://idp".to_string(),
+            client_id: "plm".to_string(),
+            client_secret: None,
+            redirect_uri: "http://localhost/callback".to_string(),
+            app_redirect_uri: "http://localhost:5173/login/oidc".to_string(),
+            scopes: "openid".to_string(),
+            username_claim: "preferred_username".to_string(),
+            role_claim: Some("groups".to_string()),
+            admin_values: vec!["plm-admin".to_string()],
+        };
+        assert_eq!(
+            config.role_from_claims(&json!({"groups": ["staff", "plm-admin"]})),
+            Some("admin")
+        );
+        assert_eq!(
+            config.role_from_claims(&json!({"groups": "staff"})),
+            Some("user")
+        );
+        assert_eq!(config.role_from_claims(&json!({})), Some("user"));
+
+        let unmapped = OidcConfig {
+            role_claim: None,
+            ..config
+        };
+        assert_eq!(
+            unmapped.role_from_claims(&json!({"groups": ["plm-admin"]})),
+            None
+        );
+    }
+}
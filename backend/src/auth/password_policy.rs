@@ -0,0 +1,120 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use tracing::{error, info};
+
+use crate::errors::{app_error::AppError, validation::field_validation_error};
+
+/// これより長いパスワードは受け付けない (ハッシュ計算の負荷を抑える)
+const MAX_LENGTH: usize = 128;
+
+/// パスワードの条件。`PASSWORD_MIN_LENGTH` (default 8) と
+/// `PASSWORD_BREACHED_LIST` (漏えい済みパスワードを 1 行に 1 つ並べたファイル) で設定する
+struct PasswordPolicy {
+    min_length: usize,
+    breached: HashSet<String>,
+}
+
+static POLICY: LazyLock<Result<PasswordPolicy, String>> = LazyLock::new(PasswordPolicy::from_env);
+
+impl PasswordPolicy {
+    /// `PASSWORD_BREACHED_LIST` が設定されているのに読めなければエラー
+    fn from_env() -> Result<Self, String> {
+        let min_length = std::env::var("PASSWORD_MIN_LENGTH")
+            .ok()
+            .and_then(|length| length.parse().ok())
+            .filter(|length| (1..=MAX_LENGTH).contains(length))
+            .unwrap_or(8);
+        let breached = match std::env::var("PASSWORD_BREACHED_LIST") {
+            Ok(path) if !path.is_empty() => match std::fs::read_to_string(&path) {
+                Ok(content) => {
+                    let list = parse_list(&content);
+                    info!("Loaded {} breached passwords from {}", list.len(), path);
+                    list
+                }
+                Err(e) => {
+                    return Err(format!(
+                        "Failed to read PASSWORD_BREACHED_LIST {}: {}",
+                        path, e
+                    ));
+                }
+            },
+            _ => HashSet::new(),
+        };
+        Ok(PasswordPolicy {
+            min_length,
+            breached,
+        })
+    }
+
+    /// 条件を満たさなければ理由を返す
+    fn check(&self, login_name: &str, password: &str) -> Result<(), String> {
+        let length = password.chars().count();
+        if length < self.min_length {
+            return Err(format!(
+                "Password must be at least {} characters.",
+                self.min_length
+            ));
+        }
+        if length > MAX_LENGTH {
+            return Err(format!(
+                "Password must be at most {} characters.",
+                MAX_LENGTH
+            ));
+        }
+        if password.trim().to_lowercase() == login_name.trim().to_lowercase() {
+            return Err("Password must not be the same as the login name.".to_string());
+        }
+        if self.breached.contains(password) {
+            return Err(
+                "This password has appeared in a data breach. Choose a different one.".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+fn parse_list(content: &str) -> HashSet<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 起動時にパスワードの条件を読み込む。漏えい済みパスワードの一覧が読めなければエラー
+pub fn load_password_policy() -> Result<(), String> {
+    POLICY.as_ref().map(|_| ()).map_err(Clone::clone)
+}
+
+/// 新しいパスワードが条件を満たさなければ `field` の入力エラー (400) を返す
+pub fn validate_password(login_name: &str, password: &str, field: &str) -> Result<(), AppError> {
+    let policy = POLICY.as_ref().map_err(|e| {
+        error!("{}", e);
+        AppError::InternalError("Password policy is not available".to_string())
+    })?;
+    policy
+        .check(login_name, password)
+        .map_err(|message| AppError::ValidationError(field_validation_error(field, &message)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PasswordPolicy, parse_list};
+
+    #[test]
+    fn test_check() {
+        let policy = PasswordPolicy {
+            min_length: 8,
+            breached: parse_list("123456\n\n  password1  \n"),
+        };
+        assert!(policy.check("taro", "").is_err());
+        assert!(policy.check("taro", "short").is_err());
+        assert!(policy.check("taro", &"x".repeat(129)).is_err());
+        assert!(policy.check("yamada_taro", "Yamada_Taro").is_err());
+        assert!(policy.check("taro", "password1").is_err());
+        assert!(policy.check("taro", "パスワードです。長い").is_ok());
+        assert!(policy.check("taro", "correct horse").is_ok());
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use sqlx::PgPool;
+use tokio::sync::RwLock;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::errors::app_error::AppError;
+
+/// 失効リストを DB から読み直す間隔。他のインスタンスで失効したトークンはこの時間内に拒否される
+const RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+/// 読み直しに失敗したときの最初の再試行までの時間。以降は失敗するたびに倍になる (最長 `RELOAD_INTERVAL`)
+const RETRY_BASE: Duration = Duration::from_secs(1);
+/// 最後に読み込んでからこの時間を過ぎても読み直せなければ、失効を確認できないとしてトークンを拒否する
+const MAX_STALENESS: Duration = Duration::from_secs(120);
+
+struct RevokedJtis {
+    jtis: HashSet<Uuid>,
+    loaded_at: Option<Instant>,
+    /// 連続して読み直しに失敗した回数
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+impl RevokedJtis {
+    fn is_fresh(&self) -> bool {
+        self.loaded_at
+            .is_some_and(|loaded_at| loaded_at.elapsed() < RELOAD_INTERVAL)
+    }
+
+    fn should_reload(&self) -> bool {
+        !self.is_fresh()
+            && self
+                .retry_at
+                .is_none_or(|retry_at| Instant::now() >= retry_at)
+    }
+
+    /// 手元の一覧で判定する。古すぎる (または一度も読めていない) 場合は失敗として拒否させる
+    fn check(&self, jti: Uuid) -> Result<bool, AppError> {
+        if self
+            .loaded_at
+            .is_some_and(|loaded_at| loaded_at.elapsed() < MAX_STALENESS)
+        {
+            Ok(self.jtis.contains(&jti))
+        } else {
+            Err(AppError::DatabaseError(
+                "Failed to check token revocation".to_string(),
+            ))
+        }
+    }
+}
+
+static REVOKED: LazyLock<RwLock<RevokedJtis>> = LazyLock::new(|| {
+    RwLock::new(RevokedJtis {
+        jtis: HashSet::new(),
+        loaded_at: None,
+        failures: 0,
+        retry_at: None,
+    })
+});
+
+/// `failures` 回続けて読み直しに失敗した後、次に試すまでの時間
+fn retry_backoff(failures: u32) -> Duration {
+    let multiplier = 1u32
+        .checked_shl(failures.saturating_sub(1))
+        .unwrap_or(u32::MAX);
+    RETRY_BASE.saturating_mul(multiplier).min(RELOAD_INTERVAL)
+}
+
+/// アクセストークンが失効しているか。期限切れでない失効リストを丸ごとメモリに持ち、一定間隔で読み直す。
+/// 読み直しに失敗した場合は間隔を空けて再試行し、その間は手元の一覧で判定する。
+/// 手元の一覧が `MAX_STALENESS` より古くなったら失効を確認できないため、エラーにしてトークンを拒否する (fail closed)。
+pub async fn is_revoked(pool: &PgPool, jti: Uuid) -> Result<bool, AppError> {
+    {
+        let cache = REVOKED.read().await;
+        if !cache.should_reload() {
+            return cache.check(jti);
+        }
+    }
+
+    let mut cache = REVOKED.write().await;
+    if cache.should_reload() {
+        match sqlx::query_scalar!(r#"SELECT jti FROM revoked_tokens WHERE expires_at > NOW()"#)
+            .fetch_all(pool)
+            .await
+        {
+            Ok(jtis) => {
+                cache.jtis = jtis.into_iter().collect();
+                cache.loaded_at = Some(Instant::now());
+                cache.failures = 0;
+                cache.retry_at = None;
+            }
+            Err(e) => {
+                cache.failures = cache.failures.saturating_add(1);
+                let backoff = retry_backoff(cache.failures);
+                cache.retry_at = Some(Instant::now() + backoff);
+                error!("DB error during loading revoked tokens: {}", e);
+                warn!(
+                    "Revocation list reload failed {} times; retrying in {:?}",
+                    cache.failures, backoff
+                );
+            }
+        }
+    }
+    cache.check(jti)
+}
+
+/// このインスタンスで失効させたトークンを読み直しを待たずに反映する
+pub async fn remember_revoked(jtis: &[Uuid]) {
+    if jtis.is_empty() {
+        return;
+    }
+    REVOKED.write().await.jtis.extend(jtis.iter().copied());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::retry_backoff;
+
+    #[test]
+    fn test_retry_backoff() {
+        assert_eq!(retry_backoff(1), Duration::from_secs(1));
+        assert_eq!(retry_backoff(2), Duration::from_secs(2));
+        assert_eq!(retry_backoff(5), Duration::from_secs(16));
+        assert_eq!(retry_backoff(6), Duration::from_secs(30));
+        assert_eq!(retry_backoff(100), Duration::from_secs(30));
+    }
+}
@@ -1,12 +1,28 @@
-use crate::auth::domain::{LoginRequest, LoginResponse, SignupRequest, SignupResponse};
+use std::net::IpAddr;
+
+use crate::auth::client_ip::ClientIp;
+use crate::auth::domain::{
+    ApiKey, ChangePasswordRequest, Claims, CreatedApiKey, LoginLockout, LoginOutcome, LoginRequest,
+    LoginResponse, MfaLoginRequest, MfaStatus, NewApiKey, OidcCallbackQuery, OidcCodeRequest,
+    PasswordResetToken, RecoveryCodes, RefreshTokenRequest, ResetPasswordRequest, RevokedSessions,
+    SecurityPolicy, Session, SignupRequest, SignupResponse, TotpCode, TotpEnrollment,
+    UpdateSecurityPolicy,
+};
+use crate::auth::oidc::{OidcConfig, STATE_COOKIE, find_cookie};
 use crate::auth::service as auth_service;
 use crate::responses::error::ErrorResponse;
 use crate::responses::success::SuccessResponse;
 
-use axum::{Json, extract::State};
+use axum::Extension;
+use axum::http::{HeaderMap, header};
+use axum::response::Redirect;
+use axum::{Json, extract::Path, extract::Query, extract::State};
 use sqlx::PgPool;
+use uuid::Uuid;
 
 use crate::errors::app_error::AppError;
+use crate::errors::conflict::ConflictErrorResponse;
+use crate::errors::validation::ValidationErrorResponse;
 
 #[utoipa::path(
     post,
@@ -14,7 +30,8 @@ use crate::errors::app_error::AppError;
     request_body = SignupRequest,
     responses(
         (status = 201, description = "User created successfully", body = SuccessResponse<SignupResponse>),
-        // (status = 409, description = "Conflict (already exists)", body = ErrorResponse),
+        (status = 400, description = "Validation error (password does not meet the password policy)", body = ValidationErrorResponse),
+        (status = 409, description = "Conflict (login name already exists)", body = ConflictErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     tags = ["auth"]
@@ -32,16 +49,565 @@ pub async fn signup(
     path = "/login",
     request_body = LoginRequest,
     responses(
-        (status = 200, description = "Login successful", body = SuccessResponse<LoginResponse>),
-        (status = 401, description = "Unauthorized (invalid credentials)", body = ErrorResponse),
+        (status = 200, description = "Login successful, or a challenge for `/login/mfa` if two-factor authentication is enabled", body = SuccessResponse<LoginOutcome>),
+        (status = 401, description = "Unauthorized (unknown login name or invalid credentials)", body = ErrorResponse),
+        (status = 429, description = "Too many failed attempts for the account or client IP (see `Retry-After`)", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     tags = ["auth"]
 )]
 pub async fn login(
     State(pool): State<PgPool>,
+    ClientIp(client_ip): ClientIp,
     Json(payload): Json<LoginRequest>,
+) -> Result<Json<SuccessResponse<LoginOutcome>>, AppError> {
+    let login_response = auth_service::login(&pool, payload, client_ip).await?;
+    Ok(Json(SuccessResponse::ok(login_response)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/login/mfa",
+    request_body = MfaLoginRequest,
+    responses(
+        (status = 200, description = "Login successful", body = SuccessResponse<LoginResponse>),
+        (status = 401, description = "Unauthorized (invalid code, or unknown or expired MFA token)", body = ErrorResponse),
+        (status = 429, description = "Too many failed attempts for the account or client IP (see `Retry-After`)", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"]
+)]
+pub async fn login_mfa(
+    State(pool): State<PgPool>,
+    ClientIp(client_ip): ClientIp,
+    Json(payload): Json<MfaLoginRequest>,
 ) -> Result<Json<SuccessResponse<LoginResponse>>, AppError> {
-    let login_response = auth_service::login(&pool, payload).await?;
+    let login_response = auth_service::verify_mfa_login(&pool, payload, client_ip).await?;
+    Ok(Json(SuccessResponse::ok(login_response)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/oidc/authorize",
+    responses(
+        (status = 303, description = "Redirect to the identity provider (authorization code flow with PKCE). Sets a signed, HttpOnly state cookie"),
+        (status = 404, description = "OIDC login is not configured", body = ErrorResponse),
+        (status = 500, description = "Internal server error (identity provider unreachable or issuer mismatch)", body = ErrorResponse)
+    ),
+    tags = ["auth"]
+)]
+pub async fn oidc_authorize(
+    State(pool): State<PgPool>,
+) -> Result<([(header::HeaderName, String); 1], Redirect), AppError> {
+    let (url, cookie) = auth_service::oidc_authorize(&pool).await?;
+    Ok(([(header::SET_COOKIE, cookie)], Redirect::to(&url)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/oidc/callback",
+    params(OidcCallbackQuery),
+    responses(
+        (status = 303, description = "Redirect to `OIDC_APP_REDIRECT_URI` with a one-time `code` for `POST /oidc/token`"),
+        (status = 401, description = "Unauthorized (rejected login, invalid state, state cookie or ID token)", body = ErrorResponse),
+        (status = 404, description = "OIDC login is not configured", body = ErrorResponse),
+        (status = 409, description = "Conflict (a local user already has the login name)", body = ConflictErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"]
+)]
+pub async fn oidc_callback(
+    State(pool): State<PgPool>,
+    headers: HeaderMap,
+    Query(query): Query<OidcCallbackQuery>,
+) -> Result<([(header::HeaderName, String); 1], Redirect), AppError> {
+    let state_cookie = headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .find_map(|value| find_cookie(value, STATE_COOKIE));
+    let url = auth_service::oidc_callback(&pool, query, state_cookie).await?;
+    // state は使い終わったので Cookie を消す
+    let cookie = OidcConfig::from_env()
+        .map(|config| config.state_cookie("", 0))
+        .unwrap_or_default();
+    Ok(([(header::SET_COOKIE, cookie)], Redirect::to(&url)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/oidc/token",
+    request_body = OidcCodeRequest,
+    responses(
+        (status = 200, description = "Login successful, or a challenge for `/login/mfa` if two-factor authentication is enabled", body = SuccessResponse<LoginOutcome>),
+        (status = 401, description = "Unauthorized (unknown, used or expired code)", body = ErrorResponse),
+        (status = 404, description = "OIDC login is not configured", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"]
+)]
+pub async fn oidc_token(
+    State(pool): State<PgPool>,
+    Json(payload): Json<OidcCodeRequest>,
+) -> Result<Json<SuccessResponse<LoginOutcome>>, AppError> {
+    let login_response = auth_service::oidc_exchange_code(&pool, payload).await?;
     Ok(Json(SuccessResponse::ok(login_response)))
 }
+
+#[utoipa::path(
+    post,
+    path = "/token/refresh",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, description = "New access and refresh tokens (the given refresh token is used up)", body = SuccessResponse<LoginResponse>),
+        (status = 401, description = "Unauthorized (unknown, expired, revoked or reused refresh token)", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"]
+)]
+pub async fn refresh_token(
+    State(pool): State<PgPool>,
+    Json(payload): Json<RefreshTokenRequest>,
+) -> Result<Json<SuccessResponse<LoginResponse>>, AppError> {
+    let tokens = auth_service::refresh_token(&pool, payload).await?;
+    Ok(Json(SuccessResponse::ok(tokens)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/logout",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 204, description = "Refresh token and every token rotated from it revoked"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"]
+)]
+pub async fn logout(
+    State(pool): State<PgPool>,
+    Json(payload): Json<RefreshTokenRequest>,
+) -> Result<Json<SuccessResponse<()>>, AppError> {
+    auth_service::logout(&pool, payload).await?;
+    Ok(Json(SuccessResponse::no_content()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/sessions",
+    responses(
+        (status = 200, description = "Active sessions of the current user", body = SuccessResponse<Vec<Session>>),
+        (status = 401, description = "Unauthorized error", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn get_sessions(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+) -> Result<Json<SuccessResponse<Vec<Session>>>, AppError> {
+    let sessions = auth_service::get_sessions(claims, &pool).await?;
+    Ok(Json(SuccessResponse::ok(sessions)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/sessions/{id}",
+    params(("id" = Uuid, Path, description = "Session ID to revoke")),
+    responses(
+        (status = 204, description = "Session revoked (its refresh and access tokens stop working)"),
+        (status = 401, description = "Unauthorized error", body = ErrorResponse),
+        (status = 404, description = "NotFound error", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn revoke_session(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<()>>, AppError> {
+    auth_service::revoke_session(claims, &pool, id).await?;
+    Ok(Json(SuccessResponse::no_content()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/users/{id}/sessions",
+    params(("id" = Uuid, Path, description = "User ID whose sessions are revoked")),
+    responses(
+        (status = 200, description = "All sessions and API keys of the user revoked", body = SuccessResponse<RevokedSessions>),
+        (status = 401, description = "Unauthorized error (admin only)", body = ErrorResponse),
+        (status = 404, description = "NotFound error", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn revoke_user_sessions(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<RevokedSessions>>, AppError> {
+    let result = auth_service::revoke_user_sessions(claims, &pool, id).await?;
+    Ok(Json(SuccessResponse::ok(result)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api-keys",
+    request_body = NewApiKey,
+    responses(
+        (status = 201, description = "API key created. The key is only returned in this response", body = SuccessResponse<CreatedApiKey>),
+        (status = 400, description = "Validation error", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized error", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn create_api_key(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Json(payload): Json<NewApiKey>,
+) -> Result<Json<SuccessResponse<CreatedApiKey>>, AppError> {
+    let api_key = auth_service::create_api_key(claims, &pool, payload).await?;
+    Ok(Json(SuccessResponse::created(api_key)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api-keys",
+    responses(
+        (status = 200, description = "API keys of the current user", body = SuccessResponse<Vec<ApiKey>>),
+        (status = 401, description = "Unauthorized error", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn get_api_keys(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+) -> Result<Json<SuccessResponse<Vec<ApiKey>>>, AppError> {
+    let api_keys = auth_service::get_api_keys(claims, &pool).await?;
+    Ok(Json(SuccessResponse::ok(api_keys)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api-keys/{id}",
+    params(("id" = Uuid, Path, description = "API key ID to revoke")),
+    responses(
+        (status = 204, description = "API key revoked"),
+        (status = 401, description = "Unauthorized error", body = ErrorResponse),
+        (status = 404, description = "NotFound error", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn revoke_api_key(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<()>>, AppError> {
+    auth_service::revoke_api_key(claims, &pool, id).await?;
+    Ok(Json(SuccessResponse::no_content()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/mfa",
+    responses(
+        (status = 200, description = "Two-factor authentication status of the current user", body = SuccessResponse<MfaStatus>),
+        (status = 401, description = "Unauthorized error", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn get_mfa_status(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+) -> Result<Json<SuccessResponse<MfaStatus>>, AppError> {
+    let status = auth_service::get_mfa_status(claims, &pool).await?;
+    Ok(Json(SuccessResponse::ok(status)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/mfa/totp",
+    responses(
+        (status = 200, description = "TOTP secret and provisioning URI. Confirm with `/mfa/totp/confirm` to enable it", body = SuccessResponse<TotpEnrollment>),
+        (status = 401, description = "Unauthorized error", body = ErrorResponse),
+        (status = 409, description = "Conflict (two-factor authentication already enabled)", body = ConflictErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn start_totp_enrollment(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+) -> Result<Json<SuccessResponse<TotpEnrollment>>, AppError> {
+    let enrollment = auth_service::start_totp_enrollment(claims, &pool).await?;
+    Ok(Json(SuccessResponse::ok(enrollment)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/mfa/totp/confirm",
+    request_body = TotpCode,
+    responses(
+        (status = 200, description = "Two-factor authentication enabled. The recovery codes are only returned in this response", body = SuccessResponse<RecoveryCodes>),
+        (status = 401, description = "Unauthorized error (invalid code)", body = ErrorResponse),
+        (status = 404, description = "No enrollment in progress", body = ErrorResponse),
+        (status = 409, description = "Conflict (two-factor authentication already enabled)", body = ConflictErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn confirm_totp_enrollment(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Json(payload): Json<TotpCode>,
+) -> Result<Json<SuccessResponse<RecoveryCodes>>, AppError> {
+    let recovery_codes = auth_service::confirm_totp_enrollment(claims, &pool, payload).await?;
+    Ok(Json(SuccessResponse::ok(recovery_codes)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/mfa/totp",
+    request_body = TotpCode,
+    responses(
+        (status = 204, description = "Two-factor authentication disabled"),
+        (status = 401, description = "Unauthorized error (invalid code, or required for admins)", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn disable_totp(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Json(payload): Json<TotpCode>,
+) -> Result<Json<SuccessResponse<()>>, AppError> {
+    auth_service::disable_totp(claims, &pool, payload).await?;
+    Ok(Json(SuccessResponse::no_content()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/mfa/recovery-codes",
+    request_body = TotpCode,
+    responses(
+        (status = 200, description = "New recovery codes. The previous codes no longer work", body = SuccessResponse<RecoveryCodes>),
+        (status = 401, description = "Unauthorized error (invalid code)", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn regenerate_recovery_codes(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Json(payload): Json<TotpCode>,
+) -> Result<Json<SuccessResponse<RecoveryCodes>>, AppError> {
+    let recovery_codes = auth_service::regenerate_recovery_codes(claims, &pool, payload).await?;
+    Ok(Json(SuccessResponse::ok(recovery_codes)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/users/{id}/mfa",
+    params(("id" = Uuid, Path, description = "User ID whose two-factor authentication is reset")),
+    responses(
+        (status = 204, description = "Two-factor authentication of the user removed"),
+        (status = 401, description = "Unauthorized error (admin only, not for your own account)", body = ErrorResponse),
+        (status = 404, description = "NotFound error", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn reset_user_mfa(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<()>>, AppError> {
+    auth_service::reset_user_mfa(claims, &pool, id).await?;
+    Ok(Json(SuccessResponse::no_content()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/security-policy",
+    responses(
+        (status = 200, description = "Authentication policy", body = SuccessResponse<SecurityPolicy>),
+        (status = 401, description = "Unauthorized error", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn get_security_policy(
+    State(pool): State<PgPool>,
+) -> Result<Json<SuccessResponse<SecurityPolicy>>, AppError> {
+    let policy = auth_service::get_security_policy(&pool).await?;
+    Ok(Json(SuccessResponse::ok(policy)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/security-policy",
+    request_body = UpdateSecurityPolicy,
+    responses(
+        (status = 200, description = "Authentication policy updated", body = SuccessResponse<SecurityPolicy>),
+        (status = 401, description = "Unauthorized error (admin only)", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn update_security_policy(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Json(payload): Json<UpdateSecurityPolicy>,
+) -> Result<Json<SuccessResponse<SecurityPolicy>>, AppError> {
+    let policy = auth_service::update_security_policy(claims, &pool, payload).await?;
+    Ok(Json(SuccessResponse::ok(policy)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/login-lockouts",
+    responses(
+        (status = 200, description = "Accounts and client IPs with recorded login failures, locked ones first", body = SuccessResponse<Vec<LoginLockout>>),
+        (status = 401, description = "Unauthorized error (admin only)", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn get_login_lockouts(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+) -> Result<Json<SuccessResponse<Vec<LoginLockout>>>, AppError> {
+    let lockouts = auth_service::get_login_lockouts(claims, &pool).await?;
+    Ok(Json(SuccessResponse::ok(lockouts)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/users/{id}/lockout",
+    params(("id" = Uuid, Path, description = "User ID")),
+    responses(
+        (status = 204, description = "Login failures and lockout of the user cleared"),
+        (status = 401, description = "Unauthorized error (admin only)", body = ErrorResponse),
+        (status = 404, description = "User not found, or no login failures recorded", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn unlock_user(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<()>>, AppError> {
+    auth_service::unlock_user(claims, &pool, id).await?;
+    Ok(Json(SuccessResponse::no_content()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/login-lockouts/ips/{ip}",
+    params(("ip" = String, Path, description = "Client IP address")),
+    responses(
+        (status = 204, description = "Login failures and lockout of the IP address cleared"),
+        (status = 401, description = "Unauthorized error (admin only)", body = ErrorResponse),
+        (status = 404, description = "No login failures recorded for the IP address", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn unlock_ip(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(ip): Path<IpAddr>,
+) -> Result<Json<SuccessResponse<()>>, AppError> {
+    auth_service::unlock_ip(claims, &pool, ip).await?;
+    Ok(Json(SuccessResponse::no_content()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/me/password",
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = 204, description = "Password changed, and every other session revoked"),
+        (status = 400, description = "Validation error (new password does not meet the password policy, or no local password)", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized (wrong current password)", body = ErrorResponse),
+        (status = 429, description = "Too many failed attempts for the account or client IP (see `Retry-After`)", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn change_password(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    ClientIp(client_ip): ClientIp,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<Json<SuccessResponse<()>>, AppError> {
+    auth_service::change_password(claims, &pool, payload, client_ip).await?;
+    Ok(Json(SuccessResponse::no_content()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/{id}/password-reset",
+    params(("id" = Uuid, Path, description = "User ID")),
+    responses(
+        (status = 201, description = "One-time password reset token issued (replaces any earlier token)", body = SuccessResponse<PasswordResetToken>),
+        (status = 400, description = "Validation error (the user has no local password)", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized error (admin only)", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"],
+    security(("bearerAuth" = []))
+)]
+pub async fn create_password_reset(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<PasswordResetToken>>, AppError> {
+    let reset = auth_service::create_password_reset(claims, &pool, id).await?;
+    Ok(Json(SuccessResponse::created(reset)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/password-reset",
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = 204, description = "Password set, every session revoked and the login lockout cleared"),
+        (status = 400, description = "Validation error (new password does not meet the password policy)", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized (unknown, used or expired reset token)", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tags = ["auth"]
+)]
+pub async fn reset_password(
+    State(pool): State<PgPool>,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> Result<Json<SuccessResponse<()>>, AppError> {
+    auth_service::reset_password(&pool, payload).await?;
+    Ok(Json(SuccessResponse::no_content()))
+}
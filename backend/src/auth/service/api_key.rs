@@ -0,0 +1,306 @@
+use crate::audit::domain::{AuditAction, AuditEntity};
+use crate::audit::service::{record_audit, snapshot};
+use crate::auth::domain::{ApiKey, ApiKeyScope, Claims, CreatedApiKey, NewApiKey, Role};
+use crate::errors::app_error::AppError;
+use crate::errors::validation::{extract_validation_errors, field_validation_error};
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::token::{generate_secret, hash_token};
+
+/// API キーの接頭辞。ログなどに紛れたキーを見つけやすくする
+const KEY_PREFIX: &str = "plm_";
+
+struct ApiKeyRow {
+    id: Uuid,
+    name: String,
+    prefix: String,
+    scopes: Vec<String>,
+    created_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<ApiKeyRow> for ApiKey {
+    fn from(row: ApiKeyRow) -> Self {
+        ApiKey {
+            id: row.id,
+            name: row.name,
+            prefix: row.prefix,
+            scopes: row
+                .scopes
+                .iter()
+                .filter_map(|scope| ApiKeyScope::parse(scope))
+                .collect(),
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+            last_used_at: row.last_used_at,
+            revoked_at: row.revoked_at,
+        }
+    }
+}
+
+pub async fn create_api_key(
+    claims: Claims,
+    pool: &PgPool,
+    new_key: NewApiKey,
+) -> Result<CreatedApiKey, AppError> {
+    new_key
+        .validate()
+        .map_err(|e| AppError::ValidationError(extract_validation_errors(e)))?;
+    if new_key
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(AppError::ValidationError(field_validation_error(
+            "expires_at",
+            "expires_at must be in the future",
+        )));
+    }
+
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let key = format!("{}{}", KEY_PREFIX, generate_secret());
+    let scopes = new_key
+        .scopes
+        .iter()
+        .map(|scope| scope.as_str().to_string())
+        .collect::<Vec<_>>();
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to create API key".to_string())
+    })?;
+
+    let row = sqlx::query_as!(
+        ApiKeyRow,
+        r#"INSERT INTO api_keys (user_id, name, prefix, key_hash, scopes, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, name, prefix, scopes, created_at, expires_at, last_used_at, revoked_at
+        "#,
+        user_id,
+        new_key.name,
+        &key[..KEY_PREFIX.len() + 8],
+        hash_token(&key),
+        &scopes,
+        new_key.expires_at
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during creating API key: {}", e);
+        AppError::DatabaseError("Failed to create API key".to_string())
+    })?;
+
+    record_audit(
+        &mut tx,
+        AuditEntity::ApiKey,
+        row.id,
+        AuditAction::Create,
+        Some(user_id),
+        None,
+    )
+    .await?;
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing API key: {}", e);
+        AppError::DatabaseError("Failed to create API key".to_string())
+    })?;
+
+    info!("API key {} created for user {}", row.id, user_id);
+    Ok(CreatedApiKey {
+        api_key: row.into(),
+        key,
+    })
+}
+
+/// 自分の API キーの一覧 (失効・期限切れのものを含む)
+pub async fn get_api_keys(claims: Claims, pool: &PgPool) -> Result<Vec<ApiKey>, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let rows = sqlx::query_as!(
+        ApiKeyRow,
+        r#"SELECT id, name, prefix, scopes, created_at, expires_at, last_used_at, revoked_at
+        FROM api_keys
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching API keys: {}", e);
+        AppError::DatabaseError("Failed to fetch API keys".to_string())
+    })?;
+
+    Ok(rows.into_iter().map(ApiKey::from).collect())
+}
+
+pub async fn revoke_api_key(claims: Claims, pool: &PgPool, id: Uuid) -> Result<(), AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to revoke API key".to_string())
+    })?;
+
+    let owned = sqlx::query_scalar!(
+        r#"SELECT id FROM api_keys WHERE id = $1 AND user_id = $2 FOR UPDATE"#,
+        id,
+        user_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during revoking API key: {}", e);
+        AppError::DatabaseError("Failed to revoke API key".to_string())
+    })?;
+    if owned.is_none() {
+        return Err(AppError::NotFound(format!("API key not found: {}", id)));
+    }
+
+    let before = snapshot(&mut tx, AuditEntity::ApiKey, id).await?;
+    sqlx::query!(
+        r#"UPDATE api_keys SET revoked_at = COALESCE(revoked_at, NOW()) WHERE id = $1"#,
+        id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during revoking API key: {}", e);
+        AppError::DatabaseError("Failed to revoke API key".to_string())
+    })?;
+    record_audit(
+        &mut tx,
+        AuditEntity::ApiKey,
+        id,
+        AuditAction::Update,
+        Some(user_id),
+        before,
+    )
+    .await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing API key revocation: {}", e);
+        AppError::DatabaseError("Failed to revoke API key".to_string())
+    })?;
+    info!("API key {} revoked by user {}", id, user_id);
+    Ok(())
+}
+
+/// ユーザーの有効な API キーをすべて失効させて監査ログに残す。失効させたキーの id を返す
+pub async fn revoke_user_api_keys(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    actor_id: Option<Uuid>,
+) -> Result<Vec<Uuid>, AppError> {
+    let ids = sqlx::query_scalar!(
+        r#"SELECT id FROM api_keys WHERE user_id = $1 AND revoked_at IS NULL FOR UPDATE"#,
+        user_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching API keys: {}", e);
+        AppError::DatabaseError("Failed to revoke API keys".to_string())
+    })?;
+
+    for id in &ids {
+        let before = snapshot(&mut *conn, AuditEntity::ApiKey, *id).await?;
+        sqlx::query!(
+            r#"UPDATE api_keys SET revoked_at = NOW() WHERE id = $1"#,
+            id
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            error!("DB error during revoking API key: {}", e);
+            AppError::DatabaseError("Failed to revoke API keys".to_string())
+        })?;
+        record_audit(
+            &mut *conn,
+            AuditEntity::ApiKey,
+            *id,
+            AuditAction::Update,
+            actor_id,
+            before,
+        )
+        .await?;
+    }
+    Ok(ids)
+}
+
+/// `Authorization: ApiKey ...` のキーを検証し、キーの持ち主の `Claims` と許可されたスコープを返す
+pub async fn authenticate_api_key(
+    pool: &PgPool,
+    key: &str,
+) -> Result<(Claims, Vec<ApiKeyScope>), AppError> {
+    // 二要素認証が必須なのに未登録の admin のキーは、ログインと同じく使わせない。
+    // 最終使用日時はリクエストごとに書き込まないよう、1 分に 1 回だけ更新する
+    let found = sqlx::query!(
+        r#"WITH found AS (
+            SELECT k.id, k.user_id, k.scopes, k.expires_at, u.role,
+                u.role = 'admin'
+                    AND COALESCE((SELECT require_admin_mfa FROM security_policy), FALSE)
+                    AND NOT EXISTS (
+                        SELECT 1 FROM user_totp t WHERE t.user_id = u.id AND t.enabled_at IS NOT NULL
+                    ) AS mfa_enrollment_required
+            FROM api_keys k
+            JOIN users u ON u.id = k.user_id
+            WHERE k.key_hash = $1
+              AND k.revoked_at IS NULL
+              AND (k.expires_at IS NULL OR k.expires_at > NOW())
+        ), used AS (
+            UPDATE api_keys SET last_used_at = NOW()
+            WHERE id IN (SELECT id FROM found WHERE NOT mfa_enrollment_required)
+              AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '1 minute')
+        )
+        SELECT id, user_id, scopes, expires_at, role,
+            mfa_enrollment_required AS "mfa_enrollment_required!"
+        FROM found
+        "#,
+        hash_token(key)
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during checking API key: {}", e);
+        AppError::DatabaseError("Failed to check API key".to_string())
+    })?
+    .ok_or_else(|| AppError::Unauthorized("Invalid or expired API key.".to_string()))?;
+
+    if found.mfa_enrollment_required {
+        info!(
+            "Rejected API key {} of admin {} without two-factor authentication",
+            found.id, found.user_id
+        );
+        return Err(AppError::Unauthorized(
+            "Two-factor authentication is required for admins. Set it up before using API keys."
+                .to_string(),
+        ));
+    }
+
+    let claims = Claims {
+        sub: found.user_id.to_string(),
+        role: Role::from(found.role.as_str()),
+        exp: found
+            .expires_at
+            .map_or(usize::MAX, |expires_at| expires_at.timestamp() as usize),
+        jti: found.id,
+        mfa_enrollment_required: false,
+    };
+    let scopes = found
+        .scopes
+        .iter()
+        .filter_map(|scope| ApiKeyScope::parse(scope))
+        .collect();
+    Ok((claims, scopes))
+}
@@ -0,0 +1,118 @@
+use crate::audit::domain::{AuditAction, AuditEntity};
+use crate::audit::service::{record_audit, snapshot};
+use crate::errors::app_error::AppError;
+
+use sqlx::PgConnection;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::user_create::insert_user;
+
+/// 外部の認証基盤 (OIDC / LDAP) で認証されたユーザー
+#[derive(Debug)]
+pub struct ExternalIdentity<'a> {
+    /// OIDC なら issuer、LDAP なら `ldap:<LDAP_DIRECTORY_ID>`
+    pub issuer: &'a str,
+    /// OIDC なら `sub`、LDAP ならエントリの `entryUUID` / `objectGUID`
+    pub subject: &'a str,
+    pub login_name: &'a str,
+    /// None ならロールを同期しない
+    pub role: Option<&'a str>,
+    pub display_name: Option<&'a str>,
+    pub email: Option<&'a str>,
+}
+
+/// 外部のユーザーに対応するローカルユーザーを返す。いなければ作成する (JIT プロビジョニング)。
+/// 既存のユーザーはロール・表示名・メールアドレスを同期し、変更があれば監査ログに記録する。
+pub async fn find_or_provision_user(
+    conn: &mut PgConnection,
+    identity: &ExternalIdentity<'_>,
+) -> Result<(Uuid, String), AppError> {
+    let linked = sqlx::query!(
+        r#"UPDATE user_identities i
+        SET last_login_at = NOW()
+        FROM users u
+        WHERE u.id = i.user_id AND i.issuer = $1 AND i.subject = $2
+        RETURNING u.id, u.role, u.display_name, u.email
+        "#,
+        identity.issuer,
+        identity.subject
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching user identity: {}", e);
+        AppError::DatabaseError("Failed to complete external login".to_string())
+    })?;
+
+    let Some(user) = linked else {
+        let role = identity.role.unwrap_or("user");
+        let user_id = insert_user(
+            &mut *conn,
+            identity.login_name,
+            None,
+            role,
+            identity.display_name,
+            identity.email,
+        )
+        .await?;
+        sqlx::query!(
+            r#"INSERT INTO user_identities (issuer, subject, user_id) VALUES ($1, $2, $3)"#,
+            identity.issuer,
+            identity.subject,
+            user_id
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            error!("DB error during linking user identity: {}", e);
+            AppError::DatabaseError("Failed to complete external login".to_string())
+        })?;
+        info!(
+            "Provisioned user {} for {} subject {}",
+            user_id, identity.issuer, identity.subject
+        );
+        return Ok((user_id, role.to_string()));
+    };
+
+    let changed =
+        |new: Option<&str>, current: Option<&str>| new.is_some_and(|new| Some(new) != current);
+    if !changed(identity.role, Some(&user.role))
+        && !changed(identity.display_name, user.display_name.as_deref())
+        && !changed(identity.email, user.email.as_deref())
+    {
+        return Ok((user.id, user.role));
+    }
+
+    let before = snapshot(&mut *conn, AuditEntity::User, user.id).await?;
+    let role = sqlx::query_scalar!(
+        r#"UPDATE users
+        SET role = COALESCE($2, role),
+            display_name = COALESCE($3, display_name),
+            email = COALESCE($4, email)
+        WHERE id = $1
+        RETURNING role
+        "#,
+        user.id,
+        identity.role,
+        identity.display_name,
+        identity.email
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during syncing user {}: {}", user.id, e);
+        AppError::DatabaseError("Failed to complete external login".to_string())
+    })?;
+    record_audit(
+        conn,
+        AuditEntity::User,
+        user.id,
+        AuditAction::Update,
+        None,
+        before,
+    )
+    .await?;
+    info!("Synced user {} from {}", user.id, identity.issuer);
+    Ok((user.id, role))
+}
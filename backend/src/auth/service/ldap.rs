@@ -0,0 +1,49 @@
+use crate::auth::domain::{LoginOutcome, LoginRequest};
+use crate::auth::ldap::LdapConfig;
+use crate::errors::app_error::AppError;
+
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use super::identity::{ExternalIdentity, find_or_provision_user};
+use super::mfa::complete_login;
+
+/// ディレクトリにユーザー本人としてバインドしてログインさせる。
+/// 初めてのユーザーはその場で作成し、表示名・メールアドレス・グループから決めたロールを同期する。
+pub async fn ldap_login(
+    pool: &PgPool,
+    config: &LdapConfig,
+    payload: &LoginRequest,
+) -> Result<LoginOutcome, AppError> {
+    let entry = config
+        .authenticate(&payload.login_name, &payload.password)
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("Invalid login credentials.".to_string()))?;
+    let role = config.role_from_groups(&entry.groups)?;
+
+    let issuer = format!("ldap:{}", config.directory_id);
+    let identity = ExternalIdentity {
+        issuer: &issuer,
+        subject: &entry.id,
+        login_name: &payload.login_name,
+        role: role.as_ref().map(|role| role.as_str()),
+        display_name: entry.display_name.as_deref(),
+        email: entry.email.as_deref(),
+    };
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to complete LDAP login".to_string())
+    })?;
+
+    let (user_id, role) = find_or_provision_user(&mut tx, &identity).await?;
+    let outcome = complete_login(&mut tx, user_id, &role).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing LDAP login: {}", e);
+        AppError::DatabaseError("Failed to complete LDAP login".to_string())
+    })?;
+
+    info!("User {} logged in via LDAP", user_id);
+    Ok(outcome)
+}
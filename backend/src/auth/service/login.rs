@@ -1,22 +1,51 @@
+use std::net::IpAddr;
+use std::sync::LazyLock;
+
 use crate::{
     auth::{
-        domain::{Claims, LoginRequest, LoginResponse, Role},
-        jwt::generate_jwt,
-        password::verify_password,
+        domain::{LoginOutcome, LoginRequest},
+        ldap::LdapConfig,
+        password::{hash_password, verify_password},
     },
     models::user::User,
 };
 
 use sqlx::PgPool;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::error;
 
+use super::ldap::ldap_login;
+use super::mfa::complete_login;
+use super::throttle;
+
 use crate::errors::app_error::AppError;
 
-pub async fn login(pool: &PgPool, payload: LoginRequest) -> Result<LoginResponse, AppError> {
+/// ユーザーがいないときにも照合する捨てのハッシュ。応答時間でユーザーの有無が分からないようにする
+static DUMMY_PASSWORD_HASH: LazyLock<String> =
+    LazyLock::new(|| hash_password("dummy-password").unwrap_or_default());
+
+/// ロック中でなければ認証し、失敗 (401) をアカウントと接続元 IP ごとに数える
+pub async fn login(
+    pool: &PgPool,
+    payload: LoginRequest,
+    client_ip: Option<IpAddr>,
+) -> Result<LoginOutcome, AppError> {
+    let attempt = throttle::begin_attempt(pool, &payload.login_name, client_ip).await?;
+
+    let result = authenticate(pool, &payload).await;
+    match &result {
+        Ok(LoginOutcome::Tokens(_)) => attempt.succeeded(pool).await?,
+        // 二要素認証が続く場合、アカウントの失敗回数は `/login/mfa` でトークンを発行するまで残す
+        Ok(LoginOutcome::MfaRequired(_)) => attempt.release(pool).await?,
+        Err(AppError::Unauthorized(_)) => attempt.failed(pool).await?,
+        Err(_) => attempt.release(pool).await?,
+    }
+    result
+}
+
+async fn authenticate(pool: &PgPool, payload: &LoginRequest) -> Result<LoginOutcome, AppError> {
     let user = sqlx::query_as!(
         User,
-        r#"SELECT id, login_name, password_hash, role, created_at
+        r#"SELECT id, login_name, password_hash, role, display_name, email, created_at
         FROM users
         WHERE login_name = $1"#,
         payload.login_name,
@@ -26,24 +55,28 @@ pub async fn login(pool: &PgPool, payload: LoginRequest) -> Result<LoginResponse
     .map_err(|e| {
         error!("Login failed: fetch user from database: {}", e);
         AppError::DatabaseError("Login failed: fetch user".to_string())
-    })?
-    .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
-
-    verify_password(&payload.password, &user.password_hash)?;
-
-    let expiration = SystemTime::now()
-        .checked_add(Duration::from_secs(60 * 60))
-        .unwrap()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as usize;
-
-    let claims = Claims {
-        sub: user.id.to_string(),
-        role: Role::from(user.role.as_str()),
-        exp: expiration,
+    })?;
+
+    // パスワードを持つローカルユーザーはローカルで、それ以外は LDAP が設定されていれば LDAP で認証する
+    let user = match (user, LdapConfig::from_env()) {
+        (Some(user), _) if user.password_hash.is_some() => user,
+        (_, Some(config)) => return ldap_login(pool, &config, payload).await,
+        // ユーザーがいない場合もパスワード違いと同じ応答にする
+        (_, None) => {
+            let _ = verify_password(&payload.password, &DUMMY_PASSWORD_HASH);
+            return Err(AppError::Unauthorized(
+                "Invalid login credentials.".to_string(),
+            ));
+        }
     };
 
-    let token = generate_jwt(claims)?;
-    Ok(LoginResponse { token })
+    let password_hash = user.password_hash.as_deref().unwrap_or_default();
+    verify_password(&payload.password, password_hash)?;
+
+    let mut conn = pool.acquire().await.map_err(|e| {
+        error!("Login failed: acquire connection: {}", e);
+        AppError::DatabaseError("Login failed: issue tokens".to_string())
+    })?;
+
+    complete_login(&mut conn, user.id, &user.role).await
 }
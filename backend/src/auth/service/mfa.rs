@@ -0,0 +1,636 @@
+use std::net::IpAddr;
+
+use crate::audit::domain::{AuditAction, AuditEntity};
+use crate::audit::service::{record_audit, snapshot};
+use crate::auth::domain::{
+    Claims, LoginOutcome, LoginResponse, MfaChallenge, MfaLoginRequest, MfaStatus, RecoveryCodes,
+    Role, TotpCode, TotpEnrollment,
+};
+use crate::auth::totp;
+use crate::errors::app_error::AppError;
+use crate::errors::conflict::conflict_error;
+
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::throttle;
+use super::token::{generate_secret, hash_token, issue_tokens};
+
+/// パスワード確認後に二要素認証のコードを待つ時間 (分)
+const MFA_CHALLENGE_TTL_MINUTES: i32 = 5;
+/// 1 回のログインで試せるコードの回数
+const MFA_MAX_ATTEMPTS: i32 = 5;
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// 認証アプリに表示される発行者名 (`TOTP_ISSUER`, default "PLM")
+fn totp_issuer() -> String {
+    std::env::var("TOTP_ISSUER")
+        .ok()
+        .filter(|issuer| !issuer.is_empty())
+        .unwrap_or_else(|| "PLM".to_string())
+}
+
+fn user_id_of(claims: &Claims) -> Result<Uuid, AppError> {
+    Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))
+}
+
+fn invalid_code() -> AppError {
+    AppError::Unauthorized("Invalid authentication code.".to_string())
+}
+
+/// ポリシーで二要素認証が必須なのに、まだ有効にしていないか (admin ロールのユーザーについて呼ぶ)
+pub async fn mfa_enrollment_required(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+) -> Result<bool, AppError> {
+    sqlx::query_scalar!(
+        r#"SELECT COALESCE((SELECT require_admin_mfa FROM security_policy), FALSE)
+            AND NOT EXISTS (
+                SELECT 1 FROM user_totp WHERE user_id = $1 AND enabled_at IS NOT NULL
+            ) AS "required!"
+        "#,
+        user_id
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during checking two-factor policy: {}", e);
+        AppError::DatabaseError("Failed to check two-factor policy".to_string())
+    })
+}
+
+/// 本人確認の済んだユーザーのログインを完了する。
+/// 二要素認証が有効なら、トークンの代わりに `POST /login/mfa` で使うチャレンジを返す。
+pub async fn complete_login(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    role: &str,
+) -> Result<LoginOutcome, AppError> {
+    let enabled = sqlx::query_scalar!(
+        r#"SELECT EXISTS (
+            SELECT 1 FROM user_totp WHERE user_id = $1 AND enabled_at IS NOT NULL
+        ) AS "enabled!""#,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during checking two-factor authentication: {}", e);
+        AppError::DatabaseError("Failed to check two-factor authentication".to_string())
+    })?;
+
+    if !enabled {
+        // ログインごとに新しいリフレッシュトークンの系列を始める
+        return issue_tokens(conn, user_id, role, Uuid::new_v4())
+            .await
+            .map(LoginOutcome::Tokens);
+    }
+
+    sqlx::query!(
+        r#"DELETE FROM mfa_challenges WHERE created_at < NOW() - make_interval(mins => $1)"#,
+        MFA_CHALLENGE_TTL_MINUTES
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during starting two-factor login: {}", e);
+        AppError::DatabaseError("Failed to start two-factor login".to_string())
+    })?;
+
+    let mfa_token = generate_secret();
+    sqlx::query!(
+        r#"INSERT INTO mfa_challenges (token_hash, user_id) VALUES ($1, $2)"#,
+        hash_token(&mfa_token),
+        user_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during starting two-factor login: {}", e);
+        AppError::DatabaseError("Failed to start two-factor login".to_string())
+    })?;
+
+    info!("User {} passed the first login step", user_id);
+    Ok(LoginOutcome::MfaRequired(MfaChallenge {
+        mfa_required: true,
+        mfa_token,
+        expires_in: MFA_CHALLENGE_TTL_MINUTES as u64 * 60,
+    }))
+}
+
+/// ログインの 2 段階目。認証アプリのコードかリカバリーコードを確認してトークンを発行する。
+/// 失敗が `MFA_MAX_ATTEMPTS` 回に達したチャレンジは破棄し、パスワードからやり直させる。
+pub async fn verify_mfa_login(
+    pool: &PgPool,
+    payload: MfaLoginRequest,
+    client_ip: Option<IpAddr>,
+) -> Result<LoginResponse, AppError> {
+    // ロックの確認と試行の記録は、トランザクションで接続を確保する前に済ませる
+    let login_name = sqlx::query_scalar!(
+        r#"SELECT u.login_name
+        FROM mfa_challenges c
+        JOIN users u ON u.id = c.user_id
+        WHERE c.token_hash = $1 AND c.created_at >= NOW() - make_interval(mins => $2)
+        "#,
+        hash_token(&payload.mfa_token),
+        MFA_CHALLENGE_TTL_MINUTES
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during completing two-factor login: {}", e);
+        AppError::DatabaseError("Failed to complete two-factor login".to_string())
+    })?
+    .ok_or_else(|| AppError::Unauthorized("Invalid or expired MFA token.".to_string()))?;
+
+    let attempt = throttle::begin_attempt(pool, &login_name, client_ip).await?;
+    let result = finish_mfa_login(pool, &payload).await;
+    match &result {
+        Ok(_) => attempt.succeeded(pool).await?,
+        Err(AppError::Unauthorized(_)) => attempt.failed(pool).await?,
+        Err(_) => attempt.release(pool).await?,
+    }
+    result
+}
+
+async fn finish_mfa_login(
+    pool: &PgPool,
+    payload: &MfaLoginRequest,
+) -> Result<LoginResponse, AppError> {
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to complete two-factor login".to_string())
+    })?;
+
+    let challenge = sqlx::query!(
+        r#"SELECT c.token_hash, c.user_id, c.attempts, u.role
+        FROM mfa_challenges c
+        JOIN users u ON u.id = c.user_id
+        WHERE c.token_hash = $1 AND c.created_at >= NOW() - make_interval(mins => $2)
+        FOR UPDATE OF c
+        "#,
+        hash_token(&payload.mfa_token),
+        MFA_CHALLENGE_TTL_MINUTES
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during completing two-factor login: {}", e);
+        AppError::DatabaseError("Failed to complete two-factor login".to_string())
+    })?
+    .ok_or_else(|| AppError::Unauthorized("Invalid or expired MFA token.".to_string()))?;
+
+    if !verify_second_factor(&mut tx, challenge.user_id, &payload.code, true).await? {
+        if challenge.attempts + 1 >= MFA_MAX_ATTEMPTS {
+            sqlx::query!(
+                r#"DELETE FROM mfa_challenges WHERE token_hash = $1"#,
+                challenge.token_hash
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!("DB error during completing two-factor login: {}", e);
+                AppError::DatabaseError("Failed to complete two-factor login".to_string())
+            })?;
+        } else {
+            sqlx::query!(
+                r#"UPDATE mfa_challenges SET attempts = attempts + 1 WHERE token_hash = $1"#,
+                challenge.token_hash
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!("DB error during completing two-factor login: {}", e);
+                AppError::DatabaseError("Failed to complete two-factor login".to_string())
+            })?;
+        }
+        tx.commit().await.map_err(|e| {
+            error!("DB error during committing two-factor login: {}", e);
+            AppError::DatabaseError("Failed to complete two-factor login".to_string())
+        })?;
+        info!("Invalid MFA code for user {}", challenge.user_id);
+        return Err(invalid_code());
+    }
+
+    sqlx::query!(
+        r#"DELETE FROM mfa_challenges WHERE token_hash = $1"#,
+        challenge.token_hash
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during completing two-factor login: {}", e);
+        AppError::DatabaseError("Failed to complete two-factor login".to_string())
+    })?;
+
+    let response =
+        issue_tokens(&mut tx, challenge.user_id, &challenge.role, Uuid::new_v4()).await?;
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing two-factor login: {}", e);
+        AppError::DatabaseError("Failed to complete two-factor login".to_string())
+    })?;
+
+    info!("User {} completed two-factor login", challenge.user_id);
+    Ok(response)
+}
+
+/// 有効な TOTP のコード (`allow_recovery` ならリカバリーコードも) を確認し、使用済みにする
+async fn verify_second_factor(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    code: &str,
+    allow_recovery: bool,
+) -> Result<bool, AppError> {
+    let Some(totp) = sqlx::query!(
+        r#"SELECT secret, last_used_step
+        FROM user_totp
+        WHERE user_id = $1 AND enabled_at IS NOT NULL
+        FOR UPDATE
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during verifying authentication code: {}", e);
+        AppError::DatabaseError("Failed to verify authentication code".to_string())
+    })?
+    else {
+        return Ok(false);
+    };
+
+    if let Some(step) = totp::verify(&totp.secret, code, totp.last_used_step) {
+        sqlx::query!(
+            r#"UPDATE user_totp SET last_used_step = $1 WHERE user_id = $2"#,
+            step,
+            user_id
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            error!("DB error during verifying authentication code: {}", e);
+            AppError::DatabaseError("Failed to verify authentication code".to_string())
+        })?;
+        return Ok(true);
+    }
+
+    if !allow_recovery {
+        return Ok(false);
+    }
+    let used = sqlx::query!(
+        r#"UPDATE totp_recovery_codes
+        SET used_at = NOW()
+        WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+        "#,
+        user_id,
+        hash_token(&totp::normalize_recovery_code(code))
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during verifying authentication code: {}", e);
+        AppError::DatabaseError("Failed to verify authentication code".to_string())
+    })?;
+
+    if used.rows_affected() > 0 {
+        info!("User {} used a recovery code", user_id);
+    }
+    Ok(used.rows_affected() > 0)
+}
+
+/// リカバリーコードを作り直す。以前のコードは使えなくなる
+async fn replace_recovery_codes(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+) -> Result<RecoveryCodes, AppError> {
+    let recovery_codes = totp::generate_recovery_codes(RECOVERY_CODE_COUNT);
+    let hashes: Vec<String> = recovery_codes.iter().map(|code| hash_token(code)).collect();
+
+    sqlx::query!(
+        r#"DELETE FROM totp_recovery_codes WHERE user_id = $1"#,
+        user_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during storing recovery codes: {}", e);
+        AppError::DatabaseError("Failed to store recovery codes".to_string())
+    })?;
+
+    sqlx::query!(
+        r#"INSERT INTO totp_recovery_codes (user_id, code_hash)
+        SELECT $1, UNNEST($2::text[])
+        "#,
+        user_id,
+        &hashes
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during storing recovery codes: {}", e);
+        AppError::DatabaseError("Failed to store recovery codes".to_string())
+    })?;
+
+    Ok(RecoveryCodes { recovery_codes })
+}
+
+pub async fn get_mfa_status(claims: Claims, pool: &PgPool) -> Result<MfaStatus, AppError> {
+    let user_id = user_id_of(&claims)?;
+    let status = sqlx::query!(
+        r#"SELECT
+            (SELECT enabled_at FROM user_totp WHERE user_id = $1) AS enabled_at,
+            (SELECT COUNT(*) FROM totp_recovery_codes
+             WHERE user_id = $1 AND used_at IS NULL) AS "recovery_codes_remaining!",
+            COALESCE((SELECT require_admin_mfa FROM security_policy), FALSE) AS "require_admin_mfa!"
+        "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching two-factor status: {}", e);
+        AppError::DatabaseError("Failed to fetch two-factor status".to_string())
+    })?;
+
+    Ok(MfaStatus {
+        enabled: status.enabled_at.is_some(),
+        enabled_at: status.enabled_at,
+        recovery_codes_remaining: status.recovery_codes_remaining,
+        required: claims.role == Role::Admin && status.require_admin_mfa,
+    })
+}
+
+/// TOTP の登録を始める。確認前の共有鍵があれば作り直す
+pub async fn start_totp_enrollment(
+    claims: Claims,
+    pool: &PgPool,
+) -> Result<TotpEnrollment, AppError> {
+    let user_id = user_id_of(&claims)?;
+    let secret = totp::generate_secret();
+
+    let login_name = sqlx::query_scalar!(
+        r#"WITH enrollment AS (
+            INSERT INTO user_totp (user_id, secret) VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE
+            SET secret = EXCLUDED.secret, last_used_step = NULL, created_at = NOW()
+            WHERE user_totp.enabled_at IS NULL
+            RETURNING user_id
+        )
+        SELECT u.login_name FROM users u JOIN enrollment e ON e.user_id = u.id
+        "#,
+        user_id,
+        secret
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during starting two-factor enrollment: {}", e);
+        AppError::DatabaseError("Failed to start two-factor enrollment".to_string())
+    })?
+    .ok_or_else(|| conflict_error("mfa", "Two-factor authentication is already enabled.", None))?;
+
+    info!("User {} started TOTP enrollment", user_id);
+    Ok(TotpEnrollment {
+        otpauth_uri: totp::provisioning_uri(&totp_issuer(), &login_name, &secret),
+        secret,
+    })
+}
+
+/// 認証アプリのコードで登録を確認して二要素認証を有効にし、リカバリーコードを返す
+pub async fn confirm_totp_enrollment(
+    claims: Claims,
+    pool: &PgPool,
+    payload: TotpCode,
+) -> Result<RecoveryCodes, AppError> {
+    let user_id = user_id_of(&claims)?;
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to enable two-factor authentication".to_string())
+    })?;
+
+    let pending = sqlx::query!(
+        r#"SELECT secret, enabled_at FROM user_totp WHERE user_id = $1 FOR UPDATE"#,
+        user_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during enabling two-factor authentication: {}", e);
+        AppError::DatabaseError("Failed to enable two-factor authentication".to_string())
+    })?
+    .ok_or_else(|| {
+        AppError::NotFound("No TOTP enrollment in progress. Call POST /mfa/totp first.".to_string())
+    })?;
+    if pending.enabled_at.is_some() {
+        return Err(conflict_error(
+            "mfa",
+            "Two-factor authentication is already enabled.",
+            None,
+        ));
+    }
+
+    let step = totp::verify(&pending.secret, &payload.code, None).ok_or_else(invalid_code)?;
+    let before = snapshot(&mut tx, AuditEntity::Totp, user_id).await?;
+    sqlx::query!(
+        r#"UPDATE user_totp SET enabled_at = NOW(), last_used_step = $1 WHERE user_id = $2"#,
+        step,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during enabling two-factor authentication: {}", e);
+        AppError::DatabaseError("Failed to enable two-factor authentication".to_string())
+    })?;
+
+    let recovery_codes = replace_recovery_codes(&mut tx, user_id).await?;
+    record_audit(
+        &mut tx,
+        AuditEntity::Totp,
+        user_id,
+        AuditAction::Update,
+        Some(user_id),
+        before,
+    )
+    .await?;
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing two-factor enrollment: {}", e);
+        AppError::DatabaseError("Failed to enable two-factor authentication".to_string())
+    })?;
+
+    info!("User {} enabled TOTP", user_id);
+    Ok(recovery_codes)
+}
+
+/// 本人のコードを確認して二要素認証を無効にする。ポリシーで必須の admin は無効にできない
+pub async fn disable_totp(
+    claims: Claims,
+    pool: &PgPool,
+    payload: TotpCode,
+) -> Result<(), AppError> {
+    let user_id = user_id_of(&claims)?;
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to disable two-factor authentication".to_string())
+    })?;
+
+    if claims.role == Role::Admin {
+        let required = sqlx::query_scalar!(
+            r#"SELECT COALESCE((SELECT require_admin_mfa FROM security_policy), FALSE)
+                AS "required!"
+            "#
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("DB error during fetching security policy: {}", e);
+            AppError::DatabaseError("Failed to disable two-factor authentication".to_string())
+        })?;
+        if required {
+            info!("Admin {} cannot disable required TOTP", user_id);
+            return Err(AppError::Unauthorized(
+                "Two-factor authentication is required for admins.".to_string(),
+            ));
+        }
+    }
+
+    if !verify_second_factor(&mut tx, user_id, &payload.code, true).await? {
+        return Err(invalid_code());
+    }
+    let before = snapshot(&mut tx, AuditEntity::Totp, user_id).await?;
+    remove_second_factor(&mut tx, user_id).await?;
+    record_audit(
+        &mut tx,
+        AuditEntity::Totp,
+        user_id,
+        AuditAction::Delete,
+        Some(user_id),
+        before,
+    )
+    .await?;
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing two-factor removal: {}", e);
+        AppError::DatabaseError("Failed to disable two-factor authentication".to_string())
+    })?;
+
+    info!("User {} disabled TOTP", user_id);
+    Ok(())
+}
+
+/// 認証アプリのコードを確認してリカバリーコードを作り直す
+pub async fn regenerate_recovery_codes(
+    claims: Claims,
+    pool: &PgPool,
+    payload: TotpCode,
+) -> Result<RecoveryCodes, AppError> {
+    let user_id = user_id_of(&claims)?;
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to regenerate recovery codes".to_string())
+    })?;
+
+    if !verify_second_factor(&mut tx, user_id, &payload.code, false).await? {
+        return Err(invalid_code());
+    }
+    let before = snapshot(&mut tx, AuditEntity::Totp, user_id).await?;
+    let recovery_codes = replace_recovery_codes(&mut tx, user_id).await?;
+    record_audit(
+        &mut tx,
+        AuditEntity::Totp,
+        user_id,
+        AuditAction::Update,
+        Some(user_id),
+        before,
+    )
+    .await?;
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing recovery codes: {}", e);
+        AppError::DatabaseError("Failed to regenerate recovery codes".to_string())
+    })?;
+
+    info!("User {} regenerated recovery codes", user_id);
+    Ok(recovery_codes)
+}
+
+/// 端末をなくしたユーザーの二要素認証を解除する (管理者のみ)
+pub async fn reset_user_mfa(claims: Claims, pool: &PgPool, user_id: Uuid) -> Result<(), AppError> {
+    if claims.role != Role::Admin {
+        info!(
+            "User {} is not allowed to reset two-factor authentication",
+            claims.sub
+        );
+        return Err(AppError::Unauthorized(
+            "Only admins can reset two-factor authentication.".to_string(),
+        ));
+    }
+    let admin_id = user_id_of(&claims)?;
+    // 自分の二要素認証は、コードの確認とポリシーのチェックがある DELETE /mfa/totp で外す
+    if admin_id == user_id {
+        info!("Admin {} tried to reset their own TOTP", admin_id);
+        return Err(AppError::Unauthorized(
+            "Use DELETE /mfa/totp to turn off your own two-factor authentication.".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to reset two-factor authentication".to_string())
+    })?;
+    let before = snapshot(&mut tx, AuditEntity::Totp, user_id).await?;
+    if !remove_second_factor(&mut tx, user_id).await? {
+        return Err(AppError::NotFound(format!(
+            "Two-factor authentication is not set up for user: {}",
+            user_id
+        )));
+    }
+    record_audit(
+        &mut tx,
+        AuditEntity::Totp,
+        user_id,
+        AuditAction::Delete,
+        Some(admin_id),
+        before,
+    )
+    .await?;
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing two-factor reset: {}", e);
+        AppError::DatabaseError("Failed to reset two-factor authentication".to_string())
+    })?;
+
+    info!(
+        "Admin {} reset two-factor authentication of user {}",
+        admin_id, user_id
+    );
+    Ok(())
+}
+
+/// TOTP の共有鍵・リカバリーコード・待機中のログインを削除する。共有鍵がなければ false
+async fn remove_second_factor(conn: &mut PgConnection, user_id: Uuid) -> Result<bool, AppError> {
+    let removed = sqlx::query!(r#"DELETE FROM user_totp WHERE user_id = $1"#, user_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            error!("DB error during removing two-factor authentication: {}", e);
+            AppError::DatabaseError("Failed to remove two-factor authentication".to_string())
+        })?;
+    sqlx::query!(
+        r#"DELETE FROM totp_recovery_codes WHERE user_id = $1"#,
+        user_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during removing two-factor authentication: {}", e);
+        AppError::DatabaseError("Failed to remove two-factor authentication".to_string())
+    })?;
+    sqlx::query!(r#"DELETE FROM mfa_challenges WHERE user_id = $1"#, user_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            error!("DB error during removing two-factor authentication: {}", e);
+            AppError::DatabaseError("Failed to remove two-factor authentication".to_string())
+        })?;
+    Ok(removed.rows_affected() > 0)
+}
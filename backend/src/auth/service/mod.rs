@@ -1,6 +1,27 @@
+pub mod api_key;
+pub mod identity;
+pub mod ldap;
 pub mod login;
+pub mod mfa;
+pub mod oidc;
+pub mod password;
+pub mod policy;
+pub mod session;
 pub mod signup;
+pub mod throttle;
+pub mod token;
 pub mod user_create;
 
+pub use api_key::{create_api_key, get_api_keys, revoke_api_key};
 pub use login::login;
+pub use mfa::{
+    confirm_totp_enrollment, disable_totp, get_mfa_status, regenerate_recovery_codes,
+    reset_user_mfa, start_totp_enrollment, verify_mfa_login,
+};
+pub use oidc::{oidc_authorize, oidc_callback, oidc_exchange_code};
+pub use password::{change_password, create_password_reset, reset_password};
+pub use policy::{get_security_policy, update_security_policy};
+pub use session::{get_sessions, revoke_session, revoke_user_sessions};
 pub use signup::signup;
+pub use throttle::{get_login_lockouts, unlock_ip, unlock_user};
+pub use token::{logout, refresh_token};
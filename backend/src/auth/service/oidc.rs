@@ -0,0 +1,319 @@
+use crate::auth::domain::{LoginOutcome, OidcCallbackQuery, OidcCodeRequest};
+use crate::auth::oidc::{
+    OidcConfig, ProviderMetadata, TokenResponse, derive_state_key, find_jwk, http_client,
+    pkce_challenge, provider_metadata, sign_state, verify_state,
+};
+use crate::errors::app_error::AppError;
+
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use super::identity::{ExternalIdentity, find_or_provision_user};
+use super::mfa::complete_login;
+use super::token::{generate_secret, hash_token};
+
+/// 認可リクエストの有効期間 (分)
+const LOGIN_STATE_TTL_MINUTES: i32 = 10;
+
+/// SPA に渡すログインコードの有効期間 (分)
+const LOGIN_CODE_TTL_MINUTES: i32 = 1;
+
+/// state の Cookie に署名する鍵 (`JWT_SECRET` から導出する)
+fn state_key() -> Result<Vec<u8>, AppError> {
+    std::env::var("JWT_SECRET")
+        .map(|secret| derive_state_key(secret.as_bytes()))
+        .map_err(|_| AppError::InternalError("JWT secret is not set.".into()))
+}
+
+fn oidc_config() -> Result<OidcConfig, AppError> {
+    OidcConfig::from_env()
+        .ok_or_else(|| AppError::NotFound("OIDC login is not configured".to_string()))
+}
+
+/// state / nonce / PKCE の code_verifier を保存し、IdP の認可エンドポイントの URL と、
+/// 認可リクエストをこのブラウザに結び付ける署名付きの state の Cookie を返す
+pub async fn oidc_authorize(pool: &PgPool) -> Result<(String, String), AppError> {
+    let config = oidc_config()?;
+    let metadata = provider_metadata(&config).await?;
+
+    let state = generate_secret();
+    let nonce = generate_secret();
+    let code_verifier = generate_secret();
+
+    sqlx::query!(
+        r#"DELETE FROM oidc_login_states WHERE created_at < NOW() - make_interval(mins => $1)"#,
+        LOGIN_STATE_TTL_MINUTES
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during cleaning up OIDC login states: {}", e);
+        AppError::DatabaseError("Failed to start OIDC login".to_string())
+    })?;
+
+    sqlx::query!(
+        r#"INSERT INTO oidc_login_states (state, nonce, code_verifier) VALUES ($1, $2, $3)"#,
+        state,
+        nonce,
+        code_verifier
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during storing OIDC login state: {}", e);
+        AppError::DatabaseError("Failed to start OIDC login".to_string())
+    })?;
+
+    let url = reqwest::Url::parse_with_params(
+        &metadata.authorization_endpoint,
+        &[
+            ("response_type", "code"),
+            ("client_id", config.client_id.as_str()),
+            ("redirect_uri", config.redirect_uri.as_str()),
+            ("scope", config.scopes.as_str()),
+            ("state", state.as_str()),
+            ("nonce", nonce.as_str()),
+            ("code_challenge", pkce_challenge(&code_verifier).as_str()),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map_err(|e| {
+        error!("Invalid OIDC authorization endpoint: {}", e);
+        AppError::InternalError("Invalid OIDC authorization endpoint".to_string())
+    })?;
+
+    let cookie = config.state_cookie(
+        &sign_state(&state, &state_key()?),
+        i64::from(LOGIN_STATE_TTL_MINUTES) * 60,
+    );
+    Ok((url.into(), cookie))
+}
+
+/// 認可コードをトークンに交換し、ID トークンを検証する。
+/// 初めてのユーザーはその場で作成し、ロールのクレームが設定されていればロールを同期する。
+/// トークンは URL に載せず、`POST /oidc/token` で交換する 1 回限りのコードを付けた SPA の URL を返す。
+pub async fn oidc_callback(
+    pool: &PgPool,
+    query: OidcCallbackQuery,
+    state_cookie: Option<&str>,
+) -> Result<String, AppError> {
+    let config = oidc_config()?;
+
+    if let Some(error) = query.error {
+        info!("OIDC login rejected by provider: {}", error);
+        return Err(AppError::Unauthorized(format!(
+            "Login was rejected by the identity provider: {}",
+            query.error_description.unwrap_or(error)
+        )));
+    }
+    let (Some(code), Some(state)) = (query.code, query.state) else {
+        return Err(AppError::Unauthorized(
+            "Missing code or state in OIDC callback.".to_string(),
+        ));
+    };
+    // 認可リクエストを始めたブラウザ以外からのコールバック (ログイン CSRF) を拒否する
+    let key = state_key()?;
+    if state_cookie.and_then(|value| verify_state(value, &key)) != Some(state.as_str()) {
+        return Err(AppError::Unauthorized(
+            "OIDC state does not match this browser.".to_string(),
+        ));
+    }
+
+    let login_state = sqlx::query!(
+        r#"DELETE FROM oidc_login_states
+        WHERE state = $1 AND created_at >= NOW() - make_interval(mins => $2)
+        RETURNING nonce, code_verifier
+        "#,
+        state,
+        LOGIN_STATE_TTL_MINUTES
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching OIDC login state: {}", e);
+        AppError::DatabaseError("Failed to complete OIDC login".to_string())
+    })?
+    .ok_or_else(|| AppError::Unauthorized("Unknown or expired OIDC state.".to_string()))?;
+
+    let metadata = provider_metadata(&config).await?;
+
+    let mut request = http_client().post(&metadata.token_endpoint).form(&[
+        ("grant_type", "authorization_code"),
+        ("code", code.as_str()),
+        ("redirect_uri", config.redirect_uri.as_str()),
+        ("client_id", config.client_id.as_str()),
+        ("code_verifier", login_state.code_verifier.as_str()),
+    ]);
+    if let Some(secret) = &config.client_secret {
+        request = request.basic_auth(&config.client_id, Some(secret));
+    }
+    let response = request.send().await.map_err(|e| {
+        error!("OIDC token request failed: {}", e);
+        AppError::InternalError("Failed to reach the identity provider (token)".to_string())
+    })?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        info!("OIDC token exchange failed with {}: {}", status, body);
+        return Err(AppError::Unauthorized(
+            "The identity provider rejected the authorization code.".to_string(),
+        ));
+    }
+    let tokens = response.json::<TokenResponse>().await.map_err(|e| {
+        error!("Invalid OIDC token response: {}", e);
+        AppError::InternalError("Invalid response from the identity provider".to_string())
+    })?;
+
+    let claims = verify_id_token(&config, metadata, &tokens.id_token, &login_state.nonce).await?;
+
+    let subject = claims
+        .get("sub")
+        .and_then(Value::as_str)
+        .ok_or_else(|| AppError::Unauthorized("ID token has no subject.".to_string()))?
+        .to_string();
+    let login_name = claims
+        .get(&config.username_claim)
+        .and_then(Value::as_str)
+        .unwrap_or(&subject)
+        .to_string();
+    let role = config.role_from_claims(&claims);
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to complete OIDC login".to_string())
+    })?;
+
+    let identity = ExternalIdentity {
+        issuer: &metadata.issuer,
+        subject: &subject,
+        login_name: &login_name,
+        role,
+        display_name: claims.get("name").and_then(Value::as_str),
+        email: claims.get("email").and_then(Value::as_str),
+    };
+    let (user_id, _) = find_or_provision_user(&mut tx, &identity).await?;
+
+    sqlx::query!(
+        r#"DELETE FROM oidc_login_codes WHERE created_at < NOW() - make_interval(mins => $1)"#,
+        LOGIN_CODE_TTL_MINUTES
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during cleaning up OIDC login codes: {}", e);
+        AppError::DatabaseError("Failed to complete OIDC login".to_string())
+    })?;
+
+    let login_code = generate_secret();
+    sqlx::query!(
+        r#"INSERT INTO oidc_login_codes (code_hash, user_id) VALUES ($1, $2)"#,
+        hash_token(&login_code),
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during storing OIDC login code: {}", e);
+        AppError::DatabaseError("Failed to complete OIDC login".to_string())
+    })?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing OIDC login: {}", e);
+        AppError::DatabaseError("Failed to complete OIDC login".to_string())
+    })?;
+
+    let url =
+        reqwest::Url::parse_with_params(&config.app_redirect_uri, &[("code", login_code.as_str())])
+            .map_err(|e| {
+                error!("Invalid OIDC_APP_REDIRECT_URI: {}", e);
+                AppError::InternalError("Invalid OIDC app redirect URI".to_string())
+            })?;
+
+    info!("User {} authenticated via OIDC", user_id);
+    Ok(url.into())
+}
+
+/// `/oidc/callback` が SPA に渡したコードを、`/login` と同じトークン (または二要素認証のチャレンジ) と交換する
+pub async fn oidc_exchange_code(
+    pool: &PgPool,
+    payload: OidcCodeRequest,
+) -> Result<LoginOutcome, AppError> {
+    oidc_config()?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to complete OIDC login".to_string())
+    })?;
+
+    let user = sqlx::query!(
+        r#"WITH used AS (
+            DELETE FROM oidc_login_codes
+            WHERE code_hash = $1 AND created_at >= NOW() - make_interval(mins => $2)
+            RETURNING user_id
+        )
+        SELECT u.id, u.role FROM used JOIN users u ON u.id = used.user_id
+        "#,
+        hash_token(&payload.code),
+        LOGIN_CODE_TTL_MINUTES
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching OIDC login code: {}", e);
+        AppError::DatabaseError("Failed to complete OIDC login".to_string())
+    })?
+    .ok_or_else(|| AppError::Unauthorized("Unknown or expired OIDC login code.".to_string()))?;
+
+    let outcome = complete_login(&mut tx, user.id, &user.role).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing OIDC login: {}", e);
+        AppError::DatabaseError("Failed to complete OIDC login".to_string())
+    })?;
+
+    info!("User {} logged in via OIDC", user.id);
+    Ok(outcome)
+}
+
+async fn verify_id_token(
+    config: &OidcConfig,
+    metadata: &ProviderMetadata,
+    id_token: &str,
+    nonce: &str,
+) -> Result<Value, AppError> {
+    let invalid = |e: jsonwebtoken::errors::Error| {
+        info!("Invalid ID token: {}", e);
+        AppError::Unauthorized("Invalid ID token.".to_string())
+    };
+
+    let header = decode_header(id_token).map_err(invalid)?;
+    if matches!(
+        header.alg,
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+    ) {
+        return Err(AppError::Unauthorized(
+            "ID token must be signed with an asymmetric key.".to_string(),
+        ));
+    }
+
+    let jwk = find_jwk(metadata, header.kid.as_deref()).await?;
+    let key = DecodingKey::from_jwk(&jwk).map_err(invalid)?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_issuer(&[&metadata.issuer]);
+    validation.set_audience(&[&config.client_id]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+    let claims = decode::<Value>(id_token, &key, &validation)
+        .map_err(invalid)?
+        .claims;
+
+    if claims.get("nonce").and_then(Value::as_str) != Some(nonce) {
+        return Err(AppError::Unauthorized(
+            "ID token nonce mismatch.".to_string(),
+        ));
+    }
+    Ok(claims)
+}
@@ -0,0 +1,292 @@
+use std::net::IpAddr;
+
+use crate::audit::{
+    domain::{AuditAction, AuditEntity},
+    service::{record_audit, snapshot},
+};
+use crate::auth::domain::{
+    ChangePasswordRequest, Claims, PasswordResetToken, ResetPasswordRequest, Role,
+};
+use crate::auth::password::{hash_password, verify_password};
+use crate::auth::password_policy::validate_password;
+use crate::auth::revocation::remember_revoked;
+use crate::errors::app_error::AppError;
+use crate::errors::validation::field_validation_error;
+
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::api_key::revoke_user_api_keys;
+use super::session::revoke_families;
+use super::throttle;
+use super::token::{generate_secret, hash_token};
+
+/// 再設定トークンの有効時間 (`PASSWORD_RESET_TTL_HOURS`, default 24)
+fn password_reset_ttl_hours() -> i32 {
+    std::env::var("PASSWORD_RESET_TTL_HOURS")
+        .ok()
+        .and_then(|hours| hours.parse().ok())
+        .filter(|hours| *hours > 0)
+        .unwrap_or(24)
+}
+
+/// パスワードを書き換えて監査ログに記録する (ハッシュは記録されず、変更日時だけが残る)
+pub async fn update_password_hash(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    password: &str,
+    actor_id: Option<Uuid>,
+) -> Result<(), AppError> {
+    let hash = hash_password(password)?;
+    let before = snapshot(&mut *conn, AuditEntity::User, user_id).await?;
+
+    sqlx::query!(
+        r#"UPDATE users SET password_hash = $2, password_changed_at = NOW() WHERE id = $1"#,
+        user_id,
+        hash
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during updating password: {}", e);
+        AppError::DatabaseError("Failed to update password".to_string())
+    })?;
+
+    record_audit(
+        conn,
+        AuditEntity::User,
+        user_id,
+        AuditAction::Update,
+        actor_id,
+        before,
+    )
+    .await
+}
+
+/// 自分のパスワードを変更する。現在のパスワードの間違いはログインの失敗として数え、
+/// 変更後は今のセッション以外を失効させる
+pub async fn change_password(
+    claims: Claims,
+    pool: &PgPool,
+    payload: ChangePasswordRequest,
+    client_ip: Option<IpAddr>,
+) -> Result<(), AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let user = sqlx::query!(
+        r#"SELECT login_name, password_hash FROM users WHERE id = $1"#,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching user: {}", e);
+        AppError::DatabaseError("Failed to change password".to_string())
+    })?
+    .ok_or_else(|| AppError::NotFound(format!("User not found: {}", user_id)))?;
+
+    let Some(password_hash) = user.password_hash else {
+        return Err(AppError::ValidationError(field_validation_error(
+            "current_password",
+            "This account has no local password. It signs in through SSO or LDAP.",
+        )));
+    };
+
+    let attempt = throttle::begin_attempt(pool, &user.login_name, client_ip).await?;
+    if let Err(e) = verify_password(&payload.current_password, &password_hash) {
+        if matches!(e, AppError::Unauthorized(_)) {
+            attempt.failed(pool).await?;
+            info!("Invalid current password for user {}", user_id);
+        } else {
+            attempt.release(pool).await?;
+        }
+        return Err(e);
+    }
+    attempt.release(pool).await?;
+
+    validate_password(&user.login_name, &payload.new_password, "new_password")?;
+    if payload.new_password == payload.current_password {
+        return Err(AppError::ValidationError(field_validation_error(
+            "new_password",
+            "New password must be different from the current password.",
+        )));
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to change password".to_string())
+    })?;
+
+    update_password_hash(&mut tx, user_id, &payload.new_password, Some(user_id)).await?;
+
+    let family_ids = sqlx::query_scalar!(
+        r#"SELECT DISTINCT family_id FROM refresh_tokens
+        WHERE user_id = $1 AND revoked_at IS NULL
+          AND family_id NOT IN (SELECT family_id FROM refresh_tokens WHERE access_jti = $2)
+        "#,
+        user_id,
+        claims.jti
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching sessions: {}", e);
+        AppError::DatabaseError("Failed to change password".to_string())
+    })?;
+    let revoked = revoke_families(&mut tx, &family_ids).await?;
+    let api_keys = revoke_user_api_keys(&mut tx, user_id, Some(user_id)).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing password change: {}", e);
+        AppError::DatabaseError("Failed to change password".to_string())
+    })?;
+    remember_revoked(&revoked).await;
+
+    info!(
+        "User {} changed password and {} other sessions and {} API keys were revoked",
+        user_id,
+        family_ids.len(),
+        api_keys.len()
+    );
+    Ok(())
+}
+
+/// パスワードの再設定トークンを発行する (管理者のみ)。以前に発行したトークンは無効になる
+pub async fn create_password_reset(
+    claims: Claims,
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<PasswordResetToken, AppError> {
+    if claims.role != Role::Admin {
+        info!(
+            "User {} is not allowed to issue password reset tokens",
+            claims.sub
+        );
+        return Err(AppError::Unauthorized(
+            "Only admins can reset passwords.".to_string(),
+        ));
+    }
+    let admin_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let user = sqlx::query!(r#"SELECT password_hash FROM users WHERE id = $1"#, user_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| {
+            error!("DB error during fetching user: {}", e);
+            AppError::DatabaseError("Failed to issue password reset token".to_string())
+        })?
+        .ok_or_else(|| AppError::NotFound(format!("User not found: {}", user_id)))?;
+    // SSO / LDAP のユーザーにローカルのパスワードを作ると、そちらが優先されてしまう
+    if user.password_hash.is_none() {
+        return Err(AppError::ValidationError(field_validation_error(
+            "id",
+            "This user has no local password. It signs in through SSO or LDAP.",
+        )));
+    }
+
+    let token = generate_secret();
+    let expires_at = sqlx::query_scalar!(
+        r#"INSERT INTO password_reset_tokens (token_hash, user_id, created_by, expires_at)
+        VALUES ($1, $2, $3, NOW() + make_interval(hours => $4))
+        ON CONFLICT (user_id) DO UPDATE SET
+            token_hash = EXCLUDED.token_hash,
+            created_by = EXCLUDED.created_by,
+            created_at = NOW(),
+            expires_at = EXCLUDED.expires_at
+        RETURNING expires_at
+        "#,
+        hash_token(&token),
+        user_id,
+        admin_id,
+        password_reset_ttl_hours()
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during issuing password reset token: {}", e);
+        AppError::DatabaseError("Failed to issue password reset token".to_string())
+    })?;
+
+    info!(
+        "Admin {} issued a password reset token for user {}",
+        admin_id, user_id
+    );
+    Ok(PasswordResetToken {
+        user_id,
+        token,
+        expires_at,
+    })
+}
+
+/// 再設定トークンで新しいパスワードを設定する。
+/// トークンは使い切りで、ユーザーのすべてのセッションとログインのロックを解除する
+pub async fn reset_password(pool: &PgPool, payload: ResetPasswordRequest) -> Result<(), AppError> {
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to reset password".to_string())
+    })?;
+
+    let reset = sqlx::query!(
+        r#"SELECT t.token_hash, t.user_id, u.login_name
+        FROM password_reset_tokens t
+        JOIN users u ON u.id = t.user_id
+        WHERE t.token_hash = $1 AND t.expires_at > NOW()
+        FOR UPDATE OF t
+        "#,
+        hash_token(&payload.token)
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching password reset token: {}", e);
+        AppError::DatabaseError("Failed to reset password".to_string())
+    })?
+    .ok_or_else(|| AppError::Unauthorized("Invalid or expired reset token.".to_string()))?;
+
+    // 条件を満たさないときはトークンを残し、別のパスワードでやり直せるようにする
+    validate_password(&reset.login_name, &payload.new_password, "new_password")?;
+
+    sqlx::query!(
+        r#"DELETE FROM password_reset_tokens WHERE token_hash = $1"#,
+        reset.token_hash
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during deleting password reset token: {}", e);
+        AppError::DatabaseError("Failed to reset password".to_string())
+    })?;
+
+    update_password_hash(&mut tx, reset.user_id, &payload.new_password, None).await?;
+
+    let family_ids = sqlx::query_scalar!(
+        r#"SELECT DISTINCT family_id FROM refresh_tokens WHERE user_id = $1 AND revoked_at IS NULL"#,
+        reset.user_id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching sessions: {}", e);
+        AppError::DatabaseError("Failed to reset password".to_string())
+    })?;
+    let revoked = revoke_families(&mut tx, &family_ids).await?;
+    let api_keys = revoke_user_api_keys(&mut tx, reset.user_id, None).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing password reset: {}", e);
+        AppError::DatabaseError("Failed to reset password".to_string())
+    })?;
+    remember_revoked(&revoked).await;
+    throttle::record_success(pool, &reset.login_name).await?;
+
+    info!(
+        "Password of user {} reset and {} sessions and {} API keys were revoked",
+        reset.user_id,
+        family_ids.len(),
+        api_keys.len()
+    );
+    Ok(())
+}
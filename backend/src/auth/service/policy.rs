@@ -0,0 +1,92 @@
+use crate::audit::domain::{AuditAction, AuditEntity};
+use crate::audit::service::{record_audit, snapshot};
+use crate::auth::domain::{Claims, Role, SecurityPolicy, UpdateSecurityPolicy};
+use crate::errors::app_error::AppError;
+
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+pub async fn get_security_policy(pool: &PgPool) -> Result<SecurityPolicy, AppError> {
+    sqlx::query_as!(
+        SecurityPolicy,
+        r#"SELECT require_admin_mfa, updated_at, updated_by FROM security_policy"#
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching security policy: {}", e);
+        AppError::DatabaseError("Failed to fetch security policy".to_string())
+    })
+}
+
+/// 認証のポリシーを変更する (管理者のみ)。
+/// 二要素認証を必須にすると、未登録の admin は次のトークン発行から `/mfa` しか使えなくなる。
+pub async fn update_security_policy(
+    claims: Claims,
+    pool: &PgPool,
+    policy: UpdateSecurityPolicy,
+) -> Result<SecurityPolicy, AppError> {
+    if claims.role != Role::Admin {
+        info!(
+            "User {} is not allowed to change security policy",
+            claims.sub
+        );
+        return Err(AppError::Unauthorized(
+            "Only admins can change the security policy.".to_string(),
+        ));
+    }
+
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to update security policy".to_string())
+    })?;
+
+    let policy_id = sqlx::query_scalar!(r#"SELECT policy_id FROM security_policy FOR UPDATE"#)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("DB error during fetching security policy: {}", e);
+            AppError::DatabaseError("Failed to update security policy".to_string())
+        })?;
+    let before = snapshot(&mut tx, AuditEntity::SecurityPolicy, policy_id).await?;
+
+    let updated = sqlx::query_as!(
+        SecurityPolicy,
+        r#"UPDATE security_policy
+        SET require_admin_mfa = $1, updated_at = NOW(), updated_by = $2
+        RETURNING require_admin_mfa, updated_at, updated_by
+        "#,
+        policy.require_admin_mfa,
+        user_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during updating security policy: {}", e);
+        AppError::DatabaseError("Failed to update security policy".to_string())
+    })?;
+    record_audit(
+        &mut tx,
+        AuditEntity::SecurityPolicy,
+        policy_id,
+        AuditAction::Update,
+        Some(user_id),
+        before,
+    )
+    .await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing security policy: {}", e);
+        AppError::DatabaseError("Failed to update security policy".to_string())
+    })?;
+
+    info!(
+        "Security policy updated by {}: require_admin_mfa = {}",
+        user_id, updated.require_admin_mfa
+    );
+    Ok(updated)
+}
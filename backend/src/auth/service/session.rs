@@ -0,0 +1,180 @@
+use crate::auth::domain::{Claims, RevokedSessions, Role, Session};
+use crate::auth::revocation::remember_revoked;
+use crate::errors::app_error::AppError;
+
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::api_key::revoke_user_api_keys;
+use super::token::ACCESS_TOKEN_TTL_SECS;
+
+/// セッション (リフレッシュトークンの系列) を失効させ、そこから発行した期限内のアクセストークンを失効リストに載せる。
+/// 失効リストに追加した jti を返す。
+pub async fn revoke_families(
+    conn: &mut PgConnection,
+    family_ids: &[Uuid],
+) -> Result<Vec<Uuid>, AppError> {
+    sqlx::query_scalar!(
+        r#"WITH revoked AS (
+            UPDATE refresh_tokens SET revoked_at = NOW()
+            WHERE family_id = ANY($1) AND revoked_at IS NULL
+            RETURNING access_jti, created_at
+        )
+        INSERT INTO revoked_tokens (jti, expires_at)
+        SELECT access_jti, created_at + make_interval(secs => $2)
+        FROM revoked
+        WHERE access_jti IS NOT NULL AND created_at + make_interval(secs => $2) > NOW()
+        ON CONFLICT (jti) DO NOTHING
+        RETURNING jti
+        "#,
+        family_ids,
+        ACCESS_TOKEN_TTL_SECS as f64
+    )
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during revoking sessions: {}", e);
+        AppError::DatabaseError("Failed to revoke sessions".to_string())
+    })
+}
+
+/// 自分の有効なセッションの一覧 (新しい順)
+pub async fn get_sessions(claims: Claims, pool: &PgPool) -> Result<Vec<Session>, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let sessions = sqlx::query_as!(
+        Session,
+        r#"SELECT family_id AS "id!",
+                  MIN(created_at) AS "created_at!",
+                  MAX(created_at) AS "last_used_at!",
+                  MAX(expires_at) AS "expires_at!",
+                  COALESCE(BOOL_OR(access_jti = $2), FALSE) AS "current!"
+        FROM refresh_tokens
+        WHERE user_id = $1
+        GROUP BY family_id
+        HAVING BOOL_AND(revoked_at IS NULL) AND BOOL_OR(used_at IS NULL AND expires_at > NOW())
+        ORDER BY MAX(created_at) DESC
+        "#,
+        user_id,
+        claims.jti
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching sessions: {}", e);
+        AppError::DatabaseError("Failed to fetch sessions".to_string())
+    })?;
+
+    info!("Fetched {} sessions for user {}", sessions.len(), user_id);
+    Ok(sessions)
+}
+
+/// 自分のセッションを失効させる
+pub async fn revoke_session(claims: Claims, pool: &PgPool, id: Uuid) -> Result<(), AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to revoke session".to_string())
+    })?;
+
+    let owned = sqlx::query_scalar!(
+        r#"SELECT EXISTS (
+            SELECT 1 FROM refresh_tokens WHERE family_id = $1 AND user_id = $2
+        ) AS "owned!""#,
+        id,
+        user_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching session: {}", e);
+        AppError::DatabaseError("Failed to revoke session".to_string())
+    })?;
+    if !owned {
+        return Err(AppError::NotFound(format!("Session not found: {}", id)));
+    }
+
+    let revoked = revoke_families(&mut tx, &[id]).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing session revocation: {}", e);
+        AppError::DatabaseError("Failed to revoke session".to_string())
+    })?;
+    remember_revoked(&revoked).await;
+
+    info!("Session {} revoked by user {}", id, user_id);
+    Ok(())
+}
+
+/// ユーザーのすべてのセッションを失効させる (管理者のみ)
+pub async fn revoke_user_sessions(
+    claims: Claims,
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<RevokedSessions, AppError> {
+    if claims.role != Role::Admin {
+        info!(
+            "User {} is not allowed to revoke other sessions",
+            claims.sub
+        );
+        return Err(AppError::Unauthorized(
+            "Only admins can revoke sessions of other users.".to_string(),
+        ));
+    }
+    let admin_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to revoke sessions".to_string())
+    })?;
+
+    let user = sqlx::query_scalar!(r#"SELECT id FROM users WHERE id = $1"#, user_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("DB error during fetching user: {}", e);
+            AppError::DatabaseError("Failed to revoke sessions".to_string())
+        })?;
+    if user.is_none() {
+        return Err(AppError::NotFound(format!("User not found: {}", user_id)));
+    }
+
+    let family_ids = sqlx::query_scalar!(
+        r#"SELECT DISTINCT family_id FROM refresh_tokens WHERE user_id = $1 AND revoked_at IS NULL"#,
+        user_id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching sessions: {}", e);
+        AppError::DatabaseError("Failed to revoke sessions".to_string())
+    })?;
+
+    let revoked = revoke_families(&mut tx, &family_ids).await?;
+    // API キーもログインと同じく本人として使えるため、合わせて失効させる
+    let api_keys = revoke_user_api_keys(&mut tx, user_id, Some(admin_id)).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing session revocation: {}", e);
+        AppError::DatabaseError("Failed to revoke sessions".to_string())
+    })?;
+    remember_revoked(&revoked).await;
+
+    info!(
+        "Revoked {} sessions and {} API keys of user {} by admin {}",
+        family_ids.len(),
+        api_keys.len(),
+        user_id,
+        admin_id
+    );
+    Ok(RevokedSessions {
+        user_id,
+        revoked: family_ids.len() as i64,
+        revoked_api_keys: api_keys.len() as i64,
+    })
+}
@@ -1,4 +1,5 @@
 use crate::auth::domain::{SignupRequest, SignupResponse};
+use crate::auth::password_policy::validate_password;
 
 use sqlx::PgPool;
 
@@ -7,6 +8,7 @@ use crate::errors::app_error::AppError;
 use super::user_create::create_user_with_role;
 
 pub async fn signup(pool: &PgPool, payload: SignupRequest) -> Result<SignupResponse, AppError> {
+    validate_password(&payload.login_name, &payload.password, "password")?;
     create_user_with_role(pool, &payload.login_name, &payload.password, "user").await?;
 
     Ok(SignupResponse {
@@ -0,0 +1,462 @@
+use std::net::IpAddr;
+
+use crate::audit::domain::{AuditAction, AuditEntity};
+use crate::audit::request_id::current_request_id;
+use crate::auth::domain::{Claims, LoginLockout, LoginThrottleKind, Role};
+use crate::errors::app_error::AppError;
+
+use chrono::Utc;
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// 最初のロックの長さ (秒)。以降は失敗するたびに倍になる
+const BASE_LOCKOUT_SECS: u64 = 30;
+/// 最後の失敗からこの時間 (分) が経つと失敗回数を数え直す
+const FAILURE_WINDOW_MINUTES: i32 = 60;
+
+/// アカウントをロックするまでの失敗回数 (`LOGIN_MAX_FAILURES`, default 5)
+fn account_max_failures() -> i32 {
+    std::env::var("LOGIN_MAX_FAILURES")
+        .ok()
+        .and_then(|count| count.parse().ok())
+        .filter(|count| *count > 0)
+        .unwrap_or(5)
+}
+
+/// 接続元 IP をロックするまでの失敗回数 (`LOGIN_IP_MAX_FAILURES`, default 20)
+fn ip_max_failures() -> i32 {
+    std::env::var("LOGIN_IP_MAX_FAILURES")
+        .ok()
+        .and_then(|count| count.parse().ok())
+        .filter(|count| *count > 0)
+        .unwrap_or(20)
+}
+
+/// ロックの最長時間 (`LOGIN_LOCKOUT_MAX_MINUTES`, default 15)
+fn max_lockout_secs() -> u64 {
+    std::env::var("LOGIN_LOCKOUT_MAX_MINUTES")
+        .ok()
+        .and_then(|minutes| minutes.parse::<u64>().ok())
+        .filter(|minutes| *minutes > 0)
+        .unwrap_or(15)
+        * 60
+}
+
+/// `failures` 回目の失敗でロックする秒数。しきい値に達するまでは None
+fn lockout_secs(failures: i32, threshold: i32, max_secs: u64) -> Option<u64> {
+    let exponent = u32::try_from(failures - threshold).ok()?;
+    let multiplier = 1u64.checked_shl(exponent).unwrap_or(u64::MAX);
+    Some(BASE_LOCKOUT_SECS.saturating_mul(multiplier).min(max_secs))
+}
+
+fn account_key(login_name: &str) -> String {
+    login_name.to_lowercase()
+}
+
+/// 結果が出る前に失敗として数えた 1 回分
+struct Reservation {
+    kind: LoginThrottleKind,
+    key: String,
+    failures: i32,
+    /// この試行で回数がしきい値に達してかけたロックの秒数
+    locked_secs: Option<u64>,
+}
+
+/// 認証の試行。検証の前に失敗として数えておき、同時に送られた試行でもしきい値を超えて検証させない。
+/// 結果に応じて `failed` / `succeeded` / `release` のいずれかを呼ぶ (呼ばなければ失敗のまま残る)
+#[must_use]
+pub struct LoginAttempt {
+    login_name: String,
+    reservations: Vec<Reservation>,
+}
+
+/// アカウントか接続元 IP がロック中なら 429 を返す。
+/// そうでなければ試行を失敗として 1 回ずつ数え、しきい値に達したらその場でロックする
+pub async fn begin_attempt(
+    pool: &PgPool,
+    login_name: &str,
+    ip: Option<IpAddr>,
+) -> Result<LoginAttempt, AppError> {
+    purge_stale(pool).await?;
+
+    let mut targets = vec![(
+        LoginThrottleKind::Account,
+        account_key(login_name),
+        account_max_failures(),
+    )];
+    if let Some(ip) = ip {
+        targets.push((LoginThrottleKind::Ip, ip.to_string(), ip_max_failures()));
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to check login lockout".to_string())
+    })?;
+
+    let mut reservations = Vec::new();
+    for (kind, key, threshold) in targets {
+        // 行ロックで同じアカウント・IP の試行を 1 つずつ数える。ロック中なら数えない
+        let failures = sqlx::query_scalar!(
+            r#"INSERT INTO login_throttles (kind, key, failures, last_failure_at)
+            VALUES ($1, $2, 1, NOW())
+            ON CONFLICT (kind, key) DO UPDATE SET
+                failures = CASE
+                    WHEN login_throttles.last_failure_at < NOW() - make_interval(mins => $3) THEN 1
+                    ELSE login_throttles.failures + 1
+                END,
+                last_failure_at = NOW()
+            WHERE login_throttles.locked_until IS NULL OR login_throttles.locked_until <= NOW()
+            RETURNING failures
+            "#,
+            kind.as_str(),
+            key,
+            FAILURE_WINDOW_MINUTES
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("DB error during counting login attempt: {}", e);
+            AppError::DatabaseError("Failed to check login lockout".to_string())
+        })?;
+
+        let Some(failures) = failures else {
+            // 先に数えた分はロールバックで戻る
+            return Err(locked_error(&mut tx, kind, &key).await);
+        };
+
+        let locked_secs = lockout_secs(failures, threshold, max_lockout_secs());
+        if let Some(secs) = locked_secs {
+            sqlx::query!(
+                r#"UPDATE login_throttles
+                SET locked_until = NOW() + make_interval(secs => $3)
+                WHERE kind = $1 AND key = $2
+                "#,
+                kind.as_str(),
+                key,
+                secs as f64
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!("DB error during locking login: {}", e);
+                AppError::DatabaseError("Failed to check login lockout".to_string())
+            })?;
+        }
+        reservations.push(Reservation {
+            kind,
+            key,
+            failures,
+            locked_secs,
+        });
+    }
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing login attempt: {}", e);
+        AppError::DatabaseError("Failed to check login lockout".to_string())
+    })?;
+
+    Ok(LoginAttempt {
+        login_name: login_name.to_string(),
+        reservations,
+    })
+}
+
+async fn locked_error(conn: &mut PgConnection, kind: LoginThrottleKind, key: &str) -> AppError {
+    let locked_until = sqlx::query_scalar!(
+        r#"SELECT locked_until FROM login_throttles WHERE kind = $1 AND key = $2"#,
+        kind.as_str(),
+        key
+    )
+    .fetch_optional(conn)
+    .await;
+
+    match locked_until {
+        Ok(locked_until) => AppError::TooManyRequests {
+            message: "Too many failed login attempts. Try again later.".to_string(),
+            retry_after_secs: locked_until.flatten().map_or(1, |locked_until| {
+                (locked_until - Utc::now()).num_seconds().max(1) as u64
+            }),
+        },
+        Err(e) => {
+            error!("DB error during checking login lockout: {}", e);
+            AppError::DatabaseError("Failed to check login lockout".to_string())
+        }
+    }
+}
+
+/// 最後の失敗から `FAILURE_WINDOW_MINUTES` が過ぎ、ロックも切れた記録を消す
+async fn purge_stale(pool: &PgPool) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"DELETE FROM login_throttles
+        WHERE last_failure_at < NOW() - make_interval(mins => $1)
+          AND (locked_until IS NULL OR locked_until <= NOW())
+        "#,
+        FAILURE_WINDOW_MINUTES
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during purging login failures: {}", e);
+        AppError::DatabaseError("Failed to check login lockout".to_string())
+    })?;
+    Ok(())
+}
+
+impl LoginAttempt {
+    /// 認証に失敗した。数えた回数はそのまま残し、この試行でかけたロックを監査ログに記録する
+    pub async fn failed(self, pool: &PgPool) -> Result<(), AppError> {
+        for reservation in &self.reservations {
+            let Some(secs) = reservation.locked_secs else {
+                continue;
+            };
+            // ロックは失敗した本人の操作なので actor は null
+            sqlx::query!(
+                r#"INSERT INTO audit_logs (entity, entity_id, action, request_id, after)
+                SELECT $3, id, $4, $5, to_jsonb(t)
+                FROM login_throttles t
+                WHERE kind = $1 AND key = $2
+                "#,
+                reservation.kind.as_str(),
+                reservation.key,
+                AuditEntity::LoginThrottle.as_str(),
+                AuditAction::Update.as_str(),
+                current_request_id()
+            )
+            .execute(pool)
+            .await
+            .map_err(|e| {
+                error!("DB error during recording login lockout: {}", e);
+                AppError::DatabaseError("Failed to record login failure".to_string())
+            })?;
+
+            warn!(
+                "Login locked for {} {} after {} failures ({}s)",
+                reservation.kind.as_str(),
+                reservation.key,
+                reservation.failures,
+                secs
+            );
+        }
+        Ok(())
+    }
+
+    /// トークンを発行した。アカウントの失敗回数を消し、接続元 IP は数えた分だけ戻す
+    pub async fn succeeded(self, pool: &PgPool) -> Result<(), AppError> {
+        record_success(pool, &self.login_name).await?;
+        for reservation in &self.reservations {
+            if reservation.kind == LoginThrottleKind::Ip {
+                undo(pool, reservation).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 失敗ではなかった (パスワードは正しく二要素認証が続く、サーバー側のエラーなど)。
+    /// 数えた分とこの試行でかけたロックを戻す
+    pub async fn release(self, pool: &PgPool) -> Result<(), AppError> {
+        for reservation in &self.reservations {
+            undo(pool, reservation).await?;
+        }
+        Ok(())
+    }
+}
+
+/// 数えた 1 回分を戻す。ロック中は他の試行が数えられないので、この試行のロックはそのまま外してよい
+async fn undo(pool: &PgPool, reservation: &Reservation) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"UPDATE login_throttles
+        SET failures = failures - 1,
+            locked_until = CASE WHEN $3 THEN NULL ELSE locked_until END
+        WHERE kind = $1 AND key = $2 AND failures > 0
+        "#,
+        reservation.kind.as_str(),
+        reservation.key,
+        reservation.locked_secs.is_some()
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during releasing login attempt: {}", e);
+        AppError::DatabaseError("Failed to record login attempt".to_string())
+    })?;
+    sqlx::query!(
+        r#"DELETE FROM login_throttles
+        WHERE kind = $1 AND key = $2 AND failures = 0 AND locked_until IS NULL
+        "#,
+        reservation.kind.as_str(),
+        reservation.key
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during releasing login attempt: {}", e);
+        AppError::DatabaseError("Failed to record login attempt".to_string())
+    })?;
+    Ok(())
+}
+
+/// ログインに成功したらアカウントの失敗回数を消す (接続元 IP の回数は残す)
+pub async fn record_success(pool: &PgPool, login_name: &str) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"DELETE FROM login_throttles WHERE kind = 'account' AND key = $1"#,
+        account_key(login_name)
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during clearing login failures: {}", e);
+        AppError::DatabaseError("Failed to clear login failures".to_string())
+    })?;
+    Ok(())
+}
+
+/// 管理者でなければ 401、管理者ならその id を返す
+fn ensure_admin(claims: &Claims) -> Result<Uuid, AppError> {
+    if claims.role != Role::Admin {
+        info!(
+            "User {} is not allowed to manage login lockouts",
+            claims.sub
+        );
+        return Err(AppError::Unauthorized(
+            "Only admins can manage login lockouts.".to_string(),
+        ));
+    }
+    Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))
+}
+
+/// 失敗回数とロックを消して監査ログに残す。記録がなければ false
+async fn remove_throttle(
+    pool: &PgPool,
+    kind: LoginThrottleKind,
+    key: &str,
+    admin_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let removed = sqlx::query_scalar!(
+        r#"WITH removed AS (
+            DELETE FROM login_throttles WHERE kind = $1 AND key = $2 RETURNING *
+        )
+        INSERT INTO audit_logs (entity, entity_id, action, actor_id, request_id, before)
+        SELECT $3, id, $4, $5, $6, to_jsonb(removed)
+        FROM removed
+        RETURNING entity_id
+        "#,
+        kind.as_str(),
+        key,
+        AuditEntity::LoginThrottle.as_str(),
+        AuditAction::Delete.as_str(),
+        admin_id,
+        current_request_id()
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(removed.is_some())
+}
+
+/// 失敗が記録されているアカウント・接続元 IP の一覧 (管理者のみ)
+pub async fn get_login_lockouts(
+    claims: Claims,
+    pool: &PgPool,
+) -> Result<Vec<LoginLockout>, AppError> {
+    ensure_admin(&claims)?;
+
+    let rows = sqlx::query!(
+        r#"SELECT kind, key, failures, last_failure_at, locked_until
+        FROM login_throttles
+        ORDER BY locked_until DESC NULLS LAST, last_failure_at DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching login lockouts: {}", e);
+        AppError::DatabaseError("Failed to fetch login lockouts".to_string())
+    })?;
+
+    rows.into_iter()
+        .map(|row| {
+            let kind = LoginThrottleKind::parse(&row.kind).ok_or_else(|| {
+                AppError::InternalError(format!("Invalid login throttle kind: {}", row.kind))
+            })?;
+            Ok(LoginLockout {
+                kind,
+                key: row.key,
+                failures: row.failures,
+                last_failure_at: row.last_failure_at,
+                locked_until: row.locked_until,
+            })
+        })
+        .collect()
+}
+
+/// ユーザーのロックと失敗回数を消す (管理者のみ)
+pub async fn unlock_user(claims: Claims, pool: &PgPool, user_id: Uuid) -> Result<(), AppError> {
+    let admin_id = ensure_admin(&claims)?;
+
+    let login_name = sqlx::query_scalar!(r#"SELECT login_name FROM users WHERE id = $1"#, user_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| {
+            error!("DB error during fetching user to unlock: {}", e);
+            AppError::DatabaseError("Failed to unlock user".to_string())
+        })?
+        .ok_or_else(|| AppError::NotFound(format!("User not found: {}", user_id)))?;
+
+    let removed = remove_throttle(
+        pool,
+        LoginThrottleKind::Account,
+        &account_key(&login_name),
+        admin_id,
+    )
+    .await
+    .map_err(|e| {
+        error!("DB error during unlocking user: {}", e);
+        AppError::DatabaseError("Failed to unlock user".to_string())
+    })?;
+    if !removed {
+        return Err(AppError::NotFound(format!(
+            "No login failures recorded for user: {}",
+            user_id
+        )));
+    }
+
+    info!("Admin {} unlocked user {}", claims.sub, user_id);
+    Ok(())
+}
+
+/// 接続元 IP のロックと失敗回数を消す (管理者のみ)
+pub async fn unlock_ip(claims: Claims, pool: &PgPool, ip: IpAddr) -> Result<(), AppError> {
+    let admin_id = ensure_admin(&claims)?;
+
+    let removed = remove_throttle(pool, LoginThrottleKind::Ip, &ip.to_string(), admin_id)
+        .await
+        .map_err(|e| {
+            error!("DB error during unlocking IP address: {}", e);
+            AppError::DatabaseError("Failed to unlock IP address".to_string())
+        })?;
+    if !removed {
+        return Err(AppError::NotFound(format!(
+            "No login failures recorded for IP address: {}",
+            ip
+        )));
+    }
+
+    info!("Admin {} unlocked IP address {}", claims.sub, ip);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::lockout_secs;
+
+    #[test]
+    fn test_lockout_secs() {
+        assert_eq!(lockout_secs(4, 5, 900), None);
+        assert_eq!(lockout_secs(5, 5, 900), Some(30));
+        assert_eq!(lockout_secs(6, 5, 900), Some(60));
+        assert_eq!(lockout_secs(9, 5, 900), Some(480));
+        assert_eq!(lockout_secs(10, 5, 900), Some(900));
+        assert_eq!(lockout_secs(200, 5, 900), Some(900));
+    }
+}
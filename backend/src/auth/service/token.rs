@@ -0,0 +1,212 @@
+use crate::auth::domain::{Claims, LoginResponse, RefreshTokenRequest, Role};
+use crate::auth::jwt::generate_jwt;
+use crate::auth::revocation::remember_revoked;
+use crate::errors::app_error::AppError;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, PgPool};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use super::mfa::mfa_enrollment_required;
+use super::session::revoke_families;
+
+/// アクセストークンの有効期間 (秒)
+pub const ACCESS_TOKEN_TTL_SECS: u64 = 60 * 60;
+
+/// リフレッシュトークンの有効日数 (`REFRESH_TOKEN_TTL_DAYS`, default 14)
+fn refresh_token_ttl_days() -> i32 {
+    std::env::var("REFRESH_TOKEN_TTL_DAYS")
+        .ok()
+        .and_then(|days| days.parse().ok())
+        .filter(|days| *days > 0)
+        .unwrap_or(14)
+}
+
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// 推測できないランダムな値 (16 進 64 文字)
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// アクセストークンと、`family_id` の系列に属する新しいリフレッシュトークンを発行する
+pub async fn issue_tokens(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    role: &str,
+    family_id: Uuid,
+) -> Result<LoginResponse, AppError> {
+    let expiration = SystemTime::now()
+        .checked_add(Duration::from_secs(ACCESS_TOKEN_TTL_SECS))
+        .unwrap()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as usize;
+
+    let role = Role::from(role);
+    let mfa_enrollment_required =
+        role == Role::Admin && mfa_enrollment_required(conn, user_id).await?;
+
+    let jti = Uuid::new_v4();
+    let claims = Claims {
+        sub: user_id.to_string(),
+        role,
+        exp: expiration,
+        jti,
+        mfa_enrollment_required,
+    };
+    let token = generate_jwt(claims)?;
+
+    let refresh_token = generate_secret();
+    sqlx::query!(
+        r#"INSERT INTO refresh_tokens (user_id, family_id, token_hash, expires_at, access_jti)
+        VALUES ($1, $2, $3, NOW() + make_interval(days => $4), $5)
+        "#,
+        user_id,
+        family_id,
+        hash_token(&refresh_token),
+        refresh_token_ttl_days(),
+        jti
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during storing refresh token: {}", e);
+        AppError::DatabaseError("Failed to issue refresh token".to_string())
+    })?;
+
+    Ok(LoginResponse {
+        token,
+        expires_in: ACCESS_TOKEN_TTL_SECS,
+        refresh_token,
+    })
+}
+
+/// リフレッシュトークンを新しいトークンと交換する (ローテーション)。
+/// 使用済みのトークンが再び使われた場合は盗用とみなし、同じ系列のトークンをすべて失効させる。
+pub async fn refresh_token(
+    pool: &PgPool,
+    payload: RefreshTokenRequest,
+) -> Result<LoginResponse, AppError> {
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to refresh token".to_string())
+    })?;
+
+    let current = sqlx::query!(
+        r#"SELECT t.id, t.user_id, t.family_id, u.role,
+                  t.used_at IS NOT NULL AS "used!",
+                  t.revoked_at IS NOT NULL AS "revoked!",
+                  t.expires_at <= NOW() AS "expired!"
+        FROM refresh_tokens t
+        JOIN users u ON u.id = t.user_id
+        WHERE t.token_hash = $1
+        FOR UPDATE OF t
+        "#,
+        hash_token(&payload.refresh_token)
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching refresh token: {}", e);
+        AppError::DatabaseError("Failed to refresh token".to_string())
+    })?
+    .ok_or_else(|| AppError::Unauthorized("Invalid refresh token.".to_string()))?;
+
+    if current.revoked {
+        return Err(AppError::Unauthorized(
+            "Refresh token has been revoked. Please log in again.".to_string(),
+        ));
+    }
+    if current.used {
+        warn!(
+            "Refresh token reuse detected for user {}; revoking family {}",
+            current.user_id, current.family_id
+        );
+        let revoked = revoke_families(&mut tx, &[current.family_id]).await?;
+        tx.commit().await.map_err(|e| {
+            error!("DB error during committing token revocation: {}", e);
+            AppError::DatabaseError("Failed to refresh token".to_string())
+        })?;
+        remember_revoked(&revoked).await;
+        return Err(AppError::Unauthorized(
+            "Refresh token has already been used. Please log in again.".to_string(),
+        ));
+    }
+    if current.expired {
+        return Err(AppError::Unauthorized(
+            "Refresh token has expired. Please log in again.".to_string(),
+        ));
+    }
+
+    sqlx::query!(
+        r#"UPDATE refresh_tokens SET used_at = NOW() WHERE id = $1"#,
+        current.id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during rotating refresh token: {}", e);
+        AppError::DatabaseError("Failed to refresh token".to_string())
+    })?;
+
+    let tokens = issue_tokens(&mut tx, current.user_id, &current.role, current.family_id).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing token refresh: {}", e);
+        AppError::DatabaseError("Failed to refresh token".to_string())
+    })?;
+
+    info!("Refreshed tokens for user {}", current.user_id);
+    Ok(tokens)
+}
+
+/// リフレッシュトークンの系列と、そこから発行したアクセストークンを失効させる。
+/// 未知のトークンでもエラーにはしない
+pub async fn logout(pool: &PgPool, payload: RefreshTokenRequest) -> Result<(), AppError> {
+    let family_id = sqlx::query_scalar!(
+        r#"SELECT family_id FROM refresh_tokens WHERE token_hash = $1"#,
+        hash_token(&payload.refresh_token)
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching refresh token: {}", e);
+        AppError::DatabaseError("Failed to log out".to_string())
+    })?;
+
+    if let Some(family_id) = family_id {
+        let mut conn = pool.acquire().await.map_err(|e| {
+            error!("DB error during acquiring connection: {}", e);
+            AppError::DatabaseError("Failed to log out".to_string())
+        })?;
+        let revoked = revoke_families(&mut conn, &[family_id]).await?;
+        remember_revoked(&revoked).await;
+        info!("Logged out refresh token family {}", family_id);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_secret, hash_token};
+
+    #[test]
+    fn test_refresh_token_hash() {
+        let token = generate_secret();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, generate_secret());
+        assert_eq!(hash_token(&token), hash_token(&token));
+        assert_eq!(
+            hash_token("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
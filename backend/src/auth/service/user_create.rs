@@ -1,14 +1,36 @@
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
 
-use crate::{auth::password::hash_password, errors::app_error::AppError, models::user::User};
-use tracing::{error, info};
+use crate::{
+    audit::{
+        domain::{AuditAction, AuditEntity},
+        service::record_audit,
+    },
+    auth::password::{hash_password, verify_password},
+    auth::password_policy::validate_password,
+    errors::{
+        app_error::AppError,
+        conflict::{UniqueViolation, conflict_error},
+    },
+    models::user::User,
+};
+use tracing::{error, info, warn};
 
+use super::password::update_password_hash;
+
+/// 起動時に用意する管理者のログイン名
+const INITIAL_ADMIN: &str = "admin";
+/// 以前のバージョンが初期管理者に設定していたパスワード
+const LEGACY_ADMIN_PASSWORD: &str = "admin";
+
+/// ユーザーを作成する。ログイン名が既に使われていれば 409 を返す。
+/// 未認証の `/signup` からも呼ばれるため、既存ユーザーの id は返さない。
 pub async fn create_user_with_role(
     pool: &PgPool,
     login_name: &str,
     password: &str,
     role: &str,
-) -> Result<(), AppError> {
+) -> Result<Uuid, AppError> {
     let existing = sqlx::query!(r#"SELECT id FROM users WHERE login_name = $1"#, login_name,)
         .fetch_optional(pool)
         .await
@@ -19,26 +41,135 @@ pub async fn create_user_with_role(
 
     if existing.is_some() {
         info!("This login name is already exist.");
-        return Ok(());
+        return Err(conflict_error(
+            "login_name",
+            &format!("Login name already exists: {}", login_name),
+            None,
+        ));
     }
 
     let hash = hash_password(password)?;
 
-    sqlx::query_as!(
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Signup failed: create user account.".to_string())
+    })?;
+
+    let user_id = insert_user(&mut tx, login_name, Some(&hash), role, None, None).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing user creation: {}", e);
+        AppError::DatabaseError("Signup failed: create user account.".to_string())
+    })?;
+    Ok(user_id)
+}
+
+/// トランザクション内でユーザーを作成し、監査ログに記録する。
+/// サインアップ・初期管理者・OIDC / LDAP の初回ログインは未認証のため操作者なしで記録する。
+pub async fn insert_user(
+    conn: &mut PgConnection,
+    login_name: &str,
+    password_hash: Option<&str>,
+    role: &str,
+    display_name: Option<&str>,
+    email: Option<&str>,
+) -> Result<Uuid, AppError> {
+    let user = sqlx::query_as!(
         User,
         r#"INSERT INTO users
-        (login_name, password_hash, role)
-        VALUES ($1, $2, $3)
-        RETURNING id, login_name, password_hash, role, created_at"#,
+        (login_name, password_hash, role, display_name, email)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, login_name, password_hash, role, display_name, email, created_at"#,
         login_name,
-        &hash,
-        role
+        password_hash,
+        role,
+        display_name,
+        email
     )
-    .fetch_one(pool)
+    .fetch_one(&mut *conn)
     .await
     .map_err(|e| {
+        if let Some(violation) = UniqueViolation::from_error(&e) {
+            return violation.into_conflict(None);
+        }
         error!("Signup failed: insert user into database: {}", e);
         AppError::DatabaseError("Signup failed: create user account.".to_string())
     })?;
+
+    record_audit(
+        conn,
+        AuditEntity::User,
+        user.id,
+        AuditAction::Create,
+        None,
+        None,
+    )
+    .await?;
+    Ok(user.id)
+}
+
+/// 初期管理者を用意する。パスワードは `ADMIN_INITIAL_PASSWORD` から読み、パスワードのポリシーを満たす必要がある。
+/// 以前の既定のパスワードのままの管理者がいれば `ADMIN_INITIAL_PASSWORD` に置き換え、設定がなければエラーにする。
+pub async fn ensure_initial_admin(pool: &PgPool) -> Result<(), AppError> {
+    let password = std::env::var("ADMIN_INITIAL_PASSWORD")
+        .ok()
+        .filter(|password| !password.is_empty());
+    if let Some(password) = &password {
+        validate_password(INITIAL_ADMIN, password, "ADMIN_INITIAL_PASSWORD")?;
+    }
+
+    let existing = sqlx::query!(
+        r#"SELECT id, password_hash FROM users WHERE login_name = $1"#,
+        INITIAL_ADMIN
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching initial admin: {}", e);
+        AppError::DatabaseError("Failed to ensure initial admin".to_string())
+    })?;
+
+    let Some(admin) = existing else {
+        let Some(password) = password else {
+            warn!("ADMIN_INITIAL_PASSWORD is not set, so no admin user was created");
+            return Ok(());
+        };
+        return match create_user_with_role(pool, INITIAL_ADMIN, &password, "admin").await {
+            Ok(user_id) => {
+                info!("Created initial admin {}", user_id);
+                Ok(())
+            }
+            Err(AppError::Conflict(_)) => Ok(()),
+            Err(e) => Err(e),
+        };
+    };
+
+    let has_legacy_password = admin
+        .password_hash
+        .as_deref()
+        .is_some_and(|hash| verify_password(LEGACY_ADMIN_PASSWORD, hash).is_ok());
+    if !has_legacy_password {
+        return Ok(());
+    }
+    let Some(password) = password else {
+        error!(
+            "The admin user still has the default password. Set ADMIN_INITIAL_PASSWORD to replace it."
+        );
+        return Err(AppError::InternalError(
+            "The admin user still has the default password".to_string(),
+        ));
+    };
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to ensure initial admin".to_string())
+    })?;
+    update_password_hash(&mut tx, admin.id, &password, None).await?;
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing initial admin password: {}", e);
+        AppError::DatabaseError("Failed to ensure initial admin".to_string())
+    })?;
+
+    warn!("Replaced the default password of the admin user with ADMIN_INITIAL_PASSWORD");
     Ok(())
 }
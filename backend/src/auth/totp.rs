@@ -0,0 +1,158 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base32::Alphabet;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+/// コードの桁数
+const DIGITS: u32 = 6;
+/// コードが切り替わる間隔 (秒)
+const PERIOD: u64 = 30;
+/// 端末の時計のずれを許容する前後のステップ数
+const WINDOW: i64 = 1;
+const SECRET_ALPHABET: Alphabet = Alphabet::Rfc4648 { padding: false };
+
+/// ランダムな共有鍵 (160 bit) を base32 で返す
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    OsRng.fill_bytes(&mut bytes);
+    base32::encode(SECRET_ALPHABET, &bytes)
+}
+
+/// HOTP (RFC 4226) のコード
+fn code_at(secret: &[u8], step: u64) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let value = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+    format!(
+        "{:0width$}",
+        value % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    )
+}
+
+/// `unix_time` の前後 `WINDOW` ステップでコードを照合し、一致したステップを返す。
+/// `last_used_step` 以前のステップは再利用とみなして受け付けない。
+pub fn verify_at(
+    secret: &str,
+    code: &str,
+    unix_time: u64,
+    last_used_step: Option<i64>,
+) -> Option<i64> {
+    let secret = base32::decode(SECRET_ALPHABET, secret)?;
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let current = (unix_time / PERIOD) as i64;
+    (current - WINDOW..=current + WINDOW)
+        .filter(|step| *step >= 0 && last_used_step.is_none_or(|last| *step > last))
+        .find(|step| code_at(&secret, *step as u64) == code)
+}
+
+pub fn verify(secret: &str, code: &str, last_used_step: Option<i64>) -> Option<i64> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    verify_at(secret, code, now, last_used_step)
+}
+
+/// 認証アプリに登録する `otpauth://` URI (QR コードにして読み取らせる)
+pub fn provisioning_uri(issuer: &str, account: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(issuer),
+        percent_encode(account),
+        secret,
+        percent_encode(issuer),
+        DIGITS,
+        PERIOD
+    )
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// リカバリーコード (`xxxxx-xxxxx` 形式) を `count` 個生成する
+pub fn generate_recovery_codes(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|_| {
+            let mut bytes = [0u8; 5];
+            OsRng.fill_bytes(&mut bytes);
+            let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            format!("{}-{}", &hex[..5], &hex[5..])
+        })
+        .collect()
+}
+
+/// 入力されたリカバリーコードを保存時の形式にそろえる (大文字・区切りの違いを許容する)
+pub fn normalize_recovery_code(code: &str) -> String {
+    let hex: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if hex.len() == 10 {
+        format!("{}-{}", &hex[..5], &hex[5..])
+    } else {
+        hex
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SECRET_ALPHABET, normalize_recovery_code, provisioning_uri, verify_at};
+
+    /// RFC 6238 Appendix B の SHA-1 のテストベクター (下 6 桁)
+    #[test]
+    fn test_verify_rfc6238() {
+        let secret = base32::encode(SECRET_ALPHABET, b"12345678901234567890");
+        assert_eq!(verify_at(&secret, "287082", 59, None), Some(1));
+        assert_eq!(
+            verify_at(&secret, "081804", 1111111109, None),
+            Some(37037036)
+        );
+        assert_eq!(
+            verify_at(&secret, "005924", 1234567890, None),
+            Some(41152263)
+        );
+        // 前後 1 ステップまでは受け付ける
+        assert_eq!(verify_at(&secret, "287082", 89, None), Some(1));
+        assert_eq!(verify_at(&secret, "287082", 120, None), None);
+        // 使用済みのステップは受け付けない
+        assert_eq!(verify_at(&secret, "287082", 59, Some(1)), None);
+        assert_eq!(verify_at(&secret, "28708", 59, None), None);
+    }
+
+    #[test]
+    fn test_provisioning_uri() {
+        assert_eq!(
+            provisioning_uri("PLM", "taro yamada", "JBSWY3DPEHPK3PXP"),
+            "otpauth://totp/PLM:taro%20yamada?secret=JBSWY3DPEHPK3PXP&issuer=PLM&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+
+    #[test]
+    fn test_normalize_recovery_code() {
+        assert_eq!(normalize_recovery_code(" AB12C-34DEF "), "ab12c-34def");
+        assert_eq!(normalize_recovery_code("ab12c34def"), "ab12c-34def");
+    }
+}
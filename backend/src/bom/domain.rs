@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::responses::export::{ExportRecord, ExportValue};
+
+#[derive(sqlx::FromRow, Serialize, ToSchema, Clone)]
+pub struct BomLine {
+    pub id: Uuid,
+    pub parent_id: Uuid,
+    pub child_id: Uuid,
+    pub child_part_number: String,
+    pub child_name: String,
+    pub quantity: f64,
+    pub unit_of_measure: String,
+    pub find_number: Option<i32>,
+    pub reference_designators: Vec<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct NewBomLine {
+    pub child_id: Uuid,
+    #[validate(range(exclusive_min = 0.0, message = "quantity must be greater than 0"))]
+    pub quantity: f64,
+    #[validate(length(min = 1, message = "unit_of_measure must not be empty"))]
+    pub unit_of_measure: String,
+    #[validate(range(min = 1, message = "find_number must be 1 or greater"))]
+    pub find_number: Option<i32>,
+    #[serde(default)]
+    pub reference_designators: Vec<String>,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct UpdateBomLine {
+    #[validate(range(exclusive_min = 0.0, message = "quantity must be greater than 0"))]
+    pub quantity: f64,
+    #[validate(length(min = 1, message = "unit_of_measure must not be empty"))]
+    pub unit_of_measure: String,
+    #[validate(range(min = 1, message = "find_number must be 1 or greater"))]
+    pub find_number: Option<i32>,
+    #[serde(default)]
+    pub reference_designators: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BomMode {
+    /// 直下の子部品のみ
+    #[default]
+    Single,
+    /// 末端まで展開した多階層構成
+    Exploded,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct BomQuery {
+    /// single (default) or exploded
+    #[serde(default)]
+    pub mode: BomMode,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BomNode {
+    pub level: u32,
+    pub line: BomLine,
+    #[schema(no_recursion)]
+    pub children: Vec<BomNode>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BomTree {
+    pub part_id: Uuid,
+    pub part_number: String,
+    pub name: String,
+    pub children: Vec<BomNode>,
+}
+
+/// 書き出し用に平坦化した構成行 (深さ優先順)
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub struct BomExportLine {
+    pub level: i32,
+    pub parent_part_number: String,
+    pub child_part_number: String,
+    pub child_name: String,
+    pub quantity: f64,
+    /// 最上位の部品 1 個あたりの数量 (上位の数量を掛けたもの)
+    pub extended_quantity: f64,
+    pub unit_of_measure: String,
+    pub find_number: Option<i32>,
+    pub reference_designators: Vec<String>,
+}
+
+impl ExportRecord for BomExportLine {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "level",
+            "parent_part_number",
+            "child_part_number",
+            "child_name",
+            "quantity",
+            "extended_quantity",
+            "unit_of_measure",
+            "find_number",
+            "reference_designators",
+        ]
+    }
+
+    fn values(&self) -> Vec<ExportValue> {
+        vec![
+            self.level.into(),
+            self.parent_part_number.clone().into(),
+            self.child_part_number.clone().into(),
+            self.child_name.clone().into(),
+            self.quantity.into(),
+            self.extended_quantity.into(),
+            self.unit_of_measure.clone().into(),
+            self.find_number.into(),
+            self.reference_designators.join(", ").into(),
+        ]
+    }
+}
+
+/// 親子リンクの一覧から `root` を頂点とする構成ツリーを組み立てる。
+/// 共通のサブアセンブリは使用箇所ごとに展開される。
+pub fn build_bom_nodes(root: Uuid, lines: &[BomLine], max_level: Option<u32>) -> Vec<BomNode> {
+    let mut by_parent: HashMap<Uuid, Vec<&BomLine>> = HashMap::new();
+    for line in lines {
+        by_parent.entry(line.parent_id).or_default().push(line);
+    }
+    for children in by_parent.values_mut() {
+        children.sort_by_key(|line| (line.find_number.is_none(), line.find_number));
+    }
+    build_level(root, &by_parent, 1, max_level)
+}
+
+fn build_level(
+    parent: Uuid,
+    by_parent: &HashMap<Uuid, Vec<&BomLine>>,
+    level: u32,
+    max_level: Option<u32>,
+) -> Vec<BomNode> {
+    if max_level.is_some_and(|max| level > max) {
+        return Vec::new();
+    }
+
+    by_parent
+        .get(&parent)
+        .map(|children| {
+            children
+                .iter()
+                .map(|line| BomNode {
+                    level,
+                    line: (*line).clone(),
+                    children: build_level(line.child_id, by_parent, level + 1, max_level),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+    use validator::Validate;
+
+    use super::{BomLine, NewBomLine, build_bom_nodes};
+
+    fn line(parent_id: Uuid, child_id: Uuid, find_number: Option<i32>) -> BomLine {
+        BomLine {
+            id: Uuid::new_v4(),
+            parent_id,
+            child_id,
+            child_part_number: "CHILD".to_string(),
+            child_name: "Child".to_string(),
+            quantity: 1.0,
+            unit_of_measure: "EA".to_string(),
+            find_number,
+            reference_designators: Vec::new(),
+            created_at: None,
+            created_by: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_invalid_zero_quantity() {
+        let new_line = NewBomLine {
+            child_id: Uuid::new_v4(),
+            quantity: 0.0,
+            unit_of_measure: "EA".to_string(),
+            find_number: Some(10),
+            reference_designators: vec!["R1".to_string()],
+        };
+        assert!(new_line.validate().is_err())
+    }
+
+    #[test]
+    fn test_build_exploded_bom() {
+        let (root, sub, bolt, nut) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let lines = vec![
+            line(root, nut, Some(20)),
+            line(root, sub, Some(10)),
+            line(sub, bolt, Some(10)),
+        ];
+
+        let exploded = build_bom_nodes(root, &lines, None);
+        assert_eq!(exploded.len(), 2);
+        assert_eq!(exploded[0].line.child_id, sub);
+        assert_eq!(exploded[0].children[0].line.child_id, bolt);
+        assert_eq!(exploded[0].children[0].level, 2);
+
+        let single = build_bom_nodes(root, &lines, Some(1));
+        assert!(single.iter().all(|node| node.children.is_empty()));
+    }
+}
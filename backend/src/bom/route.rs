@@ -0,0 +1,121 @@
+use crate::auth::domain::Claims;
+use crate::bom::domain::{BomExportLine, BomLine, BomQuery, BomTree, NewBomLine, UpdateBomLine};
+use crate::bom::service::{
+    add_bom_line as service_add_bom_line, delete_bom_line as service_delete_bom_line,
+    export_bom as service_export_bom, get_bom as service_get_bom,
+    update_bom_line as service_update_bom_line,
+};
+use crate::errors::app_error::AppError;
+use crate::errors::conflict::ConflictErrorResponse;
+use crate::errors::validation::ValidationErrorResponse;
+use crate::part::service::get_part as service_get_part;
+use crate::responses::error::ErrorResponse;
+use crate::responses::export::{ExportQuery, export_response};
+use crate::responses::success::SuccessResponse;
+
+use axum::Extension;
+use axum::response::Response;
+use axum::{Json, extract::Path, extract::Query, extract::State};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/parts/{id}/bom", params(("id" = Uuid, Path, description = "Parent part ID"), BomQuery), responses(
+    (status = 200, description = "Fetched BOM successfully", body = SuccessResponse<BomTree>),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["bom"], security(("bearerAuth" = [])))]
+pub async fn get_bom(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<BomQuery>,
+) -> Result<Json<SuccessResponse<BomTree>>, AppError> {
+    let bom = service_get_bom(&pool, id, query.mode).await?;
+    Ok(Json(SuccessResponse::ok(bom)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/parts/{id}/bom/export", params(("id" = Uuid, Path, description = "Parent part ID"), ExportQuery, BomQuery), responses(
+    (status = 200, description = "BOM lines in depth-first order, streamed as a file", content(
+        (String = "text/csv"),
+        (Vec<u8> = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+        (Vec<BomExportLine> = "application/json"),
+    )),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["bom"], security(("bearerAuth" = [])))]
+pub async fn export_bom(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Query(export): Query<ExportQuery>,
+    Query(query): Query<BomQuery>,
+) -> Result<Response, AppError> {
+    let part = service_get_part(&pool, id).await?;
+    export_response(
+        export.format,
+        &format!("bom-{}", part.part_number),
+        move |sender| service_export_bom(pool, id, query.mode, sender),
+    )
+    .await
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/parts/{id}/bom", params(("id" = Uuid, Path, description = "Parent part ID")), request_body = NewBomLine, responses(
+    (status = 201, description = "BOM line added successfully", body = SuccessResponse<BomLine>),
+    (status = 400, description = "Validation error (including BOM cycles)", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 409, description = "Child part is already in the BOM", body = ConflictErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["bom"], security(("bearerAuth" = [])))]
+pub async fn add_bom_line(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(new_line): Json<NewBomLine>,
+) -> Result<Json<SuccessResponse<BomLine>>, AppError> {
+    let line = service_add_bom_line(claims, &pool, id, new_line).await?;
+    Ok(Json(SuccessResponse::created(line)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(put, path = "/parts/{id}/bom/{line_id}", params(
+    ("id" = Uuid, Path, description = "Parent part ID"),
+    ("line_id" = Uuid, Path, description = "BOM line ID to update"),
+), request_body = UpdateBomLine, responses(
+    (status = 200, description = "BOM line updated successfully", body = SuccessResponse<BomLine>),
+    (status = 400, description = "Validation error", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["bom"], security(("bearerAuth" = [])))]
+pub async fn update_bom_line(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path((id, line_id)): Path<(Uuid, Uuid)>,
+    Json(updated_line): Json<UpdateBomLine>,
+) -> Result<Json<SuccessResponse<BomLine>>, AppError> {
+    let line = service_update_bom_line(claims, &pool, id, line_id, updated_line).await?;
+    Ok(Json(SuccessResponse::ok(line)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(delete, path = "/parts/{id}/bom/{line_id}", params(
+    ("id" = Uuid, Path, description = "Parent part ID"),
+    ("line_id" = Uuid, Path, description = "BOM line ID to delete"),
+), responses(
+    (status = 204, description = "BOM line deleted successfully"),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["bom"], security(("bearerAuth" = [])))]
+pub async fn delete_bom_line(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path((id, line_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<SuccessResponse<()>>, AppError> {
+    service_delete_bom_line(claims, &pool, id, line_id).await?;
+    Ok(Json(SuccessResponse::no_content()))
+}
@@ -0,0 +1,127 @@
+use crate::auth::domain::Claims;
+use crate::bom::domain::{BomLine, NewBomLine};
+use crate::errors::app_error::AppError;
+use crate::errors::conflict::conflict_error;
+use crate::errors::validation::extract_validation_errors;
+use crate::part::service::auth::ensure_admin_or_owner;
+use crate::part::service::lifecycle::ensure_not_locked;
+
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::cycle::ensure_no_cycle;
+use super::get::fetch_bom_line;
+
+pub async fn add_bom_line(
+    claims: Claims,
+    pool: &PgPool,
+    parent_id: Uuid,
+    new_line: NewBomLine,
+) -> Result<BomLine, AppError> {
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to add BOM line".to_string())
+    })?;
+
+    let line = insert_bom_line(&mut tx, &claims, parent_id, new_line).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing BOM line: {}", e);
+        AppError::DatabaseError("Failed to add BOM line".to_string())
+    })?;
+
+    info!("BOM line added: {} -> {}", parent_id, line.child_id);
+    Ok(line)
+}
+
+/// トランザクション内で構成行を追加する
+pub async fn insert_bom_line(
+    conn: &mut PgConnection,
+    claims: &Claims,
+    parent_id: Uuid,
+    new_line: NewBomLine,
+) -> Result<BomLine, AppError> {
+    new_line
+        .validate()
+        .map_err(|e| AppError::ValidationError(extract_validation_errors(e)))?;
+
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    ensure_admin_or_owner(claims.clone(), &mut *conn, parent_id).await?;
+    ensure_not_locked(claims, &mut *conn, parent_id).await?;
+
+    // 循環チェックと追加の間に他の構成変更が割り込まないようにする
+    sqlx::query!("LOCK TABLE bom_lines IN SHARE ROW EXCLUSIVE MODE")
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            error!("DB error during locking BOM: {}", e);
+            AppError::DatabaseError("Failed to add BOM line".to_string())
+        })?;
+
+    let child_exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM parts WHERE id = $1 AND deleted_at IS NULL) AS "exists!""#,
+        new_line.child_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching child part: {}", e);
+        AppError::DatabaseError("Failed to add BOM line".to_string())
+    })?;
+
+    if !child_exists {
+        info!("Child part not found: {}", new_line.child_id);
+        return Err(AppError::NotFound(format!(
+            "Part not found: {}",
+            new_line.child_id
+        )));
+    }
+
+    let existing_line = sqlx::query_scalar!(
+        r#"SELECT id FROM bom_lines WHERE parent_id = $1 AND child_id = $2"#,
+        parent_id,
+        new_line.child_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching BOM line: {}", e);
+        AppError::DatabaseError("Failed to add BOM line".to_string())
+    })?;
+
+    if let Some(existing_id) = existing_line {
+        return Err(conflict_error(
+            "child_id",
+            "This part is already in the BOM",
+            Some(existing_id),
+        ));
+    }
+
+    ensure_no_cycle(conn, parent_id, new_line.child_id).await?;
+
+    let id = sqlx::query_scalar!(
+        r#"INSERT INTO bom_lines
+        (parent_id, child_id, quantity, unit_of_measure, find_number, reference_designators, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id"#,
+        parent_id,
+        new_line.child_id,
+        new_line.quantity,
+        new_line.unit_of_measure,
+        new_line.find_number,
+        &new_line.reference_designators,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during BOM line insertion: {}", e);
+        AppError::DatabaseError("Failed to add BOM line".to_string())
+    })?;
+
+    fetch_bom_line(conn, id).await
+}
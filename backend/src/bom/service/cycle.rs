@@ -0,0 +1,49 @@
+use sqlx::PgConnection;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::errors::app_error::AppError;
+use crate::errors::validation::field_validation_error;
+
+/// `parent` に `child` を追加すると部品が自分自身を含む構成になる場合はエラーにする
+pub async fn ensure_no_cycle(
+    conn: &mut PgConnection,
+    parent_id: Uuid,
+    child_id: Uuid,
+) -> Result<(), AppError> {
+    if parent_id == child_id {
+        return Err(AppError::ValidationError(field_validation_error(
+            "child_id",
+            "A part cannot contain itself",
+        )));
+    }
+
+    let creates_cycle = sqlx::query_scalar!(
+        r#"WITH RECURSIVE descendants AS (
+            SELECT child_id FROM bom_lines WHERE parent_id = $1
+            UNION
+            SELECT b.child_id FROM bom_lines b
+            JOIN descendants d ON b.parent_id = d.child_id
+        )
+        SELECT EXISTS (SELECT 1 FROM descendants WHERE child_id = $2) AS "exists!"
+        "#,
+        child_id,
+        parent_id
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during BOM cycle check: {}", e);
+        AppError::DatabaseError("BOM cycle check failed".to_string())
+    })?;
+
+    if creates_cycle {
+        info!("BOM cycle rejected: {} -> {}", parent_id, child_id);
+        Err(AppError::ValidationError(field_validation_error(
+            "child_id",
+            "Adding this part would make the assembly contain itself",
+        )))
+    } else {
+        Ok(())
+    }
+}
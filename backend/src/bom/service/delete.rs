@@ -0,0 +1,41 @@
+use crate::{
+    auth::domain::Claims, errors::app_error::AppError, part::service::auth::ensure_admin_or_owner,
+    part::service::lifecycle::ensure_not_locked,
+};
+
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+pub async fn delete_bom_line(
+    claims: Claims,
+    pool: &PgPool,
+    parent_id: Uuid,
+    line_id: Uuid,
+) -> Result<(), AppError> {
+    ensure_not_locked(&claims, pool, parent_id).await?;
+    ensure_admin_or_owner(claims, pool, parent_id).await?;
+
+    let result = sqlx::query!(
+        r#"DELETE FROM bom_lines WHERE id = $1 AND parent_id = $2"#,
+        line_id,
+        parent_id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during deleting BOM line: {}", e);
+        AppError::DatabaseError("Failed to delete BOM line".to_string())
+    })?;
+
+    if result.rows_affected() == 0 {
+        info!("BOM line not found for deletion: {}", line_id);
+        Err(AppError::NotFound(format!(
+            "BOM line not found for deletion: {}",
+            line_id
+        )))
+    } else {
+        info!("BOM line deleted successfully: {}", line_id);
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use crate::bom::domain::{BomExportLine, BomMode};
+use crate::errors::app_error::AppError;
+use crate::responses::export::{EXPORT_CHUNK, RowSender};
+
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// 構成を深さ優先順 (同じ親の中では find_number 順) に 1 行ずつ送る。
+/// 共通のサブアセンブリは `get_bom` と同じく使用箇所ごとに展開する。
+/// 並び順のキー (`path`) で `EXPORT_CHUNK` 件ずつ読み、読み終えるたびに接続をプールに返す
+pub async fn export_bom(
+    pool: PgPool,
+    part_id: Uuid,
+    mode: BomMode,
+    sender: RowSender<BomExportLine>,
+) {
+    let mut after: Option<Vec<String>> = None;
+    let mut exported = 0;
+    loop {
+        let rows = sqlx::query!(
+            r#"WITH RECURSIVE tree AS (
+                SELECT b.id, b.child_id, 1 AS level, b.quantity AS extended_quantity,
+                       ARRAY[lpad(COALESCE(b.find_number, 2147483647)::text, 10, '0') || b.id::text] AS path
+                FROM bom_lines b
+                JOIN parts c ON c.id = b.child_id AND c.deleted_at IS NULL
+                WHERE b.parent_id = $1
+                UNION ALL
+                SELECT b.id, b.child_id, t.level + 1, t.extended_quantity * b.quantity,
+                       t.path || (lpad(COALESCE(b.find_number, 2147483647)::text, 10, '0') || b.id::text)
+                FROM bom_lines b
+                JOIN parts c ON c.id = b.child_id AND c.deleted_at IS NULL
+                JOIN tree t ON b.parent_id = t.child_id
+                WHERE $2
+            )
+            SELECT t.path AS "path!", t.level AS "level!", pp.part_number AS parent_part_number,
+                   p.part_number AS child_part_number, p.name AS child_name,
+                   b.quantity, t.extended_quantity AS "extended_quantity!",
+                   b.unit_of_measure, b.find_number, b.reference_designators
+            FROM tree t
+            JOIN bom_lines b ON b.id = t.id
+            JOIN parts p ON p.id = b.child_id
+            JOIN parts pp ON pp.id = b.parent_id
+            WHERE $3::text[] IS NULL OR t.path > $3
+            ORDER BY t.path
+            LIMIT $4
+            "#,
+            part_id,
+            mode == BomMode::Exploded,
+            after.as_deref() as Option<&[String]>,
+            EXPORT_CHUNK
+        )
+        .fetch_all(&pool)
+        .await;
+
+        let rows = match rows {
+            Ok(rows) => rows,
+            Err(e) => {
+                error!("DB error during exporting BOM: {}", e);
+                let _ = sender
+                    .send(Err(AppError::DatabaseError(
+                        "Failed to fetch BOM".to_string(),
+                    )))
+                    .await;
+                return;
+            }
+        };
+        let done = (rows.len() as i64) < EXPORT_CHUNK;
+
+        for row in rows {
+            after = Some(row.path);
+            let line = BomExportLine {
+                level: row.level,
+                parent_part_number: row.parent_part_number,
+                child_part_number: row.child_part_number,
+                child_name: row.child_name,
+                quantity: row.quantity,
+                extended_quantity: row.extended_quantity,
+                unit_of_measure: row.unit_of_measure,
+                find_number: row.find_number,
+                reference_designators: row.reference_designators,
+            };
+            // 受信側が閉じていればクライアントが切断している
+            if sender.send(Ok(line)).await.is_err() {
+                return;
+            }
+            exported += 1;
+        }
+        if done {
+            break;
+        }
+    }
+
+    info!("Exported {} BOM lines of part {}", exported, part_id);
+}
@@ -0,0 +1,94 @@
+use crate::bom::domain::{BomLine, BomMode, BomTree, build_bom_nodes};
+use crate::errors::app_error::AppError;
+use crate::part::service::get_part;
+
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+
+pub async fn fetch_bom_line(conn: &mut PgConnection, id: Uuid) -> Result<BomLine, AppError> {
+    sqlx::query_as!(
+        BomLine,
+        r#"SELECT b.id, b.parent_id, b.child_id,
+                  p.part_number AS child_part_number, p.name AS child_name,
+                  b.quantity, b.unit_of_measure, b.find_number, b.reference_designators,
+                  b.created_at, b.created_by, b.updated_at
+        FROM bom_lines b
+        JOIN parts p ON p.id = b.child_id
+        WHERE b.id = $1
+        "#,
+        id
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching BOM line: {}", e);
+        AppError::DatabaseError("Failed to fetch BOM line".to_string())
+    })
+}
+
+async fn fetch_direct_lines(pool: &PgPool, part_id: Uuid) -> Result<Vec<BomLine>, sqlx::Error> {
+    sqlx::query_as!(
+        BomLine,
+        r#"SELECT b.id, b.parent_id, b.child_id,
+                  p.part_number AS child_part_number, p.name AS child_name,
+                  b.quantity, b.unit_of_measure, b.find_number, b.reference_designators,
+                  b.created_at, b.created_by, b.updated_at
+        FROM bom_lines b
+        JOIN parts p ON p.id = b.child_id
+        WHERE b.parent_id = $1 AND p.deleted_at IS NULL
+        "#,
+        part_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+async fn fetch_exploded_lines(pool: &PgPool, part_id: Uuid) -> Result<Vec<BomLine>, sqlx::Error> {
+    sqlx::query_as!(
+        BomLine,
+        r#"WITH RECURSIVE tree AS (
+            SELECT id, child_id FROM bom_lines WHERE parent_id = $1
+            UNION
+            SELECT b.id, b.child_id FROM bom_lines b
+            JOIN tree t ON b.parent_id = t.child_id
+        )
+        SELECT b.id, b.parent_id, b.child_id,
+               p.part_number AS child_part_number, p.name AS child_name,
+               b.quantity, b.unit_of_measure, b.find_number, b.reference_designators,
+               b.created_at, b.created_by, b.updated_at
+        FROM bom_lines b
+        JOIN parts p ON p.id = b.child_id
+        WHERE b.id IN (SELECT id FROM tree) AND p.deleted_at IS NULL
+        "#,
+        part_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_bom(pool: &PgPool, part_id: Uuid, mode: BomMode) -> Result<BomTree, AppError> {
+    let part = get_part(pool, part_id).await?;
+
+    let lines = match mode {
+        BomMode::Single => fetch_direct_lines(pool, part_id).await,
+        BomMode::Exploded => fetch_exploded_lines(pool, part_id).await,
+    }
+    .map_err(|e| {
+        error!("DB error during fetching BOM: {}", e);
+        AppError::DatabaseError("Failed to fetch BOM".to_string())
+    })?;
+
+    let max_level = match mode {
+        BomMode::Single => Some(1),
+        BomMode::Exploded => None,
+    };
+
+    info!("Fetched {} BOM lines of part {}", lines.len(), part_id);
+    Ok(BomTree {
+        part_id: part.id,
+        part_number: part.part_number,
+        name: part.name,
+        children: build_bom_nodes(part_id, &lines, max_level),
+    })
+}
@@ -0,0 +1,12 @@
+pub mod create;
+pub mod cycle;
+pub mod delete;
+pub mod export;
+pub mod get;
+pub mod update;
+
+pub use create::add_bom_line;
+pub use delete::delete_bom_line;
+pub use export::export_bom;
+pub use get::get_bom;
+pub use update::update_bom_line;
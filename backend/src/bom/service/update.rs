@@ -0,0 +1,74 @@
+use crate::auth::domain::Claims;
+use crate::bom::domain::{BomLine, UpdateBomLine};
+use crate::errors::app_error::AppError;
+use crate::errors::validation::extract_validation_errors;
+use crate::part::service::auth::ensure_admin_or_owner;
+use crate::part::service::lifecycle::ensure_not_locked;
+
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::get::fetch_bom_line;
+
+pub async fn update_bom_line(
+    claims: Claims,
+    pool: &PgPool,
+    parent_id: Uuid,
+    line_id: Uuid,
+    updated_line: UpdateBomLine,
+) -> Result<BomLine, AppError> {
+    updated_line
+        .validate()
+        .map_err(|e| AppError::ValidationError(extract_validation_errors(e)))?;
+
+    ensure_not_locked(&claims, pool, parent_id).await?;
+    ensure_admin_or_owner(claims, pool, parent_id).await?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to update BOM line".to_string())
+    })?;
+
+    let result = sqlx::query!(
+        r#"UPDATE bom_lines
+        SET quantity = $1,
+            unit_of_measure = $2,
+            find_number = $3,
+            reference_designators = $4,
+            updated_at = NOW()
+        WHERE id = $5 AND parent_id = $6
+        "#,
+        updated_line.quantity,
+        updated_line.unit_of_measure,
+        updated_line.find_number,
+        &updated_line.reference_designators,
+        line_id,
+        parent_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during updating BOM line: {}", e);
+        AppError::DatabaseError("Failed to update BOM line".to_string())
+    })?;
+
+    if result.rows_affected() == 0 {
+        info!("BOM line not found for update: {}", line_id);
+        return Err(AppError::NotFound(format!(
+            "BOM line not found for update: {}",
+            line_id
+        )));
+    }
+
+    let line = fetch_bom_line(&mut tx, line_id).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing BOM line update: {}", e);
+        AppError::DatabaseError("Failed to update BOM line".to_string())
+    })?;
+
+    info!("BOM line updated successfully: {}", line_id);
+    Ok(line)
+}
@@ -0,0 +1,151 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::part::domain::NewPart;
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub struct Eco {
+    pub id: Uuid,
+    pub number: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub status: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct NewEco {
+    #[validate(length(min = 1, message = "title must not be empty"))]
+    pub title: String,
+    pub description: Option<String>,
+}
+
+/// 変更対象の部品と、承認時にリリースするリビジョンの内容
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub struct EcoItem {
+    pub id: Uuid,
+    pub eco_id: Uuid,
+    pub part_id: Uuid,
+    pub part_number: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: Option<String>,
+    pub released_revision: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct NewEcoItem {
+    pub part_id: Uuid,
+    #[serde(flatten)]
+    pub proposed: NewPart,
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub struct EcoApproval {
+    pub id: Uuid,
+    pub eco_id: Uuid,
+    pub approver_id: Uuid,
+    pub decision: String,
+    pub comment: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct EcoDetail {
+    #[serde(flatten)]
+    pub eco: Eco,
+    pub items: Vec<EcoItem>,
+    pub approvals: Vec<EcoApproval>,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct SubmitEco {
+    #[validate(length(min = 1, message = "approver_ids must not be empty"))]
+    pub approver_ids: Vec<Uuid>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct EcoDecision {
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EcoStatus {
+    Draft,
+    InReview,
+    Approved,
+    Rejected,
+}
+
+impl EcoStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EcoStatus::Draft => "draft",
+            EcoStatus::InReview => "in_review",
+            EcoStatus::Approved => "approved",
+            EcoStatus::Rejected => "rejected",
+        }
+    }
+}
+
+impl From<&str> for EcoStatus {
+    fn from(s: &str) -> Self {
+        match s {
+            "in_review" => EcoStatus::InReview,
+            "approved" => EcoStatus::Approved,
+            "rejected" => EcoStatus::Rejected,
+            _ => EcoStatus::Draft,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApprovalDecision {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl ApprovalDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalDecision::Pending => "pending",
+            ApprovalDecision::Approved => "approved",
+            ApprovalDecision::Rejected => "rejected",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use validator::Validate;
+
+    use super::{EcoStatus, SubmitEco};
+
+    #[test]
+    fn test_invalid_submit_without_approvers() {
+        let submit = SubmitEco {
+            approver_ids: Vec::new(),
+        };
+        assert!(submit.validate().is_err())
+    }
+
+    #[test]
+    fn test_eco_status_round_trip() {
+        for status in [
+            EcoStatus::Draft,
+            EcoStatus::InReview,
+            EcoStatus::Approved,
+            EcoStatus::Rejected,
+        ] {
+            assert_eq!(EcoStatus::from(status.as_str()), status);
+        }
+    }
+}
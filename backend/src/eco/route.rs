@@ -0,0 +1,156 @@
+use crate::auth::domain::Claims;
+use crate::eco::domain::{Eco, EcoDecision, EcoDetail, EcoItem, NewEco, NewEcoItem, SubmitEco};
+use crate::eco::service::{
+    add_eco_item as service_add_eco_item, approve_eco as service_approve_eco,
+    create_eco as service_create_eco, get_eco as service_get_eco, get_ecos as service_get_ecos,
+    reject_eco as service_reject_eco, remove_eco_item as service_remove_eco_item,
+    submit_eco as service_submit_eco,
+};
+use crate::errors::app_error::AppError;
+use crate::errors::conflict::ConflictErrorResponse;
+use crate::errors::validation::ValidationErrorResponse;
+use crate::responses::error::ErrorResponse;
+use crate::responses::success::SuccessResponse;
+
+use axum::Extension;
+use axum::{Json, extract::Path, extract::State};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/ecos", request_body = NewEco, responses(
+    (status = 201, description = "ECO created successfully", body = SuccessResponse<Eco>),
+    (status = 400, description = "Validation error", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["ecos"], security(("bearerAuth" = [])))]
+pub async fn create_eco(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Json(new_eco): Json<NewEco>,
+) -> Result<Json<SuccessResponse<Eco>>, AppError> {
+    let eco = service_create_eco(claims, &pool, new_eco).await?;
+    Ok(Json(SuccessResponse::created(eco)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/ecos", responses(
+    (status = 200, description = "Fetched ECOs successfully", body = SuccessResponse<Vec<Eco>>),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["ecos"], security(("bearerAuth" = [])))]
+pub async fn get_ecos(
+    State(pool): State<PgPool>,
+) -> Result<Json<SuccessResponse<Vec<Eco>>>, AppError> {
+    let ecos = service_get_ecos(&pool).await?;
+    Ok(Json(SuccessResponse::ok(ecos)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/ecos/{id}", params(("id" = Uuid, Path, description = "ECO ID to fetch")), responses(
+    (status = 200, description = "Fetched ECO successfully", body = SuccessResponse<EcoDetail>),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["ecos"], security(("bearerAuth" = [])))]
+pub async fn get_eco(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<EcoDetail>>, AppError> {
+    let eco = service_get_eco(&pool, id).await?;
+    Ok(Json(SuccessResponse::ok(eco)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/ecos/{id}/items", params(("id" = Uuid, Path, description = "ECO ID")), request_body = NewEcoItem, responses(
+    (status = 201, description = "Affected part added successfully", body = SuccessResponse<EcoItem>),
+    (status = 400, description = "Validation error", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 409, description = "Proposed part number already exists or is reserved", body = ConflictErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["ecos"], security(("bearerAuth" = [])))]
+pub async fn add_eco_item(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(new_item): Json<NewEcoItem>,
+) -> Result<Json<SuccessResponse<EcoItem>>, AppError> {
+    let item = service_add_eco_item(claims, &pool, id, new_item).await?;
+    Ok(Json(SuccessResponse::created(item)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(delete, path = "/ecos/{id}/items/{item_id}", params(
+    ("id" = Uuid, Path, description = "ECO ID"),
+    ("item_id" = Uuid, Path, description = "ECO item ID to remove"),
+), responses(
+    (status = 204, description = "Affected part removed successfully"),
+    (status = 400, description = "Validation error", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["ecos"], security(("bearerAuth" = [])))]
+pub async fn remove_eco_item(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path((id, item_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<SuccessResponse<()>>, AppError> {
+    service_remove_eco_item(claims, &pool, id, item_id).await?;
+    Ok(Json(SuccessResponse::no_content()))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/ecos/{id}/submit", params(("id" = Uuid, Path, description = "ECO ID to submit")), request_body = SubmitEco, responses(
+    (status = 200, description = "ECO routed to approvers", body = SuccessResponse<EcoDetail>),
+    (status = 400, description = "Validation error", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["ecos"], security(("bearerAuth" = [])))]
+pub async fn submit_eco(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(submit): Json<SubmitEco>,
+) -> Result<Json<SuccessResponse<EcoDetail>>, AppError> {
+    let eco = service_submit_eco(claims, &pool, id, submit).await?;
+    Ok(Json(SuccessResponse::ok(eco)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/ecos/{id}/approve", params(("id" = Uuid, Path, description = "ECO ID to approve")), request_body = EcoDecision, responses(
+    (status = 200, description = "Approval recorded (revisions released once all approvers approved)", body = SuccessResponse<EcoDetail>),
+    (status = 400, description = "Validation error", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 409, description = "Proposed part number already exists", body = ConflictErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["ecos"], security(("bearerAuth" = [])))]
+pub async fn approve_eco(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(decision): Json<EcoDecision>,
+) -> Result<Json<SuccessResponse<EcoDetail>>, AppError> {
+    let eco = service_approve_eco(claims, &pool, id, decision).await?;
+    Ok(Json(SuccessResponse::ok(eco)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/ecos/{id}/reject", params(("id" = Uuid, Path, description = "ECO ID to reject")), request_body = EcoDecision, responses(
+    (status = 200, description = "ECO rejected", body = SuccessResponse<EcoDetail>),
+    (status = 400, description = "Validation error", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["ecos"], security(("bearerAuth" = [])))]
+pub async fn reject_eco(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(decision): Json<EcoDecision>,
+) -> Result<Json<SuccessResponse<EcoDetail>>, AppError> {
+    let eco = service_reject_eco(claims, &pool, id, decision).await?;
+    Ok(Json(SuccessResponse::ok(eco)))
+}
@@ -0,0 +1,60 @@
+use sqlx::PgConnection;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::{
+    auth::domain::{Claims, Role},
+    eco::domain::{Eco, EcoStatus},
+    errors::{app_error::AppError, validation::field_validation_error},
+};
+
+/// ECO を行ロック付きで取得する
+pub async fn lock_eco(conn: &mut PgConnection, id: Uuid) -> Result<Eco, AppError> {
+    sqlx::query_as!(
+        Eco,
+        r#"SELECT id, number, title, description, status, created_at, created_by, updated_at, decided_at
+        FROM ecos
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching ECO: {}", e);
+        AppError::DatabaseError("Failed to fetch ECO".to_string())
+    })?
+    .ok_or_else(|| AppError::NotFound(format!("ECO not found: {}", id)))
+}
+
+pub fn ensure_eco_admin_or_owner(claims: &Claims, eco: &Eco) -> Result<(), AppError> {
+    if claims.role == Role::Admin {
+        return Ok(());
+    }
+
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    if eco.created_by == Some(user_id) {
+        Ok(())
+    } else {
+        Err(AppError::Unauthorized("You do not own this ECO.".into()))
+    }
+}
+
+pub fn ensure_eco_status(eco: &Eco, expected: EcoStatus) -> Result<(), AppError> {
+    if EcoStatus::from(eco.status.as_str()) == expected {
+        Ok(())
+    } else {
+        Err(AppError::ValidationError(field_validation_error(
+            "status",
+            &format!(
+                "ECO {} is '{}' but must be '{}'",
+                eco.number,
+                eco.status,
+                expected.as_str()
+            ),
+        )))
+    }
+}
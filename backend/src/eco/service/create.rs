@@ -0,0 +1,50 @@
+use crate::auth::domain::Claims;
+use crate::eco::domain::{Eco, NewEco};
+use crate::errors::app_error::AppError;
+use crate::errors::validation::extract_validation_errors;
+
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+use validator::Validate;
+
+pub async fn insert_eco(
+    conn: &mut PgConnection,
+    new_eco: &NewEco,
+    user_id: Uuid,
+) -> Result<Eco, AppError> {
+    sqlx::query_as!(
+        Eco,
+        r#"INSERT INTO ecos (title, description, created_by)
+           VALUES ($1, $2, $3)
+           RETURNING id, number, title, description, status, created_at, created_by, updated_at, decided_at"#,
+        new_eco.title,
+        new_eco.description,
+        user_id
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during ECO insertion: {}", e);
+        AppError::DatabaseError("DB insert failed".to_string())
+    })
+}
+
+pub async fn create_eco(claims: Claims, pool: &PgPool, new_eco: NewEco) -> Result<Eco, AppError> {
+    new_eco
+        .validate()
+        .map_err(|e| AppError::ValidationError(extract_validation_errors(e)))?;
+
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut conn = pool.acquire().await.map_err(|e| {
+        error!("DB error during acquiring connection: {}", e);
+        AppError::DatabaseError("DB insert failed".to_string())
+    })?;
+
+    let eco = insert_eco(&mut conn, &new_eco, user_id).await?;
+
+    info!("ECO created successfully: {}", eco.number);
+    Ok(eco)
+}
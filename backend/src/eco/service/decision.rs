@@ -0,0 +1,265 @@
+use crate::audit::domain::{AuditAction, AuditEntity};
+use crate::audit::service::{record_audit, snapshot};
+use crate::auth::domain::Claims;
+use crate::eco::domain::{ApprovalDecision, EcoDecision, EcoDetail, EcoItem, EcoStatus};
+use crate::errors::app_error::AppError;
+use crate::errors::conflict::UniqueViolation;
+use crate::part::domain::{NewPart, RevisionScheme, RevisionStatus};
+use crate::part::service::revision::{
+    insert_revision, lock_latest_revision, mark_revision_released, update_working_revision,
+};
+
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::auth::{ensure_eco_status, lock_eco};
+use super::get::{fetch_eco_detail, fetch_eco_items};
+
+async fn record_decision(
+    conn: &mut PgConnection,
+    eco_id: Uuid,
+    approver_id: Uuid,
+    decision: ApprovalDecision,
+    comment: Option<String>,
+) -> Result<(), AppError> {
+    let result = sqlx::query!(
+        r#"UPDATE eco_approvals
+        SET decision = $1,
+            comment = $2,
+            decided_at = NOW()
+        WHERE eco_id = $3 AND approver_id = $4 AND decision = $5
+        "#,
+        decision.as_str(),
+        comment,
+        eco_id,
+        approver_id,
+        ApprovalDecision::Pending.as_str()
+    )
+    .execute(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during recording ECO decision: {}", e);
+        AppError::DatabaseError("Failed to record ECO decision".to_string())
+    })?;
+
+    if result.rows_affected() == 0 {
+        Err(AppError::Unauthorized(
+            "You are not a pending approver of this ECO.".into(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+async fn close_eco(conn: &mut PgConnection, id: Uuid, status: EcoStatus) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"UPDATE ecos SET status = $1, decided_at = NOW(), updated_at = NOW() WHERE id = $2"#,
+        status.as_str(),
+        id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during closing ECO: {}", e);
+        AppError::DatabaseError("Failed to update ECO status".to_string())
+    })?;
+    sqlx::query!(r#"UPDATE eco_items SET open = FALSE WHERE eco_id = $1"#, id)
+        .execute(conn)
+        .await
+        .map_err(|e| {
+            error!("DB error during closing ECO items: {}", e);
+            AppError::DatabaseError("Failed to update ECO status".to_string())
+        })?;
+    Ok(())
+}
+
+/// ECO の変更内容を新しいリビジョンとしてリリースし、部品をリリース状態にする
+async fn release_item(
+    conn: &mut PgConnection,
+    item: &EcoItem,
+    user_id: Uuid,
+) -> Result<String, AppError> {
+    let proposed = NewPart {
+        part_number: item.part_number.clone(),
+        name: item.name.clone(),
+        description: item.description.clone(),
+        kind: item.kind.clone(),
+    };
+
+    let latest = lock_latest_revision(conn, item.part_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Part not found: {}", item.part_id)))?;
+
+    let revision = match RevisionStatus::from(latest.status.as_str()) {
+        RevisionStatus::Working => update_working_revision(conn, latest.id, &proposed).await?,
+        RevisionStatus::Released => {
+            let next = RevisionScheme::from_env().next(&latest.revision);
+            insert_revision(conn, item.part_id, &next, &proposed, user_id).await?
+        }
+    };
+    let released = mark_revision_released(conn, revision.id, user_id).await?;
+    let before = snapshot(&mut *conn, AuditEntity::Part, item.part_id).await?;
+
+    let updated = sqlx::query!(
+        r#"UPDATE parts
+        SET part_number = $1,
+            name = $2,
+            description = $3,
+            kind = $4,
+            status = COALESCE(
+                (SELECT name FROM lifecycle_states WHERE releases_revision ORDER BY sort_order LIMIT 1),
+                status
+            ),
+            updated_at = NOW()
+        WHERE id = $5 AND deleted_at IS NULL
+        "#,
+        proposed.part_number,
+        proposed.name,
+        proposed.description,
+        proposed.kind,
+        item.part_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        if let Some(violation) = UniqueViolation::from_error(&e) {
+            return violation.into_conflict(None);
+        }
+        error!("DB error during updating released part: {}", e);
+        AppError::DatabaseError("Failed to release ECO".to_string())
+    })?;
+    if updated.rows_affected() == 0 {
+        info!(
+            "Part {} of ECO item {} is in the trash",
+            item.part_id, item.id
+        );
+        return Err(AppError::NotFound(format!(
+            "Part not found: {}",
+            item.part_id
+        )));
+    }
+
+    record_audit(
+        &mut *conn,
+        AuditEntity::Part,
+        item.part_id,
+        AuditAction::Release,
+        Some(user_id),
+        before,
+    )
+    .await?;
+
+    sqlx::query!(
+        r#"UPDATE eco_items SET released_revision = $1 WHERE id = $2"#,
+        released.revision,
+        item.id
+    )
+    .execute(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during updating ECO item: {}", e);
+        AppError::DatabaseError("Failed to release ECO".to_string())
+    })?;
+
+    Ok(released.revision)
+}
+
+/// 承認を記録し、全員が承認した時点で対象部品の新リビジョンを一括リリースする
+pub async fn approve_eco(
+    claims: Claims,
+    pool: &PgPool,
+    id: Uuid,
+    decision: EcoDecision,
+) -> Result<EcoDetail, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to approve ECO".to_string())
+    })?;
+
+    let eco = lock_eco(&mut tx, id).await?;
+    ensure_eco_status(&eco, EcoStatus::InReview)?;
+
+    record_decision(
+        &mut tx,
+        id,
+        user_id,
+        ApprovalDecision::Approved,
+        decision.comment,
+    )
+    .await?;
+
+    let pending = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM eco_approvals WHERE eco_id = $1 AND decision <> $2"#,
+        id,
+        ApprovalDecision::Approved.as_str()
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during counting ECO approvals: {}", e);
+        AppError::DatabaseError("Failed to approve ECO".to_string())
+    })?;
+
+    if pending == 0 {
+        for item in fetch_eco_items(&mut tx, id).await? {
+            let revision = release_item(&mut tx, &item, user_id).await?;
+            info!(
+                "ECO {} released part {} revision {}",
+                eco.number, item.part_id, revision
+            );
+        }
+        close_eco(&mut tx, id, EcoStatus::Approved).await?;
+    }
+
+    let detail = fetch_eco_detail(&mut tx, id).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing ECO approval: {}", e);
+        AppError::DatabaseError("Failed to approve ECO".to_string())
+    })?;
+
+    info!("ECO {} approved by {}", eco.number, user_id);
+    Ok(detail)
+}
+
+pub async fn reject_eco(
+    claims: Claims,
+    pool: &PgPool,
+    id: Uuid,
+    decision: EcoDecision,
+) -> Result<EcoDetail, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to reject ECO".to_string())
+    })?;
+
+    let eco = lock_eco(&mut tx, id).await?;
+    ensure_eco_status(&eco, EcoStatus::InReview)?;
+
+    record_decision(
+        &mut tx,
+        id,
+        user_id,
+        ApprovalDecision::Rejected,
+        decision.comment,
+    )
+    .await?;
+    close_eco(&mut tx, id, EcoStatus::Rejected).await?;
+
+    let detail = fetch_eco_detail(&mut tx, id).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing ECO rejection: {}", e);
+        AppError::DatabaseError("Failed to reject ECO".to_string())
+    })?;
+
+    info!("ECO {} rejected by {}", eco.number, user_id);
+    Ok(detail)
+}
@@ -0,0 +1,108 @@
+use crate::eco::domain::{Eco, EcoApproval, EcoDetail, EcoItem};
+use crate::errors::app_error::AppError;
+
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+
+pub async fn get_ecos(pool: &PgPool) -> Result<Vec<Eco>, AppError> {
+    let ecos = sqlx::query_as!(
+        Eco,
+        r#"SELECT id, number, title, description, status, created_at, created_by, updated_at, decided_at
+        FROM ecos
+        ORDER BY created_at DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching ECOs: {}", e);
+        AppError::DatabaseError("DB select failed".to_string())
+    })?;
+
+    info!("Fetched {} ECOs successfully", ecos.len());
+    Ok(ecos)
+}
+
+pub async fn fetch_eco_items(
+    conn: &mut PgConnection,
+    eco_id: Uuid,
+) -> Result<Vec<EcoItem>, AppError> {
+    sqlx::query_as!(
+        EcoItem,
+        r#"SELECT id, eco_id, part_id, part_number, name, description, kind, released_revision, created_at
+        FROM eco_items
+        WHERE eco_id = $1
+        ORDER BY created_at
+        "#,
+        eco_id
+    )
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching ECO items: {}", e);
+        AppError::DatabaseError("Failed to fetch ECO items".to_string())
+    })
+}
+
+pub async fn fetch_eco_approvals(
+    conn: &mut PgConnection,
+    eco_id: Uuid,
+) -> Result<Vec<EcoApproval>, AppError> {
+    sqlx::query_as!(
+        EcoApproval,
+        r#"SELECT id, eco_id, approver_id, decision, comment, decided_at
+        FROM eco_approvals
+        WHERE eco_id = $1
+        ORDER BY approver_id
+        "#,
+        eco_id
+    )
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching ECO approvals: {}", e);
+        AppError::DatabaseError("Failed to fetch ECO approvals".to_string())
+    })
+}
+
+pub async fn fetch_eco_detail(conn: &mut PgConnection, id: Uuid) -> Result<EcoDetail, AppError> {
+    let eco = sqlx::query_as!(
+        Eco,
+        r#"SELECT id, number, title, description, status, created_at, created_by, updated_at, decided_at
+        FROM ecos
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching ECO: {}", e);
+        AppError::DatabaseError("Failed to fetch ECO".to_string())
+    })?
+    .ok_or_else(|| {
+        info!("ECO not found: {}", id);
+        AppError::NotFound(format!("ECO not found: {}", id))
+    })?;
+
+    let items = fetch_eco_items(conn, id).await?;
+    let approvals = fetch_eco_approvals(conn, id).await?;
+
+    Ok(EcoDetail {
+        eco,
+        items,
+        approvals,
+    })
+}
+
+pub async fn get_eco(pool: &PgPool, id: Uuid) -> Result<EcoDetail, AppError> {
+    let mut conn = pool.acquire().await.map_err(|e| {
+        error!("DB error during acquiring connection: {}", e);
+        AppError::DatabaseError("Failed to fetch ECO".to_string())
+    })?;
+
+    let detail = fetch_eco_detail(&mut conn, id).await?;
+    info!("ECO found: {}", detail.eco.number);
+    Ok(detail)
+}
@@ -0,0 +1,240 @@
+use crate::auth::domain::Claims;
+use crate::eco::domain::{EcoItem, EcoStatus, NewEcoItem};
+use crate::errors::app_error::AppError;
+use crate::errors::validation::{extract_validation_errors, field_validation_error};
+use crate::part::domain::NewPart;
+use crate::part::service::numbering::ensure_part_number_available;
+
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::auth::{ensure_eco_admin_or_owner, ensure_eco_status, lock_eco};
+
+/// 部品を含む作成中 (draft) の ECO の変更内容を取得する
+pub async fn find_open_eco_item(
+    conn: &mut PgConnection,
+    part_id: Uuid,
+) -> Result<Option<EcoItem>, AppError> {
+    sqlx::query_as!(
+        EcoItem,
+        r#"SELECT i.id, i.eco_id, i.part_id, i.part_number, i.name, i.description, i.kind,
+                  i.released_revision, i.created_at
+        FROM eco_items i
+        JOIN ecos e ON e.id = i.eco_id
+        WHERE i.part_id = $1 AND e.status = $2
+        FOR UPDATE OF i
+        "#,
+        part_id,
+        EcoStatus::Draft.as_str()
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching open ECO item: {}", e);
+        AppError::DatabaseError("Failed to fetch ECO item".to_string())
+    })
+}
+
+/// 部品の編集内容を ECO の変更内容に反映する
+pub async fn sync_eco_item(
+    conn: &mut PgConnection,
+    item_id: Uuid,
+    proposed: &NewPart,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"UPDATE eco_items
+        SET part_number = $1,
+            name = $2,
+            description = $3,
+            kind = $4
+        WHERE id = $5
+        "#,
+        proposed.part_number,
+        proposed.name,
+        proposed.description,
+        proposed.kind,
+        item_id
+    )
+    .execute(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during updating ECO item: {}", e);
+        AppError::DatabaseError("Failed to update ECO item".to_string())
+    })?;
+    Ok(())
+}
+
+/// 部品が他の未完了 ECO の対象になっていないことを確認する。
+/// 同時実行は `eco_items_open_part_id_idx` で防ぐため、ここでは分かりやすいエラーを返すための事前確認のみ
+pub async fn ensure_no_open_eco(conn: &mut PgConnection, part_id: Uuid) -> Result<(), AppError> {
+    let open_eco = sqlx::query_scalar!(
+        r#"SELECT e.number
+        FROM eco_items i
+        JOIN ecos e ON e.id = i.eco_id
+        WHERE i.part_id = $1 AND e.status IN ($2, $3)
+        "#,
+        part_id,
+        EcoStatus::Draft.as_str(),
+        EcoStatus::InReview.as_str()
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching open ECO: {}", e);
+        AppError::DatabaseError("Failed to check open ECO".to_string())
+    })?;
+
+    if let Some(number) = open_eco {
+        return Err(AppError::ValidationError(field_validation_error(
+            "part_id",
+            &format!("Part is already affected by open ECO {}", number),
+        )));
+    }
+
+    Ok(())
+}
+
+pub async fn insert_eco_item(
+    conn: &mut PgConnection,
+    eco_id: Uuid,
+    part_id: Uuid,
+    proposed: &NewPart,
+) -> Result<EcoItem, AppError> {
+    sqlx::query_as!(
+        EcoItem,
+        r#"INSERT INTO eco_items (eco_id, part_id, part_number, name, description, kind)
+           VALUES ($1, $2, $3, $4, $5, $6)
+           RETURNING id, eco_id, part_id, part_number, name, description, kind, released_revision, created_at"#,
+        eco_id,
+        part_id,
+        proposed.part_number,
+        proposed.name,
+        proposed.description,
+        proposed.kind
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        // 同時に別の ECO へ追加された場合は一意インデックスで弾かれる
+        if e
+            .as_database_error()
+            .and_then(|db| db.constraint())
+            .is_some_and(|name| name == "eco_items_open_part_id_idx")
+        {
+            return AppError::ValidationError(field_validation_error(
+                "part_id",
+                "Part is already affected by another open ECO",
+            ));
+        }
+        error!("DB error during ECO item insertion: {}", e);
+        AppError::DatabaseError("Failed to add ECO item".to_string())
+    })
+}
+
+pub async fn add_eco_item(
+    claims: Claims,
+    pool: &PgPool,
+    eco_id: Uuid,
+    new_item: NewEcoItem,
+) -> Result<EcoItem, AppError> {
+    new_item
+        .proposed
+        .validate()
+        .map_err(|e| AppError::ValidationError(extract_validation_errors(e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to add ECO item".to_string())
+    })?;
+
+    let eco = lock_eco(&mut tx, eco_id).await?;
+    ensure_eco_admin_or_owner(&claims, &eco)?;
+    ensure_eco_status(&eco, EcoStatus::Draft)?;
+
+    let part_exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM parts WHERE id = $1 AND deleted_at IS NULL) AS "exists!""#,
+        new_item.part_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching part: {}", e);
+        AppError::DatabaseError("Failed to add ECO item".to_string())
+    })?;
+
+    if !part_exists {
+        info!("Part not found: {}", new_item.part_id);
+        return Err(AppError::NotFound(format!(
+            "Part not found: {}",
+            new_item.part_id
+        )));
+    }
+
+    ensure_no_open_eco(&mut tx, new_item.part_id).await?;
+
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+    ensure_part_number_available(
+        &mut tx,
+        &new_item.proposed.part_number,
+        Some(new_item.part_id),
+        user_id,
+    )
+    .await?;
+
+    let item = insert_eco_item(&mut tx, eco_id, new_item.part_id, &new_item.proposed).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing ECO item: {}", e);
+        AppError::DatabaseError("Failed to add ECO item".to_string())
+    })?;
+
+    info!("Part {} added to ECO {}", item.part_id, eco.number);
+    Ok(item)
+}
+
+pub async fn remove_eco_item(
+    claims: Claims,
+    pool: &PgPool,
+    eco_id: Uuid,
+    item_id: Uuid,
+) -> Result<(), AppError> {
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to remove ECO item".to_string())
+    })?;
+
+    let eco = lock_eco(&mut tx, eco_id).await?;
+    ensure_eco_admin_or_owner(&claims, &eco)?;
+    ensure_eco_status(&eco, EcoStatus::Draft)?;
+
+    let result = sqlx::query!(
+        r#"DELETE FROM eco_items WHERE id = $1 AND eco_id = $2"#,
+        item_id,
+        eco_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during deleting ECO item: {}", e);
+        AppError::DatabaseError("Failed to remove ECO item".to_string())
+    })?;
+
+    if result.rows_affected() == 0 {
+        info!("ECO item not found for deletion: {}", item_id);
+        return Err(AppError::NotFound(format!(
+            "ECO item not found for deletion: {}",
+            item_id
+        )));
+    }
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing ECO item removal: {}", e);
+        AppError::DatabaseError("Failed to remove ECO item".to_string())
+    })?;
+
+    info!("ECO item removed successfully: {}", item_id);
+    Ok(())
+}
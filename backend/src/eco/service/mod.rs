@@ -0,0 +1,12 @@
+pub mod auth;
+pub mod create;
+pub mod decision;
+pub mod get;
+pub mod item;
+pub mod submit;
+
+pub use create::create_eco;
+pub use decision::{approve_eco, reject_eco};
+pub use get::{get_eco, get_ecos};
+pub use item::{add_eco_item, remove_eco_item};
+pub use submit::submit_eco;
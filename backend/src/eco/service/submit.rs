@@ -0,0 +1,113 @@
+use crate::auth::domain::Claims;
+use crate::eco::domain::{EcoDetail, EcoStatus, SubmitEco};
+use crate::errors::app_error::AppError;
+use crate::errors::validation::{extract_validation_errors, field_validation_error};
+
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::auth::{ensure_eco_admin_or_owner, ensure_eco_status, lock_eco};
+use super::get::fetch_eco_detail;
+
+/// ECO を承認者に回付する
+pub async fn submit_eco(
+    claims: Claims,
+    pool: &PgPool,
+    id: Uuid,
+    submit: SubmitEco,
+) -> Result<EcoDetail, AppError> {
+    submit
+        .validate()
+        .map_err(|e| AppError::ValidationError(extract_validation_errors(e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to submit ECO".to_string())
+    })?;
+
+    let eco = lock_eco(&mut tx, id).await?;
+    ensure_eco_admin_or_owner(&claims, &eco)?;
+    ensure_eco_status(&eco, EcoStatus::Draft)?;
+
+    let item_count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM eco_items WHERE eco_id = $1"#,
+        id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during counting ECO items: {}", e);
+        AppError::DatabaseError("Failed to submit ECO".to_string())
+    })?;
+
+    if item_count == 0 {
+        return Err(AppError::ValidationError(field_validation_error(
+            "items",
+            "ECO must have at least one affected part",
+        )));
+    }
+
+    let mut approver_ids = submit.approver_ids;
+    approver_ids.sort();
+    approver_ids.dedup();
+
+    let approver_count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM users WHERE id = ANY($1)"#,
+        &approver_ids
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching approvers: {}", e);
+        AppError::DatabaseError("Failed to submit ECO".to_string())
+    })?;
+
+    if approver_count as usize != approver_ids.len() {
+        return Err(AppError::ValidationError(field_validation_error(
+            "approver_ids",
+            "All approvers must be existing users",
+        )));
+    }
+
+    sqlx::query!(
+        r#"INSERT INTO eco_approvals (eco_id, approver_id)
+        SELECT $1, UNNEST($2::uuid[])
+        "#,
+        id,
+        &approver_ids
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during inserting ECO approvals: {}", e);
+        AppError::DatabaseError("Failed to submit ECO".to_string())
+    })?;
+
+    sqlx::query!(
+        r#"UPDATE ecos SET status = $1, updated_at = NOW() WHERE id = $2"#,
+        EcoStatus::InReview.as_str(),
+        id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during updating ECO status: {}", e);
+        AppError::DatabaseError("Failed to submit ECO".to_string())
+    })?;
+
+    let detail = fetch_eco_detail(&mut tx, id).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing ECO submission: {}", e);
+        AppError::DatabaseError("Failed to submit ECO".to_string())
+    })?;
+
+    info!(
+        "ECO {} submitted to {} approvers",
+        detail.eco.number,
+        detail.approvals.len()
+    );
+    Ok(detail)
+}
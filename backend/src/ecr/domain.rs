@@ -0,0 +1,147 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub struct Ecr {
+    pub id: Uuid,
+    pub number: String,
+    pub title: String,
+    pub description: String,
+    pub reason_code: String,
+    pub priority: String,
+    pub status: String,
+    pub eco_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasonCode {
+    Defect,
+    CostReduction,
+    Obsolescence,
+    Regulatory,
+    CustomerRequest,
+    Improvement,
+    Other,
+}
+
+impl ReasonCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReasonCode::Defect => "defect",
+            ReasonCode::CostReduction => "cost_reduction",
+            ReasonCode::Obsolescence => "obsolescence",
+            ReasonCode::Regulatory => "regulatory",
+            ReasonCode::CustomerRequest => "customer_request",
+            ReasonCode::Improvement => "improvement",
+            ReasonCode::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EcrPriority {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Critical,
+}
+
+impl EcrPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EcrPriority::Low => "low",
+            EcrPriority::Medium => "medium",
+            EcrPriority::High => "high",
+            EcrPriority::Critical => "critical",
+        }
+    }
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct NewEcr {
+    #[validate(length(min = 1, message = "title must not be empty"))]
+    pub title: String,
+    #[validate(length(min = 1, message = "description must not be empty"))]
+    pub description: String,
+    pub reason_code: ReasonCode,
+    #[serde(default)]
+    pub priority: EcrPriority,
+    #[serde(default)]
+    pub part_ids: Vec<Uuid>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct EcrDetail {
+    #[serde(flatten)]
+    pub ecr: Ecr,
+    pub part_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EcrStatus {
+    Open,
+    UnderReview,
+    Accepted,
+    Rejected,
+}
+
+impl EcrStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EcrStatus::Open => "open",
+            EcrStatus::UnderReview => "under_review",
+            EcrStatus::Accepted => "accepted",
+            EcrStatus::Rejected => "rejected",
+        }
+    }
+
+    /// 受付 → 審査中 → 受理 / 却下。審査中の案件は差し戻せる
+    pub fn can_transition_to(&self, to: EcrStatus) -> bool {
+        matches!(
+            (self, to),
+            (EcrStatus::Open, EcrStatus::UnderReview)
+                | (EcrStatus::UnderReview, EcrStatus::Open)
+                | (EcrStatus::UnderReview, EcrStatus::Accepted)
+                | (EcrStatus::UnderReview, EcrStatus::Rejected)
+        )
+    }
+}
+
+impl From<&str> for EcrStatus {
+    fn from(s: &str) -> Self {
+        match s {
+            "under_review" => EcrStatus::UnderReview,
+            "accepted" => EcrStatus::Accepted,
+            "rejected" => EcrStatus::Rejected,
+            _ => EcrStatus::Open,
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct EcrTransitionRequest {
+    pub to: EcrStatus,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EcrStatus;
+
+    #[test]
+    fn test_ecr_status_transitions() {
+        assert!(EcrStatus::Open.can_transition_to(EcrStatus::UnderReview));
+        assert!(EcrStatus::UnderReview.can_transition_to(EcrStatus::Accepted));
+        assert!(!EcrStatus::Open.can_transition_to(EcrStatus::Accepted));
+        assert!(!EcrStatus::Rejected.can_transition_to(EcrStatus::Open));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::auth::domain::Claims;
+use crate::eco::domain::EcoDetail;
+use crate::ecr::domain::{Ecr, EcrDetail, EcrTransitionRequest, NewEcr};
+use crate::ecr::service::{
+    create_ecr as service_create_ecr, get_ecr as service_get_ecr, get_ecrs as service_get_ecrs,
+    promote_ecr as service_promote_ecr, transition_ecr as service_transition_ecr,
+};
+use crate::errors::app_error::AppError;
+use crate::errors::validation::ValidationErrorResponse;
+use crate::responses::error::ErrorResponse;
+use crate::responses::success::SuccessResponse;
+
+use axum::Extension;
+use axum::{Json, extract::Path, extract::State};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/ecrs", request_body = NewEcr, responses(
+    (status = 201, description = "ECR created successfully", body = SuccessResponse<EcrDetail>),
+    (status = 400, description = "Validation error", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["ecrs"], security(("bearerAuth" = [])))]
+pub async fn create_ecr(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Json(new_ecr): Json<NewEcr>,
+) -> Result<Json<SuccessResponse<EcrDetail>>, AppError> {
+    let ecr = service_create_ecr(claims, &pool, new_ecr).await?;
+    Ok(Json(SuccessResponse::created(ecr)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/ecrs", responses(
+    (status = 200, description = "Fetched ECRs successfully", body = SuccessResponse<Vec<Ecr>>),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["ecrs"], security(("bearerAuth" = [])))]
+pub async fn get_ecrs(
+    State(pool): State<PgPool>,
+) -> Result<Json<SuccessResponse<Vec<Ecr>>>, AppError> {
+    let ecrs = service_get_ecrs(&pool).await?;
+    Ok(Json(SuccessResponse::ok(ecrs)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/ecrs/{id}", params(("id" = Uuid, Path, description = "ECR ID to fetch")), responses(
+    (status = 200, description = "Fetched ECR successfully", body = SuccessResponse<EcrDetail>),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["ecrs"], security(("bearerAuth" = [])))]
+pub async fn get_ecr(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<EcrDetail>>, AppError> {
+    let ecr = service_get_ecr(&pool, id).await?;
+    Ok(Json(SuccessResponse::ok(ecr)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/ecrs/{id}/transition", params(("id" = Uuid, Path, description = "ECR ID to transition")), request_body = EcrTransitionRequest, responses(
+    (status = 200, description = "ECR transitioned successfully", body = SuccessResponse<EcrDetail>),
+    (status = 400, description = "Validation error (transition not allowed)", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["ecrs"], security(("bearerAuth" = [])))]
+pub async fn transition_ecr(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<EcrTransitionRequest>,
+) -> Result<Json<SuccessResponse<EcrDetail>>, AppError> {
+    let ecr = service_transition_ecr(claims, &pool, id, request).await?;
+    Ok(Json(SuccessResponse::ok(ecr)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/ecrs/{id}/promote", params(("id" = Uuid, Path, description = "Accepted ECR ID to promote")), responses(
+    (status = 201, description = "ECO created from ECR", body = SuccessResponse<EcoDetail>),
+    (status = 400, description = "Validation error", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["ecrs"], security(("bearerAuth" = [])))]
+pub async fn promote_ecr(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<EcoDetail>>, AppError> {
+    let eco = service_promote_ecr(claims, &pool, id).await?;
+    Ok(Json(SuccessResponse::created(eco)))
+}
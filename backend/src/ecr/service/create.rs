@@ -0,0 +1,90 @@
+use crate::auth::domain::Claims;
+use crate::ecr::domain::{Ecr, EcrDetail, NewEcr};
+use crate::errors::app_error::AppError;
+use crate::errors::validation::{extract_validation_errors, field_validation_error};
+
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+use validator::Validate;
+
+pub async fn create_ecr(
+    claims: Claims,
+    pool: &PgPool,
+    new_ecr: NewEcr,
+) -> Result<EcrDetail, AppError> {
+    new_ecr
+        .validate()
+        .map_err(|e| AppError::ValidationError(extract_validation_errors(e)))?;
+
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut part_ids = new_ecr.part_ids;
+    part_ids.sort();
+    part_ids.dedup();
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("DB insert failed".to_string())
+    })?;
+
+    let part_count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM parts WHERE id = ANY($1) AND deleted_at IS NULL"#,
+        &part_ids
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching affected parts: {}", e);
+        AppError::DatabaseError("DB insert failed".to_string())
+    })?;
+
+    if part_count as usize != part_ids.len() {
+        return Err(AppError::ValidationError(field_validation_error(
+            "part_ids",
+            "All affected parts must exist",
+        )));
+    }
+
+    let ecr = sqlx::query_as!(
+        Ecr,
+        r#"INSERT INTO ecrs (title, description, reason_code, priority, created_by)
+           VALUES ($1, $2, $3, $4, $5)
+           RETURNING id, number, title, description, reason_code, priority, status, eco_id,
+                     created_at, created_by, updated_at"#,
+        new_ecr.title,
+        new_ecr.description,
+        new_ecr.reason_code.as_str(),
+        new_ecr.priority.as_str(),
+        user_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during ECR insertion: {}", e);
+        AppError::DatabaseError("DB insert failed".to_string())
+    })?;
+
+    sqlx::query!(
+        r#"INSERT INTO ecr_parts (ecr_id, part_id)
+        SELECT $1, UNNEST($2::uuid[])
+        "#,
+        ecr.id,
+        &part_ids
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during inserting ECR parts: {}", e);
+        AppError::DatabaseError("DB insert failed".to_string())
+    })?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing ECR: {}", e);
+        AppError::DatabaseError("DB insert failed".to_string())
+    })?;
+
+    info!("ECR created successfully: {}", ecr.number);
+    Ok(EcrDetail { ecr, part_ids })
+}
@@ -0,0 +1,95 @@
+use crate::ecr::domain::{Ecr, EcrDetail};
+use crate::errors::app_error::AppError;
+
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+
+pub async fn get_ecrs(pool: &PgPool) -> Result<Vec<Ecr>, AppError> {
+    let ecrs = sqlx::query_as!(
+        Ecr,
+        r#"SELECT id, number, title, description, reason_code, priority, status, eco_id,
+                  created_at, created_by, updated_at
+        FROM ecrs
+        ORDER BY created_at DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching ECRs: {}", e);
+        AppError::DatabaseError("DB select failed".to_string())
+    })?;
+
+    info!("Fetched {} ECRs successfully", ecrs.len());
+    Ok(ecrs)
+}
+
+/// ECR を取得する。`for_update` の場合は行ロックを取る
+pub async fn fetch_ecr(
+    conn: &mut PgConnection,
+    id: Uuid,
+    for_update: bool,
+) -> Result<Ecr, AppError> {
+    let ecr = if for_update {
+        sqlx::query_as!(
+            Ecr,
+            r#"SELECT id, number, title, description, reason_code, priority, status, eco_id,
+                      created_at, created_by, updated_at
+            FROM ecrs
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    } else {
+        sqlx::query_as!(
+            Ecr,
+            r#"SELECT id, number, title, description, reason_code, priority, status, eco_id,
+                      created_at, created_by, updated_at
+            FROM ecrs
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
+    .map_err(|e| {
+        error!("DB error during fetching ECR: {}", e);
+        AppError::DatabaseError("Failed to fetch ECR".to_string())
+    })?;
+
+    ecr.ok_or_else(|| {
+        info!("ECR not found: {}", id);
+        AppError::NotFound(format!("ECR not found: {}", id))
+    })
+}
+
+pub async fn fetch_ecr_part_ids(conn: &mut PgConnection, id: Uuid) -> Result<Vec<Uuid>, AppError> {
+    sqlx::query_scalar!(
+        r#"SELECT part_id FROM ecr_parts WHERE ecr_id = $1 ORDER BY part_id"#,
+        id
+    )
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching ECR parts: {}", e);
+        AppError::DatabaseError("Failed to fetch ECR".to_string())
+    })
+}
+
+pub async fn get_ecr(pool: &PgPool, id: Uuid) -> Result<EcrDetail, AppError> {
+    let mut conn = pool.acquire().await.map_err(|e| {
+        error!("DB error during acquiring connection: {}", e);
+        AppError::DatabaseError("Failed to fetch ECR".to_string())
+    })?;
+
+    let ecr = fetch_ecr(&mut conn, id, false).await?;
+    let part_ids = fetch_ecr_part_ids(&mut conn, id).await?;
+
+    info!("ECR found: {}", ecr.number);
+    Ok(EcrDetail { ecr, part_ids })
+}
@@ -0,0 +1,9 @@
+pub mod create;
+pub mod get;
+pub mod promote;
+pub mod transition;
+
+pub use create::create_ecr;
+pub use get::{get_ecr, get_ecrs};
+pub use promote::promote_ecr;
+pub use transition::transition_ecr;
@@ -0,0 +1,101 @@
+use crate::auth::domain::Claims;
+use crate::eco::domain::{EcoDetail, NewEco};
+use crate::eco::service::create::insert_eco;
+use crate::eco::service::get::fetch_eco_detail;
+use crate::eco::service::item::{ensure_no_open_eco, insert_eco_item};
+use crate::ecr::domain::EcrStatus;
+use crate::errors::app_error::AppError;
+use crate::errors::validation::field_validation_error;
+use crate::part::domain::NewPart;
+
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::get::{fetch_ecr, fetch_ecr_part_ids};
+use super::transition::ensure_ecr_admin_or_owner;
+
+/// 受理済みの ECR から、対象部品の現在の内容を変更案とした作成中の ECO を起こす
+pub async fn promote_ecr(claims: Claims, pool: &PgPool, id: Uuid) -> Result<EcoDetail, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to promote ECR".to_string())
+    })?;
+
+    let ecr = fetch_ecr(&mut tx, id, true).await?;
+    ensure_ecr_admin_or_owner(&claims, &ecr)?;
+
+    if EcrStatus::from(ecr.status.as_str()) != EcrStatus::Accepted {
+        return Err(AppError::ValidationError(field_validation_error(
+            "status",
+            &format!("ECR {} must be accepted before promotion", ecr.number),
+        )));
+    }
+    if let Some(eco_id) = ecr.eco_id {
+        return Err(AppError::ValidationError(field_validation_error(
+            "eco_id",
+            &format!("ECR {} is already promoted to ECO {}", ecr.number, eco_id),
+        )));
+    }
+
+    let eco = insert_eco(
+        &mut tx,
+        &NewEco {
+            title: format!("{}: {}", ecr.number, ecr.title),
+            description: Some(ecr.description.clone()),
+        },
+        user_id,
+    )
+    .await?;
+
+    for part_id in fetch_ecr_part_ids(&mut tx, id).await? {
+        ensure_no_open_eco(&mut tx, part_id).await?;
+
+        let current = sqlx::query_as!(
+            NewPart,
+            r#"SELECT part_number, name, description, kind
+            FROM parts
+            WHERE id = $1 AND deleted_at IS NULL"#,
+            part_id
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("DB error during fetching affected part: {}", e);
+            AppError::DatabaseError("Failed to promote ECR".to_string())
+        })?
+        .ok_or_else(|| {
+            AppError::ValidationError(field_validation_error(
+                "part_ids",
+                &format!("Affected part is in the trash: {}", part_id),
+            ))
+        })?;
+
+        insert_eco_item(&mut tx, eco.id, part_id, &current).await?;
+    }
+
+    sqlx::query!(
+        r#"UPDATE ecrs SET eco_id = $1, updated_at = NOW() WHERE id = $2"#,
+        eco.id,
+        id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during linking ECR to ECO: {}", e);
+        AppError::DatabaseError("Failed to promote ECR".to_string())
+    })?;
+
+    let detail = fetch_eco_detail(&mut tx, eco.id).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing ECR promotion: {}", e);
+        AppError::DatabaseError("Failed to promote ECR".to_string())
+    })?;
+
+    info!("ECR {} promoted to ECO {}", ecr.number, eco.number);
+    Ok(detail)
+}
@@ -0,0 +1,94 @@
+use crate::auth::domain::{Claims, Role};
+use crate::ecr::domain::{Ecr, EcrDetail, EcrStatus, EcrTransitionRequest};
+use crate::errors::app_error::AppError;
+use crate::errors::validation::field_validation_error;
+
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::get::{fetch_ecr, fetch_ecr_part_ids};
+
+pub fn ensure_ecr_admin_or_owner(claims: &Claims, ecr: &Ecr) -> Result<(), AppError> {
+    if claims.role == Role::Admin {
+        return Ok(());
+    }
+
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    if ecr.created_by == Some(user_id) {
+        Ok(())
+    } else {
+        Err(AppError::Unauthorized("You do not own this ECR.".into()))
+    }
+}
+
+/// ECR の状態を遷移させる。審査に回すのは起票者、受理・却下・差し戻しは管理者が行う
+pub async fn transition_ecr(
+    claims: Claims,
+    pool: &PgPool,
+    id: Uuid,
+    request: EcrTransitionRequest,
+) -> Result<EcrDetail, AppError> {
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to transition ECR".to_string())
+    })?;
+
+    let ecr = fetch_ecr(&mut tx, id, true).await?;
+    let current = EcrStatus::from(ecr.status.as_str());
+
+    if !current.can_transition_to(request.to) {
+        return Err(AppError::ValidationError(field_validation_error(
+            "to",
+            &format!(
+                "Transition from '{}' to '{}' is not allowed",
+                current.as_str(),
+                request.to.as_str()
+            ),
+        )));
+    }
+
+    if request.to == EcrStatus::UnderReview {
+        ensure_ecr_admin_or_owner(&claims, &ecr)?;
+    } else if claims.role != Role::Admin {
+        return Err(AppError::Unauthorized(
+            "Only admins can triage ECRs.".into(),
+        ));
+    }
+
+    let ecr = sqlx::query_as!(
+        Ecr,
+        r#"UPDATE ecrs
+        SET status = $1,
+            updated_at = NOW()
+        WHERE id = $2
+        RETURNING id, number, title, description, reason_code, priority, status, eco_id,
+                  created_at, created_by, updated_at
+        "#,
+        request.to.as_str(),
+        id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during updating ECR status: {}", e);
+        AppError::DatabaseError("Failed to transition ECR".to_string())
+    })?;
+
+    let part_ids = fetch_ecr_part_ids(&mut tx, id).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing ECR transition: {}", e);
+        AppError::DatabaseError("Failed to transition ECR".to_string())
+    })?;
+
+    info!(
+        "ECR {} transitioned from {} to {}",
+        ecr.number,
+        current.as_str(),
+        ecr.status
+    );
+    Ok(EcrDetail { ecr, part_ids })
+}
@@ -1,6 +1,8 @@
+use crate::errors::conflict::ConflictErrorResponse;
 use crate::errors::validation::ValidationErrorResponse;
 use crate::responses::error::{ErrorDetail, ErrorResponse};
 
+use axum::http::header::RETRY_AFTER;
 use axum::response::{IntoResponse, Response};
 use axum::{Json, http::StatusCode};
 use tracing::error;
@@ -10,10 +12,36 @@ use utoipa::ToSchema;
 pub enum AppError {
     ValidationError(ValidationErrorResponse),
     NotFound(String),
-    // Conflict(String),
+    Conflict(ConflictErrorResponse),
     DatabaseError(String),
     InternalError(String),
     Unauthorized(String),
+    PreconditionFailed(String),
+    PreconditionRequired(String),
+    UnsupportedMediaType(String),
+    /// 試行回数の制限を超えた。`retry_after_secs` 秒後に再試行できる
+    TooManyRequests {
+        message: String,
+        retry_after_secs: u64,
+    },
+}
+
+impl AppError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::DatabaseError(_) | AppError::InternalError(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            AppError::PreconditionRequired(_) => StatusCode::PRECONDITION_REQUIRED,
+            AppError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
 }
 
 impl IntoResponse for AppError {
@@ -46,19 +74,21 @@ impl IntoResponse for AppError {
 
                 (status, body).into_response()
             }
-            // AppError::Conflict(message) => {
-            //     let status = StatusCode::CONFLICT;
+            AppError::Conflict(conflict) => {
+                let status = StatusCode::CONFLICT;
 
-            //     error!("Conflict ({}): {}", status, message);
+                error!(
+                    "Conflict ({}): {} (field: {}, existing: {:?})",
+                    status,
+                    conflict.error.message,
+                    conflict.error.field,
+                    conflict.error.existing_id
+                );
 
-            //     let body = Json(ErrorResponse {
-            //         success: false,
-            //         code: status.as_u16(),
-            //         error: ErrorDetail { message },
-            //     });
+                let body = Json(conflict);
 
-            //     (status, body).into_response()
-            // }
+                (status, body).into_response()
+            }
             AppError::DatabaseError(message) => {
                 let status = StatusCode::INTERNAL_SERVER_ERROR;
 
@@ -98,6 +128,64 @@ impl IntoResponse for AppError {
 
                 (status, body).into_response()
             }
+            AppError::PreconditionFailed(message) => {
+                let status = StatusCode::PRECONDITION_FAILED;
+
+                error!("Precondition failed ({}): {}", status, message);
+
+                let body = Json(ErrorResponse {
+                    success: false,
+                    code: status.as_u16(),
+                    error: ErrorDetail { message },
+                });
+
+                (status, body).into_response()
+            }
+            AppError::PreconditionRequired(message) => {
+                let status = StatusCode::PRECONDITION_REQUIRED;
+
+                error!("Precondition required ({}): {}", status, message);
+
+                let body = Json(ErrorResponse {
+                    success: false,
+                    code: status.as_u16(),
+                    error: ErrorDetail { message },
+                });
+
+                (status, body).into_response()
+            }
+            AppError::UnsupportedMediaType(message) => {
+                let status = StatusCode::UNSUPPORTED_MEDIA_TYPE;
+
+                error!("Unsupported media type ({}): {}", status, message);
+
+                let body = Json(ErrorResponse {
+                    success: false,
+                    code: status.as_u16(),
+                    error: ErrorDetail { message },
+                });
+
+                (status, body).into_response()
+            }
+            AppError::TooManyRequests {
+                message,
+                retry_after_secs,
+            } => {
+                let status = StatusCode::TOO_MANY_REQUESTS;
+
+                error!(
+                    "Too many requests ({}): {} (retry after {}s)",
+                    status, message, retry_after_secs
+                );
+
+                let body = Json(ErrorResponse {
+                    success: false,
+                    code: status.as_u16(),
+                    error: ErrorDetail { message },
+                });
+
+                (status, [(RETRY_AFTER, retry_after_secs.to_string())], body).into_response()
+            }
         };
         response
     }
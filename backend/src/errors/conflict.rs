@@ -0,0 +1,133 @@
+use axum::http::StatusCode;
+use serde::Serialize;
+use sqlx::PgConnection;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::app_error::AppError;
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ConflictErrorResponse {
+    #[schema(example = false)]
+    pub success: bool,
+    #[schema(example = 409)]
+    pub code: u16,
+    pub error: ConflictDetail,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ConflictDetail {
+    pub message: String,
+    /// 重複した項目
+    pub field: String,
+    /// 既存のリソースの id
+    pub existing_id: Option<Uuid>,
+}
+
+pub fn conflict_error(field: &str, message: &str, existing_id: Option<Uuid>) -> AppError {
+    AppError::Conflict(ConflictErrorResponse {
+        success: false,
+        code: StatusCode::CONFLICT.as_u16(),
+        error: ConflictDetail {
+            message: message.to_string(),
+            field: field.to_string(),
+            existing_id,
+        },
+    })
+}
+
+/// PostgreSQL の一意制約違反 (23505) の内容
+#[derive(Debug, PartialEq)]
+pub struct UniqueViolation {
+    pub constraint: Option<String>,
+    pub field: String,
+    pub value: String,
+}
+
+impl UniqueViolation {
+    pub fn from_error(e: &sqlx::Error) -> Option<Self> {
+        let db = e.as_database_error()?;
+        if db.code().as_deref() != Some("23505") {
+            return None;
+        }
+        let detail = db
+            .try_downcast_ref::<sqlx::postgres::PgDatabaseError>()
+            .and_then(|pg| pg.detail());
+        let (field, value) = detail.and_then(parse_key_detail).unwrap_or_default();
+        Some(Self {
+            constraint: db.constraint().map(str::to_string),
+            field,
+            value,
+        })
+    }
+
+    pub fn into_conflict(self, existing_id: Option<Uuid>) -> AppError {
+        let message = if self.field.is_empty() {
+            "Resource already exists".to_string()
+        } else {
+            format!("{} already exists: {}", self.field, self.value)
+        };
+        conflict_error(&self.field, &message, existing_id)
+    }
+
+    /// 重複した値を持つ既存行の id を引く (既存行を特定できる制約のみ)
+    pub async fn existing_id(&self, conn: &mut PgConnection) -> Option<Uuid> {
+        let result = match self.constraint.as_deref()? {
+            "parts_part_number_key" => {
+                sqlx::query_scalar!(r#"SELECT id FROM parts WHERE part_number = $1"#, self.value)
+                    .fetch_optional(conn)
+                    .await
+            }
+            "users_login_name_key" => {
+                sqlx::query_scalar!(r#"SELECT id FROM users WHERE login_name = $1"#, self.value)
+                    .fetch_optional(conn)
+                    .await
+            }
+            _ => return None,
+        };
+        result.ok().flatten()
+    }
+}
+
+/// 一意制約違反なら既存行の id を付けた 409 に、それ以外は `fallback` に変換する。
+/// 違反した文でトランザクションは中断状態になるため、呼び出し側はその文をセーブポイント内で実行し、
+/// ロールバックしてから `conn` を渡すこと
+pub async fn map_unique_violation(
+    conn: &mut PgConnection,
+    e: sqlx::Error,
+    fallback: impl FnOnce(sqlx::Error) -> AppError,
+) -> AppError {
+    match UniqueViolation::from_error(&e) {
+        Some(violation) => {
+            let existing_id = violation.existing_id(conn).await;
+            violation.into_conflict(existing_id)
+        }
+        None => fallback(e),
+    }
+}
+
+/// `Key (part_number)=(XYZ-789) already exists.` から列名と値を取り出す
+fn parse_key_detail(detail: &str) -> Option<(String, String)> {
+    let rest = detail.strip_prefix("Key (")?;
+    let (field, rest) = rest.split_once(")=(")?;
+    let value = rest.rsplit_once(") already exists")?.0;
+    Some((field.to_string(), value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_key_detail;
+
+    #[test]
+    fn test_parse_key_detail() {
+        assert_eq!(
+            parse_key_detail("Key (part_number)=(XYZ-789) already exists."),
+            Some(("part_number".to_string(), "XYZ-789".to_string()))
+        );
+        assert_eq!(
+            parse_key_detail("Key (parent_id, child_id)=(a, b) already exists."),
+            Some(("parent_id, child_id".to_string(), "a, b".to_string()))
+        );
+        assert_eq!(parse_key_detail("something else"), None);
+    }
+}
@@ -1,2 +1,3 @@
 pub mod app_error;
+pub mod conflict;
 pub mod validation;
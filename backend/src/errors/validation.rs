@@ -40,3 +40,18 @@ pub fn extract_validation_errors(errors: ValidationErrors) -> ValidationErrorRes
         errors: field_errors,
     }
 }
+
+pub fn field_validation_error(field: &str, message: &str) -> ValidationErrorResponse {
+    fields_validation_error(vec![FieldError {
+        field: field.to_string(),
+        message: message.to_string(),
+    }])
+}
+
+pub fn fields_validation_error(errors: Vec<FieldError>) -> ValidationErrorResponse {
+    ValidationErrorResponse {
+        success: false,
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        errors,
+    }
+}
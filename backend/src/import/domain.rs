@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::bom::domain::NewBomLine;
+use crate::errors::app_error::AppError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportTarget {
+    /// 部品 (`NewPart`)
+    #[default]
+    Parts,
+    /// 構成行 (親品番・子品番・数量)
+    Bom,
+}
+
+impl ImportTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportTarget::Parts => "parts",
+            ImportTarget::Bom => "bom",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "parts" => Some(ImportTarget::Parts),
+            "bom" => Some(ImportTarget::Bom),
+            _ => None,
+        }
+    }
+
+    /// 列を割り当てられる項目
+    pub fn fields(&self) -> &'static [&'static str] {
+        match self {
+            ImportTarget::Parts => &["part_number", "name", "description", "kind"],
+            ImportTarget::Bom => &[
+                "parent_part_number",
+                "child_part_number",
+                "quantity",
+                "unit_of_measure",
+                "find_number",
+                "reference_designators",
+            ],
+        }
+    }
+
+    /// ファイルに列がなければ取り込めない項目
+    pub fn required_fields(&self) -> &'static [&'static str] {
+        match self {
+            ImportTarget::Parts => &["name"],
+            ImportTarget::Bom => &["parent_part_number", "child_part_number", "quantity"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportFormat {
+    Csv,
+    Xlsx,
+}
+
+impl ImportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportFormat::Csv => "csv",
+            ImportFormat::Xlsx => "xlsx",
+        }
+    }
+
+    /// 拡張子、なければ Content-Type から形式を判定する
+    pub fn detect(file_name: &str, content_type: Option<&str>) -> Option<Self> {
+        let extension = file_name
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_ascii_lowercase());
+        match (extension.as_deref(), content_type) {
+            (Some("csv"), _) => Some(ImportFormat::Csv),
+            (Some("xlsx"), _) => Some(ImportFormat::Xlsx),
+            (_, Some("text/csv")) => Some(ImportFormat::Csv),
+            (_, Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")) => {
+                Some(ImportFormat::Xlsx)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub struct ImportJob {
+    pub id: Uuid,
+    pub target: String,
+    pub format: String,
+    pub file_name: String,
+    /// 取り込み先の項目 -> ファイルの列名
+    #[schema(value_type = HashMap<String, String>)]
+    pub mapping: Json<HashMap<String, String>>,
+    /// validated, invalid, committed or failed
+    pub status: String,
+    pub total_rows: i32,
+    pub error_rows: i32,
+    pub committed_rows: i32,
+    pub created_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub committed_at: Option<DateTime<Utc>>,
+}
+
+impl ImportJob {
+    pub const VALIDATED: &'static str = "validated";
+    pub const INVALID: &'static str = "invalid";
+    pub const COMMITTED: &'static str = "committed";
+    pub const FAILED: &'static str = "failed";
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema, Debug, PartialEq)]
+pub struct ImportRowError {
+    /// スプレッドシート上の行番号 (見出し行が 1)
+    pub row: i32,
+    pub field: String,
+    pub message: String,
+}
+
+impl ImportRowError {
+    pub fn new(row: i32, field: &str, message: &str) -> Self {
+        ImportRowError {
+            row,
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// 行の検証・登録で起きたエラーを項目ごとのエラーにする
+    pub fn from_app_error(row: i32, e: AppError) -> Vec<Self> {
+        match e {
+            AppError::ValidationError(response) => response
+                .errors
+                .into_iter()
+                .map(|error| ImportRowError {
+                    row,
+                    field: error.field,
+                    message: error.message,
+                })
+                .collect(),
+            AppError::Conflict(conflict) => vec![ImportRowError {
+                row,
+                field: conflict.error.field,
+                message: conflict.error.message,
+            }],
+            AppError::NotFound(message)
+            | AppError::DatabaseError(message)
+            | AppError::InternalError(message)
+            | AppError::Unauthorized(message)
+            | AppError::PreconditionFailed(message)
+            | AppError::PreconditionRequired(message)
+            | AppError::UnsupportedMediaType(message)
+            | AppError::TooManyRequests { message, .. } => {
+                vec![ImportRowError::new(row, "", &message)]
+            }
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ImportJobDetail {
+    #[serde(flatten)]
+    pub job: ImportJob,
+    pub errors: Vec<ImportRowError>,
+}
+
+/// アップロードされたファイルと取り込み条件
+pub struct ImportUpload {
+    pub file_name: String,
+    pub content_type: Option<String>,
+    pub bytes: Vec<u8>,
+    pub target: ImportTarget,
+    pub mapping: HashMap<String, String>,
+}
+
+/// `POST /imports` の multipart フォーム (OpenAPI 用)
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct ImportForm {
+    /// CSV (UTF-8) または XLSX ファイル。1 行目は見出し行
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+    /// parts (default) or bom
+    pub target: Option<ImportTarget>,
+    /// 項目 -> 列名の JSON。省略した項目は同名の列を使う
+    #[schema(example = json!({"part_number": "品番", "name": "品名"}))]
+    pub mapping: Option<String>,
+}
+
+/// 列を割り当てた 1 行分の値
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    pub row: i32,
+    pub values: HashMap<String, String>,
+}
+
+impl ImportRow {
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.values
+            .get(field)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+#[derive(Debug, Validate)]
+pub struct BomImportRow {
+    #[validate(length(min = 1, message = "parent_part_number must not be empty"))]
+    pub parent_part_number: String,
+    #[validate(length(min = 1, message = "child_part_number must not be empty"))]
+    pub child_part_number: String,
+    #[validate(range(exclusive_min = 0.0, message = "quantity must be greater than 0"))]
+    pub quantity: f64,
+    #[validate(length(min = 1, message = "unit_of_measure must not be empty"))]
+    pub unit_of_measure: String,
+    #[validate(range(min = 1, message = "find_number must be 1 or greater"))]
+    pub find_number: Option<i32>,
+    pub reference_designators: Vec<String>,
+}
+
+impl BomImportRow {
+    pub fn into_line(self, child_id: Uuid) -> NewBomLine {
+        NewBomLine {
+            child_id,
+            quantity: self.quantity,
+            unit_of_measure: self.unit_of_measure,
+            find_number: self.find_number,
+            reference_designators: self.reference_designators,
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use crate::auth::domain::Claims;
+use crate::errors::app_error::AppError;
+use crate::errors::validation::{ValidationErrorResponse, field_validation_error};
+use crate::import::domain::{ImportForm, ImportJob, ImportJobDetail, ImportTarget, ImportUpload};
+use crate::import::service::{
+    commit_import as service_commit_import, create_import as service_create_import,
+    get_import as service_get_import, get_import_error_report as service_get_import_error_report,
+    get_imports as service_get_imports,
+};
+use crate::responses::error::ErrorResponse;
+use crate::responses::success::SuccessResponse;
+
+use axum::Extension;
+use axum::extract::Multipart;
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::response::IntoResponse;
+use axum::{Json, extract::Path, extract::State};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/imports", request_body(content = ImportForm, content_type = "multipart/form-data"), responses(
+    (status = 201, description = "File validated (dry-run) and import job created", body = SuccessResponse<ImportJobDetail>),
+    (status = 400, description = "Validation error (unreadable file or column mapping)", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 415, description = "Unsupported file format", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["imports"], security(("bearerAuth" = [])))]
+pub async fn create_import(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    mut multipart: Multipart,
+) -> Result<Json<SuccessResponse<ImportJobDetail>>, AppError> {
+    let upload = read_import_form(&mut multipart).await?;
+    let job = service_create_import(claims, &pool, upload).await?;
+    Ok(Json(SuccessResponse::created(job)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/imports", responses(
+    (status = 200, description = "Fetched import jobs successfully", body = SuccessResponse<Vec<ImportJob>>),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["imports"], security(("bearerAuth" = [])))]
+pub async fn get_imports(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+) -> Result<Json<SuccessResponse<Vec<ImportJob>>>, AppError> {
+    let jobs = service_get_imports(claims, &pool).await?;
+    Ok(Json(SuccessResponse::ok(jobs)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/imports/{id}", params(("id" = Uuid, Path, description = "Import job ID to fetch")), responses(
+    (status = 200, description = "Fetched import job successfully", body = SuccessResponse<ImportJobDetail>),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["imports"], security(("bearerAuth" = [])))]
+pub async fn get_import(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<ImportJobDetail>>, AppError> {
+    let job = service_get_import(claims, &pool, id).await?;
+    Ok(Json(SuccessResponse::ok(job)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/imports/{id}/errors", params(("id" = Uuid, Path, description = "Import job ID")), responses(
+    (status = 200, description = "Error report (row, field, message)", content_type = "text/csv", body = String),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["imports"], security(("bearerAuth" = [])))]
+pub async fn get_import_errors(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let report = service_get_import_error_report(claims, &pool, id).await?;
+    Ok((
+        [
+            (CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"import-{}-errors.csv\"", id),
+            ),
+        ],
+        report,
+    ))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/imports/{id}/commit", params(("id" = Uuid, Path, description = "Validated import job ID to commit")), responses(
+    (status = 200, description = "Import committed, or failed with per-row errors (see status)", body = SuccessResponse<ImportJobDetail>),
+    (status = 400, description = "Validation error (job is not validated)", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["imports"], security(("bearerAuth" = [])))]
+pub async fn commit_import(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<ImportJobDetail>>, AppError> {
+    let job = service_commit_import(claims, &pool, id).await?;
+    Ok(Json(SuccessResponse::ok(job)))
+}
+
+async fn read_import_form(multipart: &mut Multipart) -> Result<ImportUpload, AppError> {
+    let invalid_form = |e: axum::extract::multipart::MultipartError| {
+        AppError::ValidationError(field_validation_error("file", &e.body_text()))
+    };
+
+    let mut file = None;
+    let mut target = ImportTarget::default();
+    let mut mapping = HashMap::new();
+    while let Some(field) = multipart.next_field().await.map_err(invalid_form)? {
+        match field.name() {
+            Some("file") => {
+                let file_name = field.file_name().unwrap_or("upload").to_string();
+                let content_type = field.content_type().map(str::to_string);
+                let bytes = field.bytes().await.map_err(invalid_form)?;
+                file = Some((file_name, content_type, bytes.to_vec()));
+            }
+            Some("target") => {
+                let value = field.text().await.map_err(invalid_form)?;
+                target = ImportTarget::parse(&value).ok_or_else(|| {
+                    AppError::ValidationError(field_validation_error(
+                        "target",
+                        "target must be parts or bom",
+                    ))
+                })?;
+            }
+            Some("mapping") => {
+                let value = field.text().await.map_err(invalid_form)?;
+                if !value.trim().is_empty() {
+                    mapping = serde_json::from_str(&value).map_err(|_| {
+                        AppError::ValidationError(field_validation_error(
+                            "mapping",
+                            "mapping must be a JSON object of field name to column name",
+                        ))
+                    })?;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let (file_name, content_type, bytes) = file.ok_or_else(|| {
+        AppError::ValidationError(field_validation_error("file", "file is required"))
+    })?;
+    Ok(ImportUpload {
+        file_name,
+        content_type,
+        bytes,
+        target,
+        mapping,
+    })
+}
@@ -0,0 +1,238 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::auth::domain::Claims;
+use crate::bom::service::create::insert_bom_line;
+use crate::errors::app_error::AppError;
+use crate::errors::validation::field_validation_error;
+use crate::import::domain::{ImportJob, ImportJobDetail, ImportRow, ImportRowError, ImportTarget};
+use crate::part::service::create::insert_part;
+
+use sqlx::types::Json;
+use sqlx::{Connection, PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::create::insert_import_errors;
+use super::get::{ensure_job_access, fetch_import_job};
+use super::parse::{bom_from_row, part_from_row};
+
+/// 検証済みの取り込みジョブを 1 つのトランザクションで登録する。
+/// 1 行でも登録できなければすべて取り消し、失敗した行をジョブに記録する。
+pub async fn commit_import(
+    claims: Claims,
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<ImportJobDetail, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to commit import".to_string())
+    })?;
+
+    let job = fetch_import_job(&mut tx, id, true).await?;
+    ensure_job_access(&claims, &job)?;
+    if job.status != ImportJob::VALIDATED {
+        info!("Import job {} cannot be committed: {}", id, job.status);
+        return Err(AppError::ValidationError(field_validation_error(
+            "status",
+            &format!(
+                "Import job is {}; only validated imports can be committed",
+                job.status
+            ),
+        )));
+    }
+    let target = ImportTarget::parse(&job.target)
+        .ok_or_else(|| AppError::InternalError(format!("Unknown import target: {}", job.target)))?;
+
+    let rows = sqlx::query!(
+        r#"SELECT row_number, data AS "data: Json<HashMap<String, String>>"
+        FROM import_job_rows
+        WHERE job_id = $1
+        ORDER BY row_number
+        "#,
+        id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching import rows: {}", e);
+        AppError::DatabaseError("Failed to commit import".to_string())
+    })?;
+
+    // 行ごとにセーブポイントを使い、失敗した後の行も検証を続ける
+    let mut errors = Vec::new();
+    for record in rows {
+        let row = ImportRow {
+            row: record.row_number,
+            values: record.data.0,
+        };
+        let mut savepoint = tx.begin().await.map_err(|e| {
+            error!("DB error during creating savepoint: {}", e);
+            AppError::DatabaseError("Failed to commit import".to_string())
+        })?;
+
+        match commit_row(&mut savepoint, &claims, user_id, target, &row).await {
+            Ok(()) => savepoint.commit().await,
+            Err(e) => {
+                errors.extend(ImportRowError::from_app_error(row.row, e));
+                savepoint.rollback().await
+            }
+        }
+        .map_err(|e| {
+            error!("DB error during releasing savepoint: {}", e);
+            AppError::DatabaseError("Failed to commit import".to_string())
+        })?;
+    }
+
+    if !errors.is_empty() {
+        tx.rollback().await.map_err(|e| {
+            error!("DB error during rolling back import: {}", e);
+            AppError::DatabaseError("Failed to commit import".to_string())
+        })?;
+        return record_failure(pool, id, errors).await;
+    }
+
+    let job = sqlx::query_as!(
+        ImportJob,
+        r#"UPDATE import_jobs
+        SET status = $2, committed_rows = total_rows, committed_at = NOW()
+        WHERE id = $1
+        RETURNING id, target, format, file_name,
+                  mapping AS "mapping: Json<HashMap<String, String>>",
+                  status, total_rows, error_rows, committed_rows,
+                  created_at, created_by, committed_at"#,
+        id,
+        ImportJob::COMMITTED
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during updating import job: {}", e);
+        AppError::DatabaseError("Failed to commit import".to_string())
+    })?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing import: {}", e);
+        AppError::DatabaseError("Failed to commit import".to_string())
+    })?;
+
+    info!("Import job committed: {} ({} rows)", id, job.committed_rows);
+    Ok(ImportJobDetail {
+        job,
+        errors: Vec::new(),
+    })
+}
+
+/// 1 行を登録する。取り込みの検証でも、セーブポイント内で実行してから取り消すのに使う
+pub async fn commit_row(
+    conn: &mut PgConnection,
+    claims: &Claims,
+    user_id: Uuid,
+    target: ImportTarget,
+    row: &ImportRow,
+) -> Result<(), AppError> {
+    match target {
+        ImportTarget::Parts => {
+            insert_part(conn, user_id, part_from_row(row)?).await?;
+        }
+        ImportTarget::Bom => {
+            let line = bom_from_row(row)?;
+            let parent_id =
+                find_part_id(conn, "parent_part_number", &line.parent_part_number).await?;
+            let child_id = find_part_id(conn, "child_part_number", &line.child_part_number).await?;
+            insert_bom_line(conn, claims, parent_id, line.into_line(child_id))
+                .await
+                .map_err(|e| match e {
+                    // ファイル上の列名で報告する
+                    AppError::ValidationError(mut response) => {
+                        for error in &mut response.errors {
+                            if error.field == "child_id" {
+                                error.field = "child_part_number".to_string();
+                            }
+                        }
+                        AppError::ValidationError(response)
+                    }
+                    e => e,
+                })?;
+        }
+    }
+    Ok(())
+}
+
+async fn find_part_id(
+    conn: &mut PgConnection,
+    field: &str,
+    part_number: &str,
+) -> Result<Uuid, AppError> {
+    sqlx::query_scalar!(
+        r#"SELECT id FROM parts WHERE part_number = $1 AND deleted_at IS NULL"#,
+        part_number
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching part by number: {}", e);
+        AppError::DatabaseError("DB select failed".to_string())
+    })?
+    .ok_or_else(|| {
+        AppError::ValidationError(field_validation_error(
+            field,
+            &format!("Part not found: {}", part_number),
+        ))
+    })
+}
+
+/// 登録に失敗した行を記録し、ジョブを failed にする
+async fn record_failure(
+    pool: &PgPool,
+    id: Uuid,
+    errors: Vec<ImportRowError>,
+) -> Result<ImportJobDetail, AppError> {
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to record import failure".to_string())
+    })?;
+
+    let error_rows = errors.iter().map(|e| e.row).collect::<HashSet<_>>().len();
+    let job = sqlx::query_as!(
+        ImportJob,
+        r#"UPDATE import_jobs
+        SET status = $2, error_rows = $3
+        WHERE id = $1
+        RETURNING id, target, format, file_name,
+                  mapping AS "mapping: Json<HashMap<String, String>>",
+                  status, total_rows, error_rows, committed_rows,
+                  created_at, created_by, committed_at"#,
+        id,
+        ImportJob::FAILED,
+        error_rows as i32
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during updating import job: {}", e);
+        AppError::DatabaseError("Failed to record import failure".to_string())
+    })?;
+
+    sqlx::query!("DELETE FROM import_job_errors WHERE job_id = $1", id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("DB error during clearing import errors: {}", e);
+            AppError::DatabaseError("Failed to record import failure".to_string())
+        })?;
+    insert_import_errors(&mut tx, id, &errors).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing import failure: {}", e);
+        AppError::DatabaseError("Failed to record import failure".to_string())
+    })?;
+
+    info!(
+        "Import job failed: {} ({} rows with errors)",
+        id, error_rows
+    );
+    Ok(ImportJobDetail { job, errors })
+}
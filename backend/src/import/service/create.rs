@@ -0,0 +1,296 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::auth::domain::Claims;
+use crate::errors::app_error::AppError;
+use crate::errors::validation::field_validation_error;
+use crate::import::domain::{
+    ImportFormat, ImportJob, ImportJobDetail, ImportRow, ImportRowError, ImportTarget, ImportUpload,
+};
+use crate::part::service::numbering::ensure_part_number_available;
+
+use sqlx::types::Json;
+use sqlx::{Connection, PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::commit::commit_row;
+use super::parse::{MAX_ROWS, bom_from_row, map_rows, part_from_row, read_sheet, resolve_mapping};
+
+/// ファイルを読み込んで検証だけを行い (dry-run)、結果を取り込みジョブとして保存する。
+/// 部品・構成はまだ登録しない。
+pub async fn create_import(
+    claims: Claims,
+    pool: &PgPool,
+    upload: ImportUpload,
+) -> Result<ImportJobDetail, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let format = ImportFormat::detect(&upload.file_name, upload.content_type.as_deref())
+        .ok_or_else(|| {
+            AppError::UnsupportedMediaType("Only CSV and XLSX files can be imported".to_string())
+        })?;
+
+    let mut records = read_sheet(format, &upload.bytes)?;
+    if records.is_empty() {
+        return Err(AppError::ValidationError(field_validation_error(
+            "file",
+            "File has no header row",
+        )));
+    }
+    let header = records.remove(0);
+    let mapping = resolve_mapping(upload.target, &header, &upload.mapping)?;
+    let rows = map_rows(records, &mapping.columns);
+    if rows.is_empty() || rows.len() > MAX_ROWS {
+        return Err(AppError::ValidationError(field_validation_error(
+            "file",
+            &format!("File must contain between 1 and {} data rows", MAX_ROWS),
+        )));
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to create import job".to_string())
+    })?;
+
+    let errors = match upload.target {
+        ImportTarget::Parts => check_part_rows(&mut tx, user_id, &rows).await?,
+        ImportTarget::Bom => check_bom_rows(&mut tx, &claims, user_id, &rows).await?,
+    };
+    let error_rows = errors.iter().map(|e| e.row).collect::<HashSet<_>>().len();
+    let status = if errors.is_empty() {
+        ImportJob::VALIDATED
+    } else {
+        ImportJob::INVALID
+    };
+
+    let job = sqlx::query_as!(
+        ImportJob,
+        r#"INSERT INTO import_jobs
+        (target, format, file_name, mapping, status, total_rows, error_rows, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, target, format, file_name,
+                  mapping AS "mapping: Json<HashMap<String, String>>",
+                  status, total_rows, error_rows, committed_rows,
+                  created_at, created_by, committed_at"#,
+        upload.target.as_str(),
+        format.as_str(),
+        upload.file_name,
+        Json(&mapping.names) as _,
+        status,
+        rows.len() as i32,
+        error_rows as i32,
+        user_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during import job insertion: {}", e);
+        AppError::DatabaseError("Failed to create import job".to_string())
+    })?;
+
+    let row_numbers: Vec<i32> = rows.iter().map(|row| row.row).collect();
+    let data: Vec<serde_json::Value> = rows
+        .iter()
+        .map(|row| serde_json::json!(row.values))
+        .collect();
+    sqlx::query!(
+        r#"INSERT INTO import_job_rows (job_id, row_number, data)
+        SELECT $1, * FROM UNNEST($2::int[], $3::jsonb[])"#,
+        job.id,
+        &row_numbers,
+        &data
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during import rows insertion: {}", e);
+        AppError::DatabaseError("Failed to create import job".to_string())
+    })?;
+
+    insert_import_errors(&mut tx, job.id, &errors).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing import job: {}", e);
+        AppError::DatabaseError("Failed to create import job".to_string())
+    })?;
+
+    info!(
+        "Import job created: {} ({} rows, {} with errors)",
+        job.id, job.total_rows, job.error_rows
+    );
+    Ok(ImportJobDetail { job, errors })
+}
+
+pub async fn insert_import_errors(
+    conn: &mut PgConnection,
+    job_id: Uuid,
+    errors: &[ImportRowError],
+) -> Result<(), AppError> {
+    let rows: Vec<i32> = errors.iter().map(|e| e.row).collect();
+    let fields: Vec<String> = errors.iter().map(|e| e.field.clone()).collect();
+    let messages: Vec<String> = errors.iter().map(|e| e.message.clone()).collect();
+
+    sqlx::query!(
+        r#"INSERT INTO import_job_errors (job_id, row_number, field, message)
+        SELECT $1, * FROM UNNEST($2::int[], $3::text[], $4::text[])"#,
+        job_id,
+        &rows,
+        &fields,
+        &messages
+    )
+    .execute(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during import errors insertion: {}", e);
+        AppError::DatabaseError("Failed to save import errors".to_string())
+    })?;
+    Ok(())
+}
+
+/// 部品行の検証。ファイル内の品番の重複と、既存部品・他ユーザーの予約との衝突も確認する
+async fn check_part_rows(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    rows: &[ImportRow],
+) -> Result<Vec<ImportRowError>, AppError> {
+    let mut errors = Vec::new();
+    let mut seen: HashMap<String, i32> = HashMap::new();
+
+    for row in rows {
+        let part = match part_from_row(row) {
+            Ok(part) => part,
+            Err(e) => {
+                errors.extend(ImportRowError::from_app_error(row.row, e));
+                continue;
+            }
+        };
+        if part.part_number.is_empty() {
+            continue;
+        }
+        if let Some(first) = seen.get(&part.part_number) {
+            errors.push(ImportRowError::new(
+                row.row,
+                "part_number",
+                &format!("Duplicate part_number in file (row {})", first),
+            ));
+            continue;
+        }
+        seen.insert(part.part_number.clone(), row.row);
+
+        match ensure_part_number_available(conn, &part.part_number, None, user_id).await {
+            Ok(()) => {}
+            Err(e @ AppError::Conflict(_)) => {
+                errors.extend(ImportRowError::from_app_error(row.row, e))
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(errors)
+}
+
+/// 構成行の検証。親・子の品番が登録済みであることと、ファイル内の重複を確認したうえで、
+/// 問題のない行を実際に登録してから取り消し、親部品の権限・既存行との重複・循環も確認する
+async fn check_bom_rows(
+    conn: &mut PgConnection,
+    claims: &Claims,
+    user_id: Uuid,
+    rows: &[ImportRow],
+) -> Result<Vec<ImportRowError>, AppError> {
+    let mut errors = Vec::new();
+    let mut lines = Vec::new();
+    for row in rows {
+        match bom_from_row(row) {
+            Ok(line) => lines.push((row.row, line)),
+            Err(e) => errors.extend(ImportRowError::from_app_error(row.row, e)),
+        }
+    }
+
+    let part_numbers: Vec<String> = lines
+        .iter()
+        .flat_map(|(_, line)| {
+            [
+                line.parent_part_number.clone(),
+                line.child_part_number.clone(),
+            ]
+        })
+        .collect();
+    let existing: HashSet<String> = sqlx::query_scalar!(
+        r#"SELECT part_number FROM parts WHERE part_number = ANY($1) AND deleted_at IS NULL"#,
+        &part_numbers
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching parts for BOM import: {}", e);
+        AppError::DatabaseError("DB select failed".to_string())
+    })?
+    .into_iter()
+    .collect();
+
+    let mut seen: HashMap<(String, String), i32> = HashMap::new();
+    for (row, line) in lines {
+        for (field, part_number) in [
+            ("parent_part_number", &line.parent_part_number),
+            ("child_part_number", &line.child_part_number),
+        ] {
+            if !existing.contains(part_number) {
+                errors.push(ImportRowError::new(
+                    row,
+                    field,
+                    &format!("Part not found: {}", part_number),
+                ));
+            }
+        }
+        if line.parent_part_number == line.child_part_number {
+            errors.push(ImportRowError::new(
+                row,
+                "child_part_number",
+                "A part cannot contain itself",
+            ));
+        }
+        let key = (line.parent_part_number, line.child_part_number);
+        if let Some(first) = seen.get(&key) {
+            errors.push(ImportRowError::new(
+                row,
+                "child_part_number",
+                &format!("Duplicate BOM line in file (row {})", first),
+            ));
+        } else {
+            seen.insert(key, row);
+        }
+    }
+
+    let failed: HashSet<i32> = errors.iter().map(|e| e.row).collect();
+    let mut dry_run = conn.begin().await.map_err(|e| {
+        error!("DB error during creating savepoint: {}", e);
+        AppError::DatabaseError("Failed to create import job".to_string())
+    })?;
+    for row in rows.iter().filter(|row| !failed.contains(&row.row)) {
+        let mut savepoint = dry_run.begin().await.map_err(|e| {
+            error!("DB error during creating savepoint: {}", e);
+            AppError::DatabaseError("Failed to create import job".to_string())
+        })?;
+        match commit_row(&mut savepoint, claims, user_id, ImportTarget::Bom, row).await {
+            Ok(()) => savepoint.commit().await,
+            Err(e @ AppError::DatabaseError(_)) | Err(e @ AppError::InternalError(_)) => {
+                return Err(e);
+            }
+            Err(e) => {
+                errors.extend(ImportRowError::from_app_error(row.row, e));
+                savepoint.rollback().await
+            }
+        }
+        .map_err(|e| {
+            error!("DB error during releasing savepoint: {}", e);
+            AppError::DatabaseError("Failed to create import job".to_string())
+        })?;
+    }
+    dry_run.rollback().await.map_err(|e| {
+        error!("DB error during rolling back BOM dry-run: {}", e);
+        AppError::DatabaseError("Failed to create import job".to_string())
+    })?;
+
+    errors.sort_by_key(|e| e.row);
+    Ok(errors)
+}
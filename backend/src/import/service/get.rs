@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use crate::auth::domain::{Claims, Role};
+use crate::errors::app_error::AppError;
+use crate::import::domain::{ImportJob, ImportJobDetail, ImportRowError};
+
+use sqlx::types::Json;
+use sqlx::{PgConnection, PgExecutor, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// 自分の取り込みジョブ一覧 (管理者はすべて)
+pub async fn get_imports(claims: Claims, pool: &PgPool) -> Result<Vec<ImportJob>, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let jobs = sqlx::query_as!(
+        ImportJob,
+        r#"SELECT id, target, format, file_name,
+                  mapping AS "mapping: Json<HashMap<String, String>>",
+                  status, total_rows, error_rows, committed_rows,
+                  created_at, created_by, committed_at
+        FROM import_jobs
+        WHERE $1 OR created_by = $2
+        ORDER BY created_at DESC
+        "#,
+        claims.role == Role::Admin,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching import jobs: {}", e);
+        AppError::DatabaseError("DB select failed".to_string())
+    })?;
+
+    info!("Fetched {} import jobs successfully", jobs.len());
+    Ok(jobs)
+}
+
+pub async fn get_import(
+    claims: Claims,
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<ImportJobDetail, AppError> {
+    let mut conn = pool.acquire().await.map_err(|e| {
+        error!("DB error during acquiring connection: {}", e);
+        AppError::DatabaseError("DB select failed".to_string())
+    })?;
+
+    let job = fetch_import_job(&mut conn, id, false).await?;
+    ensure_job_access(&claims, &job)?;
+    let errors = fetch_import_errors(&mut *conn, id).await?;
+
+    Ok(ImportJobDetail { job, errors })
+}
+
+/// 取り込みエラーを CSV (row, field, message) で返す
+pub async fn get_import_error_report(
+    claims: Claims,
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<String, AppError> {
+    let detail = get_import(claims, pool, id).await?;
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let write_error = |e: csv::Error| {
+        error!("Failed to write import error report: {}", e);
+        AppError::InternalError("Failed to write error report".to_string())
+    };
+    writer
+        .write_record(["row", "field", "message"])
+        .map_err(write_error)?;
+    for error in &detail.errors {
+        writer
+            .write_record([error.row.to_string().as_str(), &error.field, &error.message])
+            .map_err(write_error)?;
+    }
+    let bytes = writer.into_inner().map_err(|e| {
+        error!("Failed to write import error report: {}", e);
+        AppError::InternalError("Failed to write error report".to_string())
+    })?;
+
+    String::from_utf8(bytes)
+        .map_err(|e| AppError::InternalError(format!("Invalid error report: {}", e)))
+}
+
+/// 取り込みジョブを取得する。`for_update` の場合は行ロックを取る
+pub async fn fetch_import_job(
+    conn: &mut PgConnection,
+    id: Uuid,
+    for_update: bool,
+) -> Result<ImportJob, AppError> {
+    let job = if for_update {
+        sqlx::query_as!(
+            ImportJob,
+            r#"SELECT id, target, format, file_name,
+                      mapping AS "mapping: Json<HashMap<String, String>>",
+                      status, total_rows, error_rows, committed_rows,
+                      created_at, created_by, committed_at
+            FROM import_jobs
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    } else {
+        sqlx::query_as!(
+            ImportJob,
+            r#"SELECT id, target, format, file_name,
+                      mapping AS "mapping: Json<HashMap<String, String>>",
+                      status, total_rows, error_rows, committed_rows,
+                      created_at, created_by, committed_at
+            FROM import_jobs
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(conn)
+        .await
+    }
+    .map_err(|e| {
+        error!("DB error during fetching import job: {}", e);
+        AppError::DatabaseError("DB select failed".to_string())
+    })?;
+
+    job.ok_or_else(|| AppError::NotFound(format!("Import job not found: {}", id)))
+}
+
+pub async fn fetch_import_errors(
+    executor: impl PgExecutor<'_>,
+    id: Uuid,
+) -> Result<Vec<ImportRowError>, AppError> {
+    sqlx::query_as!(
+        ImportRowError,
+        r#"SELECT row_number AS row, field, message
+        FROM import_job_errors
+        WHERE job_id = $1
+        ORDER BY row_number, id
+        "#,
+        id
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching import errors: {}", e);
+        AppError::DatabaseError("DB select failed".to_string())
+    })
+}
+
+/// 取り込みジョブの作成者か管理者のみ参照・確定できる
+pub fn ensure_job_access(claims: &Claims, job: &ImportJob) -> Result<(), AppError> {
+    if claims.role == Role::Admin
+        || job.created_by.map(|id| id.to_string()).as_deref() == Some(claims.sub.as_str())
+    {
+        Ok(())
+    } else {
+        info!("User {} cannot access import job {}", claims.sub, job.id);
+        Err(AppError::Unauthorized(
+            "You do not own this import job.".to_string(),
+        ))
+    }
+}
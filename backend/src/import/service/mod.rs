@@ -0,0 +1,8 @@
+pub mod commit;
+pub mod create;
+pub mod get;
+pub mod parse;
+
+pub use commit::commit_import;
+pub use create::create_import;
+pub use get::{get_import, get_import_error_report, get_imports};
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use calamine::{Reader, Xlsx, open_workbook_from_rs};
+use validator::Validate;
+
+use crate::errors::app_error::AppError;
+use crate::errors::validation::{
+    FieldError, extract_validation_errors, field_validation_error, fields_validation_error,
+};
+use crate::import::domain::{BomImportRow, ImportFormat, ImportRow, ImportTarget};
+use crate::part::domain::NewPart;
+
+/// 1 ファイルで取り込める最大行数
+pub const MAX_ROWS: usize = 10_000;
+
+/// 先頭シートを文字列の表として読み込む (1 行目が見出し行)
+pub fn read_sheet(format: ImportFormat, bytes: &[u8]) -> Result<Vec<Vec<String>>, AppError> {
+    let mut records = match format {
+        ImportFormat::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .from_reader(bytes);
+            reader
+                .records()
+                .map(|record| {
+                    record
+                        .map(|record| record.iter().map(str::to_string).collect())
+                        .map_err(|e| invalid_file(&format!("Invalid CSV: {}", e)))
+                })
+                .collect::<Result<Vec<Vec<String>>, AppError>>()?
+        }
+        ImportFormat::Xlsx => {
+            let mut workbook: Xlsx<_> = open_workbook_from_rs(Cursor::new(bytes))
+                .map_err(|e| invalid_file(&format!("Invalid XLSX: {}", e)))?;
+            let range = workbook
+                .worksheet_range_at(0)
+                .ok_or_else(|| invalid_file("Workbook has no sheets"))?
+                .map_err(|e| invalid_file(&format!("Invalid XLSX: {}", e)))?;
+            range
+                .rows()
+                .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                .collect()
+        }
+    };
+
+    // Excel が付ける UTF-8 BOM を見出しから除く
+    if let Some(first) = records.first_mut().and_then(|header| header.first_mut()) {
+        *first = first.trim_start_matches('\u{feff}').to_string();
+    }
+    Ok(records)
+}
+
+/// 項目ごとに読み込む列
+#[derive(Debug)]
+pub struct ColumnMapping {
+    /// 項目 -> 列番号
+    pub columns: HashMap<String, usize>,
+    /// 項目 -> 実際に使った列名
+    pub names: HashMap<String, String>,
+}
+
+/// 項目ごとに読み込む列を決める。`mapping` にない項目は同名の列 (大文字小文字を無視) を使う。
+pub fn resolve_mapping(
+    target: ImportTarget,
+    header: &[String],
+    mapping: &HashMap<String, String>,
+) -> Result<ColumnMapping, AppError> {
+    let mut errors = Vec::new();
+    for field in mapping.keys() {
+        if !target.fields().contains(&field.as_str()) {
+            errors.push(FieldError {
+                field: "mapping".to_string(),
+                message: format!("Unknown field for {}: {}", target.as_str(), field),
+            });
+        }
+    }
+
+    let mut columns = HashMap::new();
+    let mut names = HashMap::new();
+    for field in target.fields() {
+        let column = mapping.get(*field).map(String::as_str).unwrap_or(field);
+        let index = header
+            .iter()
+            .position(|name| name.trim().eq_ignore_ascii_case(column.trim()));
+        match index {
+            Some(index) => {
+                columns.insert(field.to_string(), index);
+                names.insert(field.to_string(), header[index].trim().to_string());
+            }
+            None if mapping.contains_key(*field) => errors.push(FieldError {
+                field: "mapping".to_string(),
+                message: format!("Column not found for {}: {}", field, column),
+            }),
+            None if target.required_fields().contains(field) => errors.push(FieldError {
+                field: "mapping".to_string(),
+                message: format!("No column is mapped to required field {}", field),
+            }),
+            None => {}
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(ColumnMapping { columns, names })
+    } else {
+        Err(AppError::ValidationError(fields_validation_error(errors)))
+    }
+}
+
+/// 見出し行を除いた各行に列を割り当てる。空行は読み飛ばす。
+pub fn map_rows(records: Vec<Vec<String>>, columns: &HashMap<String, usize>) -> Vec<ImportRow> {
+    records
+        .into_iter()
+        .enumerate()
+        .filter(|(_, record)| record.iter().any(|cell| !cell.trim().is_empty()))
+        .map(|(index, record)| ImportRow {
+            row: index as i32 + 2,
+            values: columns
+                .iter()
+                .filter_map(|(field, column)| {
+                    record
+                        .get(*column)
+                        .map(|value| (field.clone(), value.clone()))
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// 行を `NewPart` にして検証する。品番が空の行は登録時に自動採番する。
+pub fn part_from_row(row: &ImportRow) -> Result<NewPart, AppError> {
+    let part = NewPart {
+        part_number: row.get("part_number").unwrap_or_default().to_string(),
+        name: row.get("name").unwrap_or_default().to_string(),
+        description: row.get("description").map(str::to_string),
+        kind: row.get("kind").map(str::to_string),
+    };
+
+    if let Err(e) = part.validate() {
+        let mut errors = extract_validation_errors(e).errors;
+        errors.retain(|error| !(error.field == "part_number" && part.part_number.is_empty()));
+        if !errors.is_empty() {
+            return Err(AppError::ValidationError(fields_validation_error(errors)));
+        }
+    }
+    Ok(part)
+}
+
+/// 行を構成行にして検証する。単位を省略した場合は `EA`
+pub fn bom_from_row(row: &ImportRow) -> Result<BomImportRow, AppError> {
+    let mut errors = Vec::new();
+
+    let quantity = match row.get("quantity").map(str::parse::<f64>) {
+        Some(Ok(quantity)) => quantity,
+        Some(Err(_)) => {
+            errors.push(parse_error("quantity", "quantity must be a number"));
+            0.0
+        }
+        None => {
+            errors.push(parse_error("quantity", "quantity must not be empty"));
+            0.0
+        }
+    };
+    let find_number = match row.get("find_number").map(str::parse::<i32>) {
+        Some(Ok(find_number)) => Some(find_number),
+        Some(Err(_)) => {
+            errors.push(parse_error("find_number", "find_number must be an integer"));
+            None
+        }
+        None => None,
+    };
+
+    let line = BomImportRow {
+        parent_part_number: row
+            .get("parent_part_number")
+            .unwrap_or_default()
+            .to_string(),
+        child_part_number: row.get("child_part_number").unwrap_or_default().to_string(),
+        quantity,
+        unit_of_measure: row.get("unit_of_measure").unwrap_or("EA").to_string(),
+        find_number,
+        reference_designators: row
+            .get("reference_designators")
+            .unwrap_or_default()
+            .split([',', ';', ' '])
+            .filter(|designator| !designator.is_empty())
+            .map(str::to_string)
+            .collect(),
+    };
+
+    if let Err(e) = line.validate() {
+        let parsed: Vec<&str> = errors.iter().map(|error| error.field.as_str()).collect();
+        let invalid: Vec<FieldError> = extract_validation_errors(e)
+            .errors
+            .into_iter()
+            .filter(|error| !parsed.contains(&error.field.as_str()))
+            .collect();
+        errors.extend(invalid);
+    }
+
+    if errors.is_empty() {
+        Ok(line)
+    } else {
+        Err(AppError::ValidationError(fields_validation_error(errors)))
+    }
+}
+
+fn parse_error(field: &str, message: &str) -> FieldError {
+    FieldError {
+        field: field.to_string(),
+        message: message.to_string(),
+    }
+}
+
+fn invalid_file(message: &str) -> AppError {
+    AppError::ValidationError(field_validation_error("file", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{bom_from_row, map_rows, part_from_row, read_sheet, resolve_mapping};
+    use crate::errors::app_error::AppError;
+    use crate::import::domain::{ImportFormat, ImportRowError, ImportTarget};
+
+    fn fields(e: AppError) -> Vec<String> {
+        ImportRowError::from_app_error(0, e)
+            .into_iter()
+            .map(|error| error.field)
+            .collect()
+    }
+
+    #[test]
+    fn test_map_parts_csv() {
+        let csv = "\u{feff}品番,Name,Memo\nA-1,Bolt,M3\n,,\n,Nut,\n,,x\n";
+        let mut records = read_sheet(ImportFormat::Csv, csv.as_bytes()).unwrap();
+        let header = records.remove(0);
+        let mapping = HashMap::from([
+            ("part_number".to_string(), "品番".to_string()),
+            ("description".to_string(), "memo".to_string()),
+        ]);
+
+        let mapping = resolve_mapping(ImportTarget::Parts, &header, &mapping).unwrap();
+        assert_eq!(mapping.names["name"], "Name");
+        assert_eq!(mapping.names["description"], "Memo");
+        assert!(!mapping.names.contains_key("kind"));
+
+        let rows = map_rows(records, &mapping.columns);
+        assert_eq!(
+            rows.iter().map(|row| row.row).collect::<Vec<_>>(),
+            vec![2, 4, 5]
+        );
+
+        let part = part_from_row(&rows[0]).unwrap();
+        assert_eq!(
+            (part.part_number.as_str(), part.name.as_str()),
+            ("A-1", "Bolt")
+        );
+        assert_eq!(part.description.as_deref(), Some("M3"));
+        // 品番は空でもよい (自動採番)
+        assert_eq!(part_from_row(&rows[1]).unwrap().part_number, "");
+        assert_eq!(fields(part_from_row(&rows[2]).unwrap_err()), vec!["name"]);
+    }
+
+    #[test]
+    fn test_resolve_mapping_errors() {
+        let header = vec!["parent".to_string(), "qty".to_string()];
+        let mapping = HashMap::from([
+            ("parent_part_number".to_string(), "parent".to_string()),
+            ("quantity".to_string(), "amount".to_string()),
+            ("color".to_string(), "qty".to_string()),
+        ]);
+        let e = resolve_mapping(ImportTarget::Bom, &header, &mapping).unwrap_err();
+        // 未知の項目、見つからない列、必須項目 (child_part_number) の欠落
+        assert_eq!(fields(e).len(), 3);
+    }
+
+    #[test]
+    fn test_bom_from_row() {
+        let header = vec![
+            "parent_part_number".to_string(),
+            "child_part_number".to_string(),
+            "quantity".to_string(),
+            "find_number".to_string(),
+            "reference_designators".to_string(),
+        ];
+        let mapping = resolve_mapping(ImportTarget::Bom, &header, &HashMap::new()).unwrap();
+        let rows = map_rows(
+            vec![
+                vec!["A", "B", "2.5", "10", "R1, R2"],
+                vec!["A", "", "two", "0", ""],
+            ]
+            .into_iter()
+            .map(|row| row.into_iter().map(str::to_string).collect())
+            .collect(),
+            &mapping.columns,
+        );
+
+        let line = bom_from_row(&rows[0]).unwrap();
+        assert_eq!(line.quantity, 2.5);
+        assert_eq!(line.unit_of_measure, "EA");
+        assert_eq!(line.find_number, Some(10));
+        assert_eq!(line.reference_designators, vec!["R1", "R2"]);
+
+        let mut invalid = fields(bom_from_row(&rows[1]).unwrap_err());
+        invalid.sort();
+        assert_eq!(
+            invalid,
+            vec!["child_part_number", "find_number", "quantity"]
+        );
+    }
+}
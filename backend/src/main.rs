@@ -1,21 +1,70 @@
+mod audit;
 mod auth;
+mod bom;
+mod eco;
+mod ecr;
 mod errors;
+mod import;
 mod models;
 mod part;
+mod patch;
 mod responses;
 
+use audit::domain::{AuditAction, AuditEntity, AuditLog};
+use audit::request_id::{X_REQUEST_ID, request_id};
+use audit::route::get_audit_logs;
 use auth::jwt::jwt_auth;
-use auth::route::{login, signup};
-use auth::service::user_create::create_user_with_role;
+use auth::password_policy::load_password_policy;
+use auth::route::{
+    change_password, confirm_totp_enrollment, create_api_key, create_password_reset, disable_totp,
+    get_api_keys, get_login_lockouts, get_mfa_status, get_security_policy, get_sessions, login,
+    login_mfa, logout, oidc_authorize, oidc_callback, oidc_token, refresh_token,
+    regenerate_recovery_codes, reset_password, reset_user_mfa, revoke_api_key, revoke_session,
+    revoke_user_sessions, signup, start_totp_enrollment, unlock_ip, unlock_user,
+    update_security_policy,
+};
+use auth::service::user_create::ensure_initial_admin;
+use axum::extract::DefaultBodyLimit;
 use axum::http::HeaderValue;
-use axum::routing::post;
+use axum::routing::{delete, post, put};
 use axum::{Router, http, middleware, routing::get};
+use bom::domain::{BomExportLine, BomLine, BomNode, BomTree, NewBomLine, UpdateBomLine};
+use bom::route::{add_bom_line, delete_bom_line, export_bom, get_bom, update_bom_line};
 use dotenvy::dotenv;
-use http::header::{AUTHORIZATION, CONTENT_TYPE};
-use part::domain::{NewPart, Part};
-use part::route::{create_part, delete_part, get_part, get_parts, update_part};
+use eco::domain::{
+    Eco, EcoApproval, EcoDecision, EcoDetail, EcoItem, NewEco, NewEcoItem, SubmitEco,
+};
+use eco::route::{
+    add_eco_item, approve_eco, create_eco, get_eco, get_ecos, reject_eco, remove_eco_item,
+    submit_eco,
+};
+use ecr::domain::{
+    Ecr, EcrDetail, EcrPriority, EcrStatus, EcrTransitionRequest, NewEcr, ReasonCode,
+};
+use ecr::route::{create_ecr, get_ecr, get_ecrs, promote_ecr, transition_ecr};
+use http::header::{AUTHORIZATION, CONTENT_TYPE, ETAG, IF_MATCH};
+use import::domain::{ImportForm, ImportJob, ImportJobDetail, ImportRowError, ImportTarget};
+use import::route::{commit_import, create_import, get_import, get_import_errors, get_imports};
+use part::domain::{
+    BulkItemResult, BulkMode, BulkPartOperation, BulkPartRequest, BulkPartResult, FieldChange,
+    Lifecycle, LifecycleState, LifecycleTransition, NewPart, Part, PartNumberReservation,
+    PartNumberScheme, PartRevision, PartSearchHit, PartSortColumn, PurgeResult, ReservePartNumbers,
+    RevisionDiff, SearchHighlight, TransitionRequest, TrashedPart, UpdatePartNumberScheme,
+    WhereUsed, WhereUsedLink, WhereUsedPath,
+};
+use part::route::{
+    bulk_parts, create_part, delete_part, diff_revisions, export_parts, get_lifecycle, get_part,
+    get_part_number_schemes, get_parts, get_revision, get_trash, get_where_used, list_revisions,
+    patch_part, purge_trash, release_revision, reserve_part_numbers, restore_part, search_parts,
+    transition_part, update_part, update_part_number_scheme,
+};
+use part::service::revision::backfill_initial_revisions;
+use patch::domain::PatchOperation;
+use responses::export::ExportFormat;
+use responses::pagination::{Pagination, SortOrder};
 use sqlx::postgres::PgPoolOptions;
 use std::env;
+use std::net::SocketAddr;
 use tokio::net::TcpListener;
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use tracing::{error, info};
@@ -23,6 +72,9 @@ use tracing_subscriber::FmtSubscriber;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
+/// 取り込みファイルのアップロード上限
+const IMPORT_BODY_LIMIT: usize = 20 * 1024 * 1024;
+
 async fn health_check() -> &'static str {
     "OK"
 }
@@ -37,6 +89,11 @@ async fn main() {
         .finish();
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
 
+    if let Err(err) = load_password_policy() {
+        error!("{}", err);
+        panic!("Password policy error");
+    }
+
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URLが設定されていません。");
     let cors_origin = env::var("CORS_ORIGIN").expect("CORS_ORIGIN must be set");
 
@@ -58,10 +115,16 @@ async fn main() {
         panic!("Migration error");
     }
 
-    // resistor admin user
-    create_user_with_role(&pool, "admin", "admin", "admin")
-        .await
-        .expect("Failed to ensure default admin");
+    match backfill_initial_revisions(&pool).await {
+        Ok(0) => {}
+        Ok(count) => info!("Created initial revisions for {} parts", count),
+        Err(err) => panic!("Failed to backfill revisions: {:?}", err),
+    }
+
+    // 初期管理者 (パスワードは ADMIN_INITIAL_PASSWORD)
+    if let Err(err) = ensure_initial_admin(&pool).await {
+        panic!("Failed to ensure initial admin: {:?}", err);
+    }
 
     let cors = CorsLayer::new()
         .allow_origin(
@@ -73,17 +136,93 @@ async fn main() {
             http::Method::GET,
             http::Method::POST,
             http::Method::PUT,
+            http::Method::PATCH,
             http::Method::DELETE,
         ])
-        .allow_headers([AUTHORIZATION, CONTENT_TYPE]);
+        .allow_headers([AUTHORIZATION, CONTENT_TYPE, IF_MATCH, X_REQUEST_ID.clone()])
+        .expose_headers([ETAG, X_REQUEST_ID.clone()]);
 
     let protected_routes = Router::new()
         .route("/parts", get(get_parts).post(create_part))
+        .route("/parts/search", get(search_parts))
+        .route("/parts/bulk", post(bulk_parts))
+        .route("/parts/export", get(export_parts))
         .route(
             "/parts/{id}",
-            get(get_part).put(update_part).delete(delete_part),
+            get(get_part)
+                .put(update_part)
+                .patch(patch_part)
+                .delete(delete_part),
+        )
+        .route("/lifecycle", get(get_lifecycle))
+        .route("/part-numbers/schemes", get(get_part_number_schemes))
+        .route(
+            "/part-numbers/schemes/{kind}",
+            put(update_part_number_scheme),
+        )
+        .route("/part-numbers/reserve", post(reserve_part_numbers))
+        .route("/parts/{id}/revisions", get(list_revisions))
+        .route("/parts/{id}/revisions/diff", get(diff_revisions))
+        .route("/parts/{id}/revisions/{revision}", get(get_revision))
+        .route(
+            "/parts/{id}/revisions/{revision}/release",
+            post(release_revision),
+        )
+        .route("/parts/{id}/transition", post(transition_part))
+        .route("/parts/{id}/restore", post(restore_part))
+        .route("/trash", get(get_trash))
+        .route("/trash/purge", post(purge_trash))
+        .route("/parts/{id}/where-used", get(get_where_used))
+        .route("/parts/{id}/bom", get(get_bom).post(add_bom_line))
+        .route("/parts/{id}/bom/export", get(export_bom))
+        .route(
+            "/parts/{id}/bom/{line_id}",
+            put(update_bom_line).delete(delete_bom_line),
+        )
+        .route("/ecos", get(get_ecos).post(create_eco))
+        .route("/ecos/{id}", get(get_eco))
+        .route("/ecos/{id}/items", post(add_eco_item))
+        .route("/ecos/{id}/items/{item_id}", delete(remove_eco_item))
+        .route("/ecos/{id}/submit", post(submit_eco))
+        .route("/ecos/{id}/approve", post(approve_eco))
+        .route("/ecos/{id}/reject", post(reject_eco))
+        .route("/ecrs", get(get_ecrs).post(create_ecr))
+        .route("/ecrs/{id}", get(get_ecr))
+        .route("/ecrs/{id}/transition", post(transition_ecr))
+        .route("/ecrs/{id}/promote", post(promote_ecr))
+        .route(
+            "/imports",
+            get(get_imports)
+                .post(create_import)
+                .layer(DefaultBodyLimit::max(IMPORT_BODY_LIMIT)),
         )
-        .route_layer(middleware::from_fn(jwt_auth));
+        .route("/imports/{id}", get(get_import))
+        .route("/imports/{id}/errors", get(get_import_errors))
+        .route("/imports/{id}/commit", post(commit_import))
+        .route("/audit", get(get_audit_logs))
+        .route("/sessions", get(get_sessions))
+        .route("/api-keys", get(get_api_keys).post(create_api_key))
+        .route("/api-keys/{id}", delete(revoke_api_key))
+        .route("/sessions/{id}", delete(revoke_session))
+        .route("/users/{id}/sessions", delete(revoke_user_sessions))
+        .route("/mfa", get(get_mfa_status))
+        .route(
+            "/mfa/totp",
+            post(start_totp_enrollment).delete(disable_totp),
+        )
+        .route("/mfa/totp/confirm", post(confirm_totp_enrollment))
+        .route("/mfa/recovery-codes", post(regenerate_recovery_codes))
+        .route("/users/{id}/mfa", delete(reset_user_mfa))
+        .route(
+            "/security-policy",
+            get(get_security_policy).put(update_security_policy),
+        )
+        .route("/login-lockouts", get(get_login_lockouts))
+        .route("/login-lockouts/ips/{ip}", delete(unlock_ip))
+        .route("/users/{id}/lockout", delete(unlock_user))
+        .route("/me/password", post(change_password))
+        .route("/users/{id}/password-reset", post(create_password_reset))
+        .route_layer(middleware::from_fn_with_state(pool.clone(), jwt_auth));
 
     let app = Router::new()
         .merge(SwaggerUi::new("/docs").url("/api-docs/openapi.json", ApiDoc::openapi()))
@@ -94,9 +233,17 @@ async fn main() {
         //     get(get_part).put(update_part).delete(delete_part),
         // )
         .route("/login", post(login))
+        .route("/login/mfa", post(login_mfa))
         .route("/signup", post(signup))
+        .route("/password-reset", post(reset_password))
+        .route("/oidc/authorize", get(oidc_authorize))
+        .route("/oidc/callback", get(oidc_callback))
+        .route("/oidc/token", post(oidc_token))
+        .route("/token/refresh", post(refresh_token))
+        .route("/logout", post(logout))
         .merge(protected_routes)
         .with_state(pool)
+        .layer(middleware::from_fn(request_id))
         .layer(cors)
         .layer(TraceLayer::new_for_http()); // HTTPリクエストのログ出力
 
@@ -108,7 +255,13 @@ async fn main() {
         });
     info!("Server is running at http://localhost:3000");
 
-    if let Err(e) = axum::serve(listener, app).await {
+    // ログイン試行の制限に接続元 IP を使う
+    if let Err(e) = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    {
         error!("Server error: {}", e);
     };
 }
@@ -117,16 +270,148 @@ async fn main() {
 #[openapi(
     paths(
         part::route::create_part,
+        part::route::bulk_parts,
         part::route::get_part,
         part::route::get_parts,
+        part::route::export_parts,
+        part::route::search_parts,
         part::route::update_part,
+        part::route::patch_part,
         part::route::delete_part,
+        part::route::get_trash,
+        part::route::restore_part,
+        part::route::purge_trash,
+        part::route::list_revisions,
+        part::route::get_revision,
+        part::route::diff_revisions,
+        part::route::release_revision,
+        part::route::get_where_used,
+        part::route::get_lifecycle,
+        part::route::transition_part,
+        part::route::get_part_number_schemes,
+        part::route::update_part_number_scheme,
+        part::route::reserve_part_numbers,
+        bom::route::get_bom,
+        bom::route::export_bom,
+        bom::route::add_bom_line,
+        bom::route::update_bom_line,
+        bom::route::delete_bom_line,
+        eco::route::create_eco,
+        eco::route::get_ecos,
+        eco::route::get_eco,
+        eco::route::add_eco_item,
+        eco::route::remove_eco_item,
+        eco::route::submit_eco,
+        eco::route::approve_eco,
+        eco::route::reject_eco,
+        ecr::route::create_ecr,
+        ecr::route::get_ecrs,
+        ecr::route::get_ecr,
+        ecr::route::transition_ecr,
+        ecr::route::promote_ecr,
+        import::route::create_import,
+        import::route::get_imports,
+        import::route::get_import,
+        import::route::get_import_errors,
+        import::route::commit_import,
+        audit::route::get_audit_logs,
         auth::route::login,
+        auth::route::login_mfa,
         auth::route::signup,
+        auth::route::oidc_authorize,
+        auth::route::oidc_callback,
+        auth::route::oidc_token,
+        auth::route::refresh_token,
+        auth::route::logout,
+        auth::route::get_sessions,
+        auth::route::revoke_session,
+        auth::route::revoke_user_sessions,
+        auth::route::create_api_key,
+        auth::route::get_api_keys,
+        auth::route::revoke_api_key,
+        auth::route::get_mfa_status,
+        auth::route::start_totp_enrollment,
+        auth::route::confirm_totp_enrollment,
+        auth::route::disable_totp,
+        auth::route::regenerate_recovery_codes,
+        auth::route::reset_user_mfa,
+        auth::route::get_security_policy,
+        auth::route::update_security_policy,
+        auth::route::get_login_lockouts,
+        auth::route::unlock_user,
+        auth::route::unlock_ip,
+        auth::route::change_password,
+        auth::route::create_password_reset,
+        auth::route::reset_password,
     ),
-    components(schemas(Part, NewPart)),
+    components(schemas(
+        Pagination,
+        SortOrder,
+        ExportFormat,
+        PartSortColumn,
+        Part,
+        TrashedPart,
+        PurgeResult,
+        PartSearchHit,
+        SearchHighlight,
+        PatchOperation,
+        BulkMode,
+        BulkPartOperation,
+        BulkPartRequest,
+        BulkItemResult,
+        BulkPartResult,
+        PartNumberScheme,
+        UpdatePartNumberScheme,
+        ReservePartNumbers,
+        PartNumberReservation,
+        NewPart,
+        PartRevision,
+        RevisionDiff,
+        FieldChange,
+        Lifecycle,
+        LifecycleState,
+        LifecycleTransition,
+        TransitionRequest,
+        WhereUsed,
+        WhereUsedLink,
+        WhereUsedPath,
+        BomLine,
+        BomNode,
+        BomTree,
+        BomExportLine,
+        NewBomLine,
+        UpdateBomLine,
+        Eco,
+        EcoApproval,
+        EcoDecision,
+        EcoDetail,
+        EcoItem,
+        NewEco,
+        NewEcoItem,
+        SubmitEco,
+        Ecr,
+        EcrDetail,
+        EcrPriority,
+        EcrStatus,
+        EcrTransitionRequest,
+        NewEcr,
+        ReasonCode,
+        ImportForm,
+        ImportTarget,
+        ImportJob,
+        ImportJobDetail,
+        ImportRowError,
+        AuditLog,
+        AuditEntity,
+        AuditAction
+    )),
     tags(
         (name = "parts", description = "Part management endpoints"),
+        (name = "bom", description = "Bill of materials endpoints"),
+        (name = "ecos", description = "Engineering change order endpoints"),
+        (name = "ecrs", description = "Engineering change request endpoints"),
+        (name = "imports", description = "CSV / XLSX import endpoints"),
+        (name = "audit", description = "Audit log endpoints"),
         (name = "auth", description = "Authentication endpoints"),
     )
 )]
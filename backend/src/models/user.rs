@@ -7,7 +7,10 @@ use uuid::Uuid;
 pub struct User {
     pub id: Uuid,
     pub login_name: String,
-    pub password_hash: String,
+    /// OIDC でログインするユーザーは None
+    pub password_hash: Option<String>,
     pub role: String,
+    pub display_name: Option<String>,
+    pub email: Option<String>,
     pub created_at: Option<DateTime<Utc>>,
 }
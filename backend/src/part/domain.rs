@@ -1,23 +1,101 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 use validator::Validate;
 
-#[derive(sqlx::FromRow, Serialize, ToSchema)]
+use crate::errors::validation::FieldError;
+use crate::responses::etag::Versioned;
+use crate::responses::export::{ExportRecord, ExportValue};
+use crate::responses::pagination::SortOrder;
+
+#[derive(sqlx::FromRow, Serialize, ToSchema, Clone)]
 pub struct Part {
     pub id: Uuid,
     pub part_number: String,
     pub name: String,
     pub description: Option<String>,
     pub kind: Option<String>,
+    pub status: String,
     pub created_at: Option<DateTime<Utc>>,
     pub created_by: Option<Uuid>,
     pub updated_at: Option<DateTime<Utc>>,
+    /// 更新のたびに加算される。`ETag` / `If-Match` で使う
+    pub version: i64,
 }
 
-#[derive(Deserialize, Validate, ToSchema)]
+impl Versioned for Part {
+    fn version(&self) -> i64 {
+        self.version
+    }
+}
+
+/// ゴミ箱の部品
+#[derive(Serialize, ToSchema)]
+pub struct TrashedPart {
+    #[serde(flatten)]
+    pub part: Part,
+    pub deleted_at: DateTime<Utc>,
+    pub deleted_by: Option<Uuid>,
+    /// この日時を過ぎると管理者が完全削除できる
+    pub purge_after: DateTime<Utc>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PurgeResult {
+    pub retention_days: i32,
+    pub purged: Vec<Uuid>,
+    /// 保持期間を過ぎているが、他の部品の構成・リリース済みリビジョン・ECO・ECR から参照されているため残した部品
+    pub kept: Vec<Uuid>,
+}
+
+/// ゴミ箱の保持日数 (`TRASH_RETENTION_DAYS`, default 30)
+pub fn trash_retention_days() -> i32 {
+    std::env::var("TRASH_RETENTION_DAYS")
+        .ok()
+        .and_then(|days| days.parse().ok())
+        .filter(|days| *days >= 0)
+        .unwrap_or(30)
+}
+
+impl ExportRecord for Part {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "id",
+            "part_number",
+            "name",
+            "description",
+            "kind",
+            "status",
+            "version",
+            "created_at",
+            "created_by",
+            "updated_at",
+        ]
+    }
+
+    fn values(&self) -> Vec<ExportValue> {
+        vec![
+            self.id.to_string().into(),
+            self.part_number.clone().into(),
+            self.name.clone().into(),
+            self.description.clone().into(),
+            self.kind.clone().into(),
+            self.status.clone().into(),
+            self.version.into(),
+            self.created_at.map(|at| at.to_rfc3339()).into(),
+            self.created_by.map(|id| id.to_string()).into(),
+            self.updated_at.map(|at| at.to_rfc3339()).into(),
+        ]
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct NewPart {
+    /// 部品作成時に省略すると kind の採番ルールで自動採番する
+    #[serde(default)]
     #[validate(length(min = 1, message = "part_number must not be empty"))]
     pub part_number: String,
     #[validate(length(min = 1, message = "name must not be empty"))]
@@ -26,11 +104,544 @@ pub struct NewPart {
     pub kind: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PartSortColumn {
+    PartNumber,
+    Name,
+    Kind,
+    Status,
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl PartSortColumn {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            PartSortColumn::PartNumber => "part_number",
+            PartSortColumn::Name => "name",
+            PartSortColumn::Kind => "kind",
+            PartSortColumn::Status => "status",
+            PartSortColumn::CreatedAt => "created_at",
+            PartSortColumn::UpdatedAt => "updated_at",
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams, Default)]
+pub struct PartListQuery {
+    /// ソート列 (default created_at)
+    #[serde(default)]
+    pub sort: PartSortColumn,
+    /// asc (default) or desc
+    #[serde(default)]
+    pub order: SortOrder,
+    pub kind: Option<String>,
+    pub status: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+    pub updated_from: Option<DateTime<Utc>>,
+    pub updated_to: Option<DateTime<Utc>>,
+    /// 名前の前方一致
+    pub name_prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkMode {
+    /// 1 件でも失敗したらすべてロールバックする
+    #[default]
+    Atomic,
+    /// 成功した操作だけをコミットし、失敗した行を報告する
+    BestEffort,
+}
+
+/// 一括操作の 1 件。update / delete は `version` (ETag の値) が一致する場合のみ実行する
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BulkPartOperation {
+    Create {
+        part: NewPart,
+    },
+    Update {
+        id: Uuid,
+        version: i64,
+        part: NewPart,
+    },
+    Delete {
+        id: Uuid,
+        version: i64,
+    },
+}
+
+impl BulkPartOperation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BulkPartOperation::Create { .. } => "create",
+            BulkPartOperation::Update { .. } => "update",
+            BulkPartOperation::Delete { .. } => "delete",
+        }
+    }
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct BulkPartRequest {
+    #[serde(default)]
+    pub mode: BulkMode,
+    #[validate(length(
+        min = 1,
+        max = 5000,
+        message = "operations must contain between 1 and 5000 items"
+    ))]
+    pub operations: Vec<BulkPartOperation>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BulkItemResult {
+    pub index: usize,
+    pub op: String,
+    /// 操作ごとの HTTP ステータス相当のコード
+    pub status: u16,
+    pub id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part: Option<Part>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BulkPartResult {
+    pub mode: BulkMode,
+    /// 変更がコミットされたか (atomic で失敗があれば false)
+    pub committed: bool,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BulkItemResult>,
+}
+
+/// kind ごとの品番採番ルール
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub struct PartNumberScheme {
+    pub kind: String,
+    pub prefix: String,
+    pub padding: i32,
+    pub check_digit: bool,
+    pub next_value: i64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl PartNumberScheme {
+    pub const DEFAULT_KIND: &'static str = "default";
+
+    /// 連番をゼロ埋めし、必要ならチェックディジットを付けて品番にする
+    pub fn format(&self, value: i64) -> String {
+        let digits = format!("{:0width$}", value, width = self.padding.max(1) as usize);
+        if self.check_digit {
+            format!("{}{}{}", self.prefix, digits, luhn_check_digit(&digits))
+        } else {
+            format!("{}{}", self.prefix, digits)
+        }
+    }
+}
+
+/// Luhn (mod 10) 方式のチェックディジットを計算する
+pub fn luhn_check_digit(digits: &str) -> u32 {
+    let sum: u32 = digits
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, d)| {
+            if i % 2 == 0 {
+                let doubled = d * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                d
+            }
+        })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct UpdatePartNumberScheme {
+    pub prefix: String,
+    #[validate(range(min = 1, max = 18, message = "padding must be between 1 and 18"))]
+    pub padding: i32,
+    #[serde(default)]
+    pub check_digit: bool,
+    /// 次に払い出す連番 (省略時は現在値を維持)
+    #[validate(range(min = 1, message = "next_value must be 1 or greater"))]
+    pub next_value: Option<i64>,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct ReservePartNumbers {
+    pub kind: Option<String>,
+    /// 予約する件数 (default 1, max 100)
+    #[validate(range(min = 1, max = 100, message = "count must be between 1 and 100"))]
+    pub count: Option<i64>,
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub struct PartNumberReservation {
+    pub part_number: String,
+    pub kind: Option<String>,
+    pub reserved_by: Uuid,
+    pub reserved_at: Option<DateTime<Utc>>,
+    pub part_id: Option<Uuid>,
+    pub consumed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Validate, IntoParams)]
+pub struct PartSearchQuery {
+    /// 検索語 (品番・名前・説明)
+    #[validate(length(min = 1, message = "q must not be empty"))]
+    pub q: String,
+    /// 最大件数 (default 20, max 100)
+    #[validate(range(min = 1, max = 100, message = "limit must be between 1 and 100"))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SearchHighlight {
+    pub field: String,
+    pub snippet: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PartSearchHit {
+    #[serde(flatten)]
+    pub part: Part,
+    pub rank: f32,
+    pub highlights: Vec<SearchHighlight>,
+}
+
+const SNIPPET_CONTEXT: usize = 20;
+
+/// HTML として表示しても安全になるよう特殊文字をエスケープする
+fn escape_html(chars: &[char]) -> String {
+    let mut escaped = String::with_capacity(chars.len());
+    for c in chars {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(*c),
+        }
+    }
+    escaped
+}
+
+/// `text` 中の `q` (大文字小文字を区別しない) を `<mark>` で囲んだ前後の抜粋を返す。
+/// 抜粋は HTML として表示されるため、`<mark>` 以外の部分はエスケープする
+pub fn highlight_snippet(text: &str, q: &str) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let needle: Vec<char> = q.trim().chars().flat_map(char::to_lowercase).collect();
+    if needle.is_empty() {
+        return None;
+    }
+    let lowered: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    let start = lowered
+        .windows(needle.len())
+        .position(|window| window == needle.as_slice())?;
+    let end = start + needle.len();
+    let from = start.saturating_sub(SNIPPET_CONTEXT);
+    let to = (end + SNIPPET_CONTEXT).min(chars.len());
+
+    Some(format!(
+        "{}{}<mark>{}</mark>{}{}",
+        if from > 0 { "…" } else { "" },
+        escape_html(&chars[from..start]),
+        escape_html(&chars[start..end]),
+        escape_html(&chars[end..to]),
+        if to < chars.len() { "…" } else { "" },
+    ))
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub struct PartRevision {
+    pub id: Uuid,
+    pub part_id: Uuid,
+    pub revision: String,
+    pub part_number: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: Option<String>,
+    pub status: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub released_at: Option<DateTime<Utc>>,
+    pub released_by: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RevisionStatus {
+    Working,
+    Released,
+}
+
+impl RevisionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RevisionStatus::Working => "working",
+            RevisionStatus::Released => "released",
+        }
+    }
+}
+
+impl From<&str> for RevisionStatus {
+    fn from(s: &str) -> Self {
+        match s {
+            "released" => RevisionStatus::Released,
+            _ => RevisionStatus::Working,
+        }
+    }
+}
+
+/// リビジョン記号の採番方式 (`REVISION_SCHEME` で切り替え)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RevisionScheme {
+    /// A, B, ..., Z, AA, AB, ...
+    Alpha,
+    /// 01, 02, ..., 99, 100, ...
+    Numeric,
+}
+
+impl RevisionScheme {
+    pub fn from_env() -> Self {
+        match std::env::var("REVISION_SCHEME").as_deref() {
+            Ok("numeric") => RevisionScheme::Numeric,
+            _ => RevisionScheme::Alpha,
+        }
+    }
+
+    pub fn initial(&self) -> String {
+        match self {
+            RevisionScheme::Alpha => "A".to_string(),
+            RevisionScheme::Numeric => "01".to_string(),
+        }
+    }
+
+    pub fn next(&self, current: &str) -> String {
+        match self {
+            RevisionScheme::Alpha => next_alpha_revision(current),
+            RevisionScheme::Numeric => {
+                let n = current.parse::<u32>().unwrap_or(0) + 1;
+                format!("{:02}", n)
+            }
+        }
+    }
+}
+
+fn next_alpha_revision(current: &str) -> String {
+    let mut chars: Vec<char> = current.chars().collect();
+    for c in chars.iter_mut().rev() {
+        if *c == 'Z' {
+            *c = 'A';
+        } else if c.is_ascii_uppercase() {
+            *c = (*c as u8 + 1) as char;
+            return chars.into_iter().collect();
+        } else {
+            *c = 'A';
+            return chars.into_iter().collect();
+        }
+    }
+    // 全桁が繰り上がった場合は桁を増やす (Z -> AA)
+    std::iter::once('A').chain(chars).collect()
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RevisionDiff {
+    pub part_id: Uuid,
+    pub from: String,
+    pub to: String,
+    pub changes: Vec<FieldChange>,
+}
+
+impl RevisionDiff {
+    pub fn between(from: &PartRevision, to: &PartRevision) -> Self {
+        let fields = [
+            (
+                "part_number",
+                Some(from.part_number.clone()),
+                Some(to.part_number.clone()),
+            ),
+            ("name", Some(from.name.clone()), Some(to.name.clone())),
+            (
+                "description",
+                from.description.clone(),
+                to.description.clone(),
+            ),
+            ("kind", from.kind.clone(), to.kind.clone()),
+        ];
+
+        let changes = fields
+            .into_iter()
+            .filter(|(_, a, b)| a != b)
+            .map(|(field, a, b)| FieldChange {
+                field: field.to_string(),
+                from: a,
+                to: b,
+            })
+            .collect();
+
+        RevisionDiff {
+            part_id: to.part_id,
+            from: from.revision.clone(),
+            to: to.revision.clone(),
+            changes,
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct RevisionDiffQuery {
+    /// 比較元のリビジョン
+    pub from: String,
+    /// 比較先のリビジョン
+    pub to: String,
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub struct LifecycleState {
+    pub name: String,
+    pub label: String,
+    pub locked: bool,
+    pub releases_revision: bool,
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
+pub struct LifecycleTransition {
+    pub from_state: String,
+    pub to_state: String,
+    pub required_role: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Lifecycle {
+    pub states: Vec<LifecycleState>,
+    pub transitions: Vec<LifecycleTransition>,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct TransitionRequest {
+    #[validate(length(min = 1, message = "to must not be empty"))]
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WhereUsedMode {
+    /// 直接の親アセンブリのみ
+    #[default]
+    Single,
+    /// 最上位アセンブリまで遡る
+    Recursive,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct WhereUsedQuery {
+    /// single (default) or recursive
+    #[serde(default)]
+    pub mode: WhereUsedMode,
+}
+
+/// 構成上の親子リンク1件 (親部品の情報付き)
+#[derive(sqlx::FromRow, Serialize, ToSchema, Clone)]
+pub struct WhereUsedLink {
+    pub line_id: Uuid,
+    pub parent_id: Uuid,
+    pub child_id: Uuid,
+    pub parent_part_number: String,
+    pub parent_name: String,
+    pub quantity: f64,
+    pub unit_of_measure: String,
+    pub find_number: Option<i32>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct WhereUsedPath {
+    /// 対象部品に近い順の親リンク。最後の要素が最上位 (single モードでは直接の親)
+    pub links: Vec<WhereUsedLink>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct WhereUsed {
+    pub part_id: Uuid,
+    pub part_number: String,
+    pub name: String,
+    pub paths: Vec<WhereUsedPath>,
+}
+
+/// 親方向のリンク一覧から、対象部品から上位アセンブリへの経路を列挙する
+pub fn build_where_used_paths(
+    part_id: Uuid,
+    links: &[WhereUsedLink],
+    mode: WhereUsedMode,
+) -> Vec<WhereUsedPath> {
+    let mut by_child: HashMap<Uuid, Vec<&WhereUsedLink>> = HashMap::new();
+    for link in links {
+        by_child.entry(link.child_id).or_default().push(link);
+    }
+    for parents in by_child.values_mut() {
+        parents.sort_by(|a, b| a.parent_part_number.cmp(&b.parent_part_number));
+    }
+
+    let mut paths = Vec::new();
+    let mut current = Vec::new();
+    collect_paths(part_id, &by_child, mode, &mut current, &mut paths);
+    paths
+}
+
+fn collect_paths<'a>(
+    child_id: Uuid,
+    by_child: &HashMap<Uuid, Vec<&'a WhereUsedLink>>,
+    mode: WhereUsedMode,
+    current: &mut Vec<&'a WhereUsedLink>,
+    paths: &mut Vec<WhereUsedPath>,
+) {
+    for link in by_child.get(&child_id).into_iter().flatten() {
+        current.push(link);
+        let has_parents = by_child.contains_key(&link.parent_id);
+        if mode == WhereUsedMode::Recursive && has_parents {
+            collect_paths(link.parent_id, by_child, mode, current, paths);
+        } else {
+            paths.push(WhereUsedPath {
+                links: current.iter().map(|link| (*link).clone()).collect(),
+            });
+        }
+        current.pop();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use validator::Validate;
 
-    use super::NewPart;
+    use uuid::Uuid;
+
+    use super::{
+        NewPart, PartNumberScheme, RevisionScheme, WhereUsedLink, WhereUsedMode,
+        build_where_used_paths, highlight_snippet, luhn_check_digit,
+    };
 
     #[test]
     fn test_valid_new_part() {
@@ -64,4 +675,101 @@ mod tests {
         };
         assert!(new_part.validate().is_err())
     }
+
+    #[test]
+    fn test_next_alpha_revision() {
+        let scheme = RevisionScheme::Alpha;
+        assert_eq!(scheme.initial(), "A");
+        assert_eq!(scheme.next("A"), "B");
+        assert_eq!(scheme.next("Z"), "AA");
+        assert_eq!(scheme.next("AZ"), "BA");
+    }
+
+    #[test]
+    fn test_next_numeric_revision() {
+        let scheme = RevisionScheme::Numeric;
+        assert_eq!(scheme.initial(), "01");
+        assert_eq!(scheme.next("01"), "02");
+        assert_eq!(scheme.next("09"), "10");
+        assert_eq!(scheme.next("99"), "100");
+    }
+
+    #[test]
+    fn test_where_used_paths() {
+        let link = |parent_id: Uuid, child_id: Uuid, parent_part_number: &str| WhereUsedLink {
+            line_id: Uuid::new_v4(),
+            parent_id,
+            child_id,
+            parent_part_number: parent_part_number.to_string(),
+            parent_name: parent_part_number.to_string(),
+            quantity: 1.0,
+            unit_of_measure: "EA".to_string(),
+            find_number: None,
+        };
+        let (bolt, sub_a, sub_b, top) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let links = vec![
+            link(sub_a, bolt, "SUB-A"),
+            link(sub_b, bolt, "SUB-B"),
+            link(top, sub_a, "TOP"),
+        ];
+
+        let single = build_where_used_paths(bolt, &links, WhereUsedMode::Single);
+        assert_eq!(single.len(), 2);
+        assert!(single.iter().all(|path| path.links.len() == 1));
+
+        let recursive = build_where_used_paths(bolt, &links, WhereUsedMode::Recursive);
+        assert_eq!(recursive.len(), 2);
+        assert_eq!(recursive[0].links.len(), 2);
+        assert_eq!(recursive[0].links[1].parent_id, top);
+        assert_eq!(recursive[1].links[0].parent_id, sub_b);
+    }
+
+    #[test]
+    fn test_highlight_snippet() {
+        assert_eq!(
+            highlight_snippet("大型用ボルト M8", "ボルト").as_deref(),
+            Some("大型用<mark>ボルト</mark> M8")
+        );
+        assert_eq!(
+            highlight_snippet("XYZ-789", "xyz").as_deref(),
+            Some("<mark>XYZ</mark>-789")
+        );
+        assert!(highlight_snippet("ナット", "ボルト").is_none());
+        assert_eq!(
+            highlight_snippet("<script>alert('bolt')</script>", "bolt").as_deref(),
+            Some("&lt;script&gt;alert(&#39;<mark>bolt</mark>&#39;)&lt;/script&gt;")
+        );
+        assert_eq!(
+            highlight_snippet("\"bolt\" & nut", "BOLT").as_deref(),
+            Some("&quot;<mark>bolt</mark>&quot; &amp; nut")
+        );
+        assert_eq!(
+            highlight_snippet("a<b>c", "<b>").as_deref(),
+            Some("a<mark>&lt;b&gt;</mark>c")
+        );
+    }
+
+    #[test]
+    fn test_part_number_scheme_format() {
+        let mut scheme = PartNumberScheme {
+            kind: "部品".to_string(),
+            prefix: "BLT-".to_string(),
+            padding: 5,
+            check_digit: false,
+            next_value: 1,
+            updated_at: None,
+        };
+        assert_eq!(scheme.format(42), "BLT-00042");
+
+        scheme.check_digit = true;
+        assert_eq!(luhn_check_digit("7992739871"), 3);
+        assert_eq!(luhn_check_digit("12345"), 5);
+        assert_eq!(scheme.format(42), "BLT-000422");
+        assert_eq!(scheme.format(12345), "BLT-123455");
+    }
 }
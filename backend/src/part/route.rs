@@ -1,53 +1,121 @@
 use crate::auth::domain::Claims;
 use crate::errors::app_error::AppError;
+use crate::errors::conflict::ConflictErrorResponse;
 use crate::errors::validation::ValidationErrorResponse;
-use crate::part::domain::{NewPart, Part};
+use crate::part::domain::{
+    BulkPartRequest, BulkPartResult, Lifecycle, NewPart, Part, PartListQuery,
+    PartNumberReservation, PartNumberScheme, PartRevision, PartSearchHit, PartSearchQuery,
+    PurgeResult, ReservePartNumbers, RevisionDiff, RevisionDiffQuery, TransitionRequest,
+    TrashedPart, UpdatePartNumberScheme, WhereUsed, WhereUsedQuery,
+};
 use crate::part::service::{
-    create_part as service_create_part, delete_part as service_delete_part,
-    get_part as service_get_part, get_parts as service_get_parts,
+    bulk_parts as service_bulk_parts, create_part as service_create_part,
+    delete_part as service_delete_part, diff_revisions as service_diff_revisions,
+    export_parts as service_export_parts, get_lifecycle as service_get_lifecycle,
+    get_part as service_get_part, get_part_number_schemes as service_get_part_number_schemes,
+    get_parts as service_get_parts, get_revision as service_get_revision,
+    get_trash as service_get_trash, get_where_used as service_get_where_used,
+    list_revisions as service_list_revisions, patch_part as service_patch_part,
+    purge_trash as service_purge_trash, release_revision as service_release_revision,
+    reserve_part_numbers as service_reserve_part_numbers, restore_part as service_restore_part,
+    search_parts as service_search_parts, transition_part as service_transition_part,
     update_part as service_update_part,
+    update_part_number_scheme as service_update_part_number_scheme,
 };
+use crate::patch::domain::{PatchDocument, PatchOperation};
 use crate::responses::error::ErrorResponse;
+use crate::responses::etag::{ETag, IfMatch};
+use crate::responses::export::{ExportQuery, export_response};
+use crate::responses::pagination::{PageQuery, Pagination};
 use crate::responses::success::SuccessResponse;
 // use crate::services::part_service::PartService;
 
 use axum::Extension;
-use axum::{Json, extract::Path, extract::State};
+use axum::response::Response;
+use axum::{Json, extract::Path, extract::Query, extract::RawQuery, extract::State};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 // #[axum::debug_handler]
 #[utoipa::path(post, path = "/parts", request_body = NewPart, responses(
-    (status = 201, description = "Part created successfully", body = SuccessResponse<Part>),
+    (status = 201, description = "Part created successfully", body = SuccessResponse<Part>, headers(("ETag" = String, description = "Current version of the part"))),
     (status = 400, description = "Validation error", body = ValidationErrorResponse),
     (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 409, description = "Part number already exists or is reserved", body = ConflictErrorResponse),
     (status = 500, description = "Database error", body = ErrorResponse),
 ), tags = ["parts"], security(("bearerAuth" = [])))]
 pub async fn create_part(
     Extension(claims): Extension<Claims>,
     State(pool): State<PgPool>,
     Json(new_part): Json<NewPart>,
-) -> Result<Json<SuccessResponse<Part>>, AppError> {
+) -> Result<(ETag, Json<SuccessResponse<Part>>), AppError> {
     let part = service_create_part(claims, &pool, new_part).await?;
-    Ok(Json(SuccessResponse::created(part)))
+    Ok((ETag::of(&part), Json(SuccessResponse::created(part))))
 }
 
 // #[axum::debug_handler]
-#[utoipa::path(get, path = "/parts", responses(
-    (status = 200, description = "Fetched parts successfully", body = SuccessResponse<Vec<Part>>),
+#[utoipa::path(get, path = "/parts", params(PageQuery, PartListQuery), responses(
+    (status = 200, description = "Fetched parts successfully (paginated)", body = SuccessResponse<Vec<Part>>),
+    (status = 400, description = "Invalid query parameters"),
     (status = 401, description = "Unauthorized error", body = ErrorResponse),
     (status = 500, description = "Database error", body = ErrorResponse),
 ), tags = ["parts"], security(("bearerAuth" = [])))]
 pub async fn get_parts(
     State(pool): State<PgPool>,
+    Query(page): Query<PageQuery>,
+    Query(filter): Query<PartListQuery>,
+    RawQuery(raw_query): RawQuery,
 ) -> Result<Json<SuccessResponse<Vec<Part>>>, AppError> {
-    let parts = service_get_parts(&pool).await?;
-    Ok(Json(SuccessResponse::ok(parts)))
+    let (parts, total) = service_get_parts(&pool, &filter, &page).await?;
+    let pagination = Pagination::new(&page, total, "/parts", raw_query.as_deref());
+    Ok(Json(SuccessResponse::paginated(parts, pagination)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/parts/export", params(ExportQuery, PartListQuery), responses(
+    (status = 200, description = "All parts matching the list filters, streamed as a file", content(
+        (String = "text/csv"),
+        (Vec<u8> = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+        (Vec<Part> = "application/json"),
+    )),
+    (status = 400, description = "Invalid query parameters"),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn export_parts(
+    State(pool): State<PgPool>,
+    Query(export): Query<ExportQuery>,
+    Query(filter): Query<PartListQuery>,
+) -> Result<Response, AppError> {
+    export_response(export.format, "parts", move |sender| {
+        service_export_parts(pool, filter, sender)
+    })
+    .await
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/parts/bulk", request_body = BulkPartRequest, responses(
+    (status = 200, description = "Operations processed. The body `code` is 200 when all succeeded and 207 when some failed (see `committed` and per-item results)", body = SuccessResponse<BulkPartResult>),
+    (status = 400, description = "Validation error", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn bulk_parts(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Json(request): Json<BulkPartRequest>,
+) -> Result<Json<SuccessResponse<BulkPartResult>>, AppError> {
+    let result = service_bulk_parts(claims, &pool, request).await?;
+    if result.failed == 0 {
+        Ok(Json(SuccessResponse::ok(result)))
+    } else {
+        Ok(Json(SuccessResponse::multi_status(result)))
+    }
 }
 
 // #[axum::debug_handler]
 #[utoipa::path(get, path = "/parts/{id}", params(("id" = Uuid, Path, description = "Part ID to fetch")),  responses(
-    (status = 200, description = "Fetched part successfully", body = SuccessResponse<Part>),
+    (status = 200, description = "Fetched part successfully", body = SuccessResponse<Part>, headers(("ETag" = String, description = "Current version of the part"))),
     (status = 401, description = "Unauthorized error", body = ErrorResponse),
     (status = 404, description = "NotFound error", body = ErrorResponse),
     (status = 500, description = "Database error", body = ErrorResponse),
@@ -55,41 +123,301 @@ pub async fn get_parts(
 pub async fn get_part(
     State(pool): State<PgPool>,
     Path(id): Path<Uuid>,
-) -> Result<Json<SuccessResponse<Part>>, AppError> {
+) -> Result<(ETag, Json<SuccessResponse<Part>>), AppError> {
     let part = service_get_part(&pool, id).await?;
-    Ok(Json(SuccessResponse::ok(part)))
+    Ok((ETag::of(&part), Json(SuccessResponse::ok(part))))
 }
 
 // #[axum::debug_handler]
-#[utoipa::path(put, path = "/parts/{id}", params(("id" = Uuid, Path, description = "Part ID to update")) , request_body = NewPart, responses(
-    (status = 200, description = "Part updated successfully", body = SuccessResponse<Part>),
+#[utoipa::path(put, path = "/parts/{id}", params(("id" = Uuid, Path, description = "Part ID to update"), ("If-Match" = String, Header, description = "ETag of the part being edited")) , request_body = NewPart, responses(
+    (status = 200, description = "Part updated successfully (working revision created or edited)", body = SuccessResponse<Part>, headers(("ETag" = String, description = "New version of the part"))),
     (status = 400, description = "Validation error", body = ValidationErrorResponse),
-    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 401, description = "Unauthorized error (not the owner, or the part is in a locked lifecycle state)", body = ErrorResponse),
     (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 409, description = "Part number already exists or is reserved", body = ConflictErrorResponse),
+    (status = 412, description = "Part has been modified since the given ETag", body = ErrorResponse),
+    (status = 428, description = "If-Match header is missing", body = ErrorResponse),
     (status = 500, description = "Database error", body = ErrorResponse),
 ), tags = ["parts"], security(("bearerAuth" = [])))]
 pub async fn update_part(
     Extension(claims): Extension<Claims>,
     State(pool): State<PgPool>,
     Path(id): Path<Uuid>,
+    if_match: IfMatch,
     Json(updated_part): Json<NewPart>,
-) -> Result<Json<SuccessResponse<Part>>, AppError> {
-    let part = service_update_part(claims, &pool, id, updated_part).await?;
-    Ok(Json(SuccessResponse::ok(part)))
+) -> Result<(ETag, Json<SuccessResponse<Part>>), AppError> {
+    let part = service_update_part(claims, &pool, id, updated_part, if_match).await?;
+    Ok((ETag::of(&part), Json(SuccessResponse::ok(part))))
 }
 
 // #[axum::debug_handler]
-#[utoipa::path(delete, path = "/parts/{id}", params(("id" = Uuid, Path, description = "Part ID to delete")) , responses(
+#[utoipa::path(patch, path = "/parts/{id}", params(("id" = Uuid, Path, description = "Part ID to patch"), ("If-Match" = String, Header, description = "ETag of the part being edited")),
+    request_body(description = "JSON Merge Patch (RFC 7396; null removes a field) or JSON Patch (RFC 6902)", content(
+        (Object = "application/merge-patch+json"),
+        (Vec<PatchOperation> = "application/json-patch+json"),
+    )),
+    responses(
+    (status = 200, description = "Part patched successfully (working revision created or edited)", body = SuccessResponse<Part>, headers(("ETag" = String, description = "New version of the part"))),
+    (status = 400, description = "Validation error (invalid patch or patched part)", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error (not the owner, or the part is in a locked lifecycle state)", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 409, description = "Test operation failed, or part number already exists", body = ConflictErrorResponse),
+    (status = 412, description = "Part has been modified since the given ETag", body = ErrorResponse),
+    (status = 415, description = "Unsupported Content-Type", body = ErrorResponse),
+    (status = 428, description = "If-Match header is missing", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn patch_part(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    if_match: IfMatch,
+    patch: PatchDocument,
+) -> Result<(ETag, Json<SuccessResponse<Part>>), AppError> {
+    let part = service_patch_part(claims, &pool, id, patch, if_match).await?;
+    Ok((ETag::of(&part), Json(SuccessResponse::ok(part))))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(delete, path = "/parts/{id}", params(("id" = Uuid, Path, description = "Part ID to delete"), ("If-Match" = String, Header, description = "ETag of the part being deleted")) , responses(
     (status = 204, description = "Part deleted successfully"),
-    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 401, description = "Unauthorized error (not the owner, or the part is in a locked lifecycle state)", body = ErrorResponse),
     (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 409, description = "Part is still used in the BOM of another part (`existing_id` is the parent part)", body = ConflictErrorResponse),
+    (status = 412, description = "Part has been modified since the given ETag", body = ErrorResponse),
+    (status = 428, description = "If-Match header is missing", body = ErrorResponse),
     (status = 500, description = "Database error", body = ErrorResponse),
 ), tags = ["parts"], security(("bearerAuth" = [])))]
 pub async fn delete_part(
     Extension(claims): Extension<Claims>,
     State(pool): State<PgPool>,
     Path(id): Path<Uuid>,
+    if_match: IfMatch,
 ) -> Result<Json<SuccessResponse<()>>, AppError> {
-    service_delete_part(claims, &pool, id).await?;
+    service_delete_part(claims, &pool, id, if_match).await?;
     Ok(Json(SuccessResponse::no_content()))
 }
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/trash", params(PageQuery), responses(
+    (status = 200, description = "Fetched trashed parts successfully (paginated)", body = SuccessResponse<Vec<TrashedPart>>),
+    (status = 400, description = "Invalid query parameters"),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn get_trash(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Query(page): Query<PageQuery>,
+    RawQuery(raw_query): RawQuery,
+) -> Result<Json<SuccessResponse<Vec<TrashedPart>>>, AppError> {
+    let (parts, total) = service_get_trash(claims, &pool, &page).await?;
+    let pagination = Pagination::new(&page, total, "/trash", raw_query.as_deref());
+    Ok(Json(SuccessResponse::paginated(parts, pagination)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/parts/{id}/restore", params(("id" = Uuid, Path, description = "Part ID to restore")), responses(
+    (status = 200, description = "Part restored successfully", body = SuccessResponse<Part>, headers(("ETag" = String, description = "Current version of the part"))),
+    (status = 401, description = "Unauthorized error (not the owner)", body = ErrorResponse),
+    (status = 404, description = "Part not found in trash", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn restore_part(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<(ETag, Json<SuccessResponse<Part>>), AppError> {
+    let part = service_restore_part(claims, &pool, id).await?;
+    Ok((ETag::of(&part), Json(SuccessResponse::ok(part))))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/trash/purge", responses(
+    (status = 200, description = "Purged parts past the retention period", body = SuccessResponse<PurgeResult>),
+    (status = 401, description = "Unauthorized error (admin only)", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn purge_trash(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+) -> Result<Json<SuccessResponse<PurgeResult>>, AppError> {
+    let result = service_purge_trash(claims, &pool).await?;
+    Ok(Json(SuccessResponse::ok(result)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/parts/{id}/revisions", params(("id" = Uuid, Path, description = "Part ID")), responses(
+    (status = 200, description = "Fetched revisions successfully", body = SuccessResponse<Vec<PartRevision>>),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn list_revisions(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SuccessResponse<Vec<PartRevision>>>, AppError> {
+    let revisions = service_list_revisions(&pool, id).await?;
+    Ok(Json(SuccessResponse::ok(revisions)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/parts/{id}/revisions/{revision}", params(
+    ("id" = Uuid, Path, description = "Part ID"),
+    ("revision" = String, Path, description = "Revision to fetch"),
+), responses(
+    (status = 200, description = "Fetched revision successfully", body = SuccessResponse<PartRevision>),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn get_revision(
+    State(pool): State<PgPool>,
+    Path((id, revision)): Path<(Uuid, String)>,
+) -> Result<Json<SuccessResponse<PartRevision>>, AppError> {
+    let revision = service_get_revision(&pool, id, &revision).await?;
+    Ok(Json(SuccessResponse::ok(revision)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/parts/{id}/revisions/diff", params(("id" = Uuid, Path, description = "Part ID"), RevisionDiffQuery), responses(
+    (status = 200, description = "Compared revisions successfully", body = SuccessResponse<RevisionDiff>),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn diff_revisions(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<RevisionDiffQuery>,
+) -> Result<Json<SuccessResponse<RevisionDiff>>, AppError> {
+    let diff = service_diff_revisions(&pool, id, &query.from, &query.to).await?;
+    Ok(Json(SuccessResponse::ok(diff)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/parts/{id}/revisions/{revision}/release", params(
+    ("id" = Uuid, Path, description = "Part ID"),
+    ("revision" = String, Path, description = "Working revision to release"),
+), responses(
+    (status = 200, description = "Revision released successfully", body = SuccessResponse<PartRevision>),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn release_revision(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path((id, revision)): Path<(Uuid, String)>,
+) -> Result<Json<SuccessResponse<PartRevision>>, AppError> {
+    let revision = service_release_revision(claims, &pool, id, &revision).await?;
+    Ok(Json(SuccessResponse::ok(revision)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/parts/{id}/where-used", params(("id" = Uuid, Path, description = "Part ID"), WhereUsedQuery), responses(
+    (status = 200, description = "Fetched where-used successfully", body = SuccessResponse<WhereUsed>),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn get_where_used(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<WhereUsedQuery>,
+) -> Result<Json<SuccessResponse<WhereUsed>>, AppError> {
+    let where_used = service_get_where_used(&pool, id, query.mode).await?;
+    Ok(Json(SuccessResponse::ok(where_used)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/lifecycle", responses(
+    (status = 200, description = "Fetched lifecycle successfully", body = SuccessResponse<Lifecycle>),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn get_lifecycle(
+    State(pool): State<PgPool>,
+) -> Result<Json<SuccessResponse<Lifecycle>>, AppError> {
+    let lifecycle = service_get_lifecycle(&pool).await?;
+    Ok(Json(SuccessResponse::ok(lifecycle)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/parts/{id}/transition", params(("id" = Uuid, Path, description = "Part ID to transition")), request_body = TransitionRequest, responses(
+    (status = 200, description = "Part transitioned successfully", body = SuccessResponse<Part>),
+    (status = 400, description = "Validation error (transition not allowed)", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 404, description = "NotFound error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn transition_part(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<TransitionRequest>,
+) -> Result<Json<SuccessResponse<Part>>, AppError> {
+    let part = service_transition_part(claims, &pool, id, request).await?;
+    Ok(Json(SuccessResponse::ok(part)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/parts/search", params(PartSearchQuery), responses(
+    (status = 200, description = "Ranked search results with highlighted snippets", body = SuccessResponse<Vec<PartSearchHit>>),
+    (status = 400, description = "Validation error", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn search_parts(
+    State(pool): State<PgPool>,
+    Query(query): Query<PartSearchQuery>,
+) -> Result<Json<SuccessResponse<Vec<PartSearchHit>>>, AppError> {
+    let hits = service_search_parts(&pool, query).await?;
+    Ok(Json(SuccessResponse::ok(hits)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(get, path = "/part-numbers/schemes", responses(
+    (status = 200, description = "Fetched part number schemes successfully", body = SuccessResponse<Vec<PartNumberScheme>>),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn get_part_number_schemes(
+    State(pool): State<PgPool>,
+) -> Result<Json<SuccessResponse<Vec<PartNumberScheme>>>, AppError> {
+    let schemes = service_get_part_number_schemes(&pool).await?;
+    Ok(Json(SuccessResponse::ok(schemes)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(put, path = "/part-numbers/schemes/{kind}", params(("kind" = String, Path, description = "Part kind, or 'default'")), request_body = UpdatePartNumberScheme, responses(
+    (status = 200, description = "Part number scheme updated successfully", body = SuccessResponse<PartNumberScheme>),
+    (status = 400, description = "Validation error", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn update_part_number_scheme(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Path(kind): Path<String>,
+    Json(scheme): Json<UpdatePartNumberScheme>,
+) -> Result<Json<SuccessResponse<PartNumberScheme>>, AppError> {
+    let scheme = service_update_part_number_scheme(claims, &pool, &kind, scheme).await?;
+    Ok(Json(SuccessResponse::ok(scheme)))
+}
+
+// #[axum::debug_handler]
+#[utoipa::path(post, path = "/part-numbers/reserve", request_body = ReservePartNumbers, responses(
+    (status = 201, description = "Part numbers reserved successfully", body = SuccessResponse<Vec<PartNumberReservation>>),
+    (status = 400, description = "Validation error", body = ValidationErrorResponse),
+    (status = 401, description = "Unauthorized error", body = ErrorResponse),
+    (status = 500, description = "Database error", body = ErrorResponse),
+), tags = ["parts"], security(("bearerAuth" = [])))]
+pub async fn reserve_part_numbers(
+    Extension(claims): Extension<Claims>,
+    State(pool): State<PgPool>,
+    Json(request): Json<ReservePartNumbers>,
+) -> Result<Json<SuccessResponse<Vec<PartNumberReservation>>>, AppError> {
+    let reservations = service_reserve_part_numbers(claims, &pool, request).await?;
+    Ok(Json(SuccessResponse::created(reservations)))
+}
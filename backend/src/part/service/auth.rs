@@ -1,4 +1,4 @@
-use sqlx::PgPool;
+use sqlx::PgExecutor;
 use tracing::error;
 use uuid::Uuid;
 
@@ -7,14 +7,22 @@ use crate::{
     errors::app_error::AppError,
 };
 
-pub async fn ensure_part_owner(claims: Claims, pool: &PgPool, id: Uuid) -> Result<(), AppError> {
-    let part_owner = sqlx::query_scalar!("SELECT created_by FROM parts WHERE ID = $1", id)
-        .fetch_one(pool)
-        .await
-        .map_err(|e| {
-            error!("DB error during ownership check: {}", e);
-            AppError::DatabaseError("Ownership check failed".into())
-        })?;
+pub async fn ensure_part_owner(
+    claims: Claims,
+    executor: impl PgExecutor<'_>,
+    id: Uuid,
+) -> Result<(), AppError> {
+    let part_owner = sqlx::query_scalar!(
+        "SELECT created_by FROM parts WHERE id = $1 AND deleted_at IS NULL",
+        id
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(|e| {
+        error!("DB error during ownership check: {}", e);
+        AppError::DatabaseError("Ownership check failed".into())
+    })?
+    .flatten();
 
     let user_id = Uuid::parse_str(&claims.sub)
         .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
@@ -33,12 +41,28 @@ pub async fn ensure_part_owner(claims: Claims, pool: &PgPool, id: Uuid) -> Resul
 
 pub async fn ensure_admin_or_owner(
     claims: Claims,
-    pool: &PgPool,
+    executor: impl PgExecutor<'_>,
     id: Uuid,
 ) -> Result<(), AppError> {
-    if claims.role == Role::Admin {
+    if claims.role != Role::Admin {
+        return ensure_part_owner(claims, executor, id).await;
+    }
+
+    // 管理者でもゴミ箱の部品は変更できない
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM parts WHERE id = $1 AND deleted_at IS NULL) AS "exists!""#,
+        id
+    )
+    .fetch_one(executor)
+    .await
+    .map_err(|e| {
+        error!("DB error during ownership check: {}", e);
+        AppError::DatabaseError("Ownership check failed".into())
+    })?;
+
+    if exists {
         Ok(())
     } else {
-        ensure_part_owner(claims, pool, id).await
+        Err(AppError::NotFound(format!("Part not found: {}", id)))
     }
 }
@@ -0,0 +1,164 @@
+use crate::auth::domain::Claims;
+use crate::errors::app_error::AppError;
+use crate::errors::validation::{FieldError, extract_validation_errors};
+use crate::part::domain::{
+    BulkItemResult, BulkMode, BulkPartOperation, BulkPartRequest, BulkPartResult, Part,
+};
+use crate::responses::etag::IfMatch;
+
+use axum::http::StatusCode;
+use sqlx::{Connection, PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::create::insert_part;
+use super::delete::remove_part;
+use super::update::apply_part_update;
+
+/// 部品の作成・更新・削除を 1 つのトランザクションで実行する。
+/// 各操作はセーブポイント内で実行し、失敗した操作だけを取り消して残りの検証を続ける。
+pub async fn bulk_parts(
+    claims: Claims,
+    pool: &PgPool,
+    request: BulkPartRequest,
+) -> Result<BulkPartResult, AppError> {
+    request
+        .validate()
+        .map_err(|e| AppError::ValidationError(extract_validation_errors(e)))?;
+
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to run bulk operation".to_string())
+    })?;
+
+    let mut results = Vec::with_capacity(request.operations.len());
+    for (index, operation) in request.operations.into_iter().enumerate() {
+        let op = operation.name();
+        let target = match &operation {
+            BulkPartOperation::Create { .. } => None,
+            BulkPartOperation::Update { id, .. } | BulkPartOperation::Delete { id, .. } => {
+                Some(*id)
+            }
+        };
+
+        let mut savepoint = tx.begin().await.map_err(|e| {
+            error!("DB error during creating savepoint: {}", e);
+            AppError::DatabaseError("Failed to run bulk operation".to_string())
+        })?;
+
+        match run_operation(&mut savepoint, &claims, user_id, operation).await {
+            Ok((status, part)) => {
+                savepoint.commit().await.map_err(|e| {
+                    error!("DB error during releasing savepoint: {}", e);
+                    AppError::DatabaseError("Failed to run bulk operation".to_string())
+                })?;
+                results.push(BulkItemResult {
+                    index,
+                    op: op.to_string(),
+                    status: status.as_u16(),
+                    id: part.as_ref().map(|part| part.id).or(target),
+                    part,
+                    message: None,
+                    errors: Vec::new(),
+                });
+            }
+            Err(e) => {
+                savepoint.rollback().await.map_err(|e| {
+                    error!("DB error during rolling back savepoint: {}", e);
+                    AppError::DatabaseError("Failed to run bulk operation".to_string())
+                })?;
+                results.push(failed_item(index, op, target, e));
+            }
+        }
+    }
+
+    let failed = results
+        .iter()
+        .filter(|result| !StatusCode::from_u16(result.status).is_ok_and(|s| s.is_success()))
+        .count();
+    let succeeded = results.len() - failed;
+    let committed = failed == 0 || request.mode == BulkMode::BestEffort;
+
+    if committed {
+        tx.commit().await.map_err(|e| {
+            error!("DB error during committing bulk operation: {}", e);
+            AppError::DatabaseError("Failed to run bulk operation".to_string())
+        })?;
+    } else {
+        tx.rollback().await.map_err(|e| {
+            error!("DB error during rolling back bulk operation: {}", e);
+            AppError::DatabaseError("Failed to run bulk operation".to_string())
+        })?;
+    }
+
+    info!(
+        "Bulk part operation ({:?}): {} succeeded, {} failed, committed: {}",
+        request.mode, succeeded, failed, committed
+    );
+    Ok(BulkPartResult {
+        mode: request.mode,
+        committed,
+        succeeded,
+        failed,
+        results,
+    })
+}
+
+async fn run_operation(
+    conn: &mut PgConnection,
+    claims: &Claims,
+    user_id: Uuid,
+    operation: BulkPartOperation,
+) -> Result<(StatusCode, Option<Part>), AppError> {
+    match operation {
+        BulkPartOperation::Create { part } => {
+            let part = insert_part(conn, user_id, part).await?;
+            Ok((StatusCode::CREATED, Some(part)))
+        }
+        BulkPartOperation::Update { id, version, part } => {
+            let part =
+                apply_part_update(conn, claims, id, part, IfMatch::for_version(version)).await?;
+            Ok((StatusCode::OK, Some(part)))
+        }
+        BulkPartOperation::Delete { id, version } => {
+            remove_part(conn, claims, id, IfMatch::for_version(version)).await?;
+            Ok((StatusCode::NO_CONTENT, None))
+        }
+    }
+}
+
+fn failed_item(index: usize, op: &str, id: Option<Uuid>, e: AppError) -> BulkItemResult {
+    let status = e.status_code().as_u16();
+    let (message, errors) = match e {
+        AppError::ValidationError(response) => (None, response.errors),
+        AppError::Conflict(conflict) => (
+            Some(conflict.error.message.clone()),
+            vec![FieldError {
+                field: conflict.error.field,
+                message: conflict.error.message,
+            }],
+        ),
+        AppError::NotFound(message)
+        | AppError::DatabaseError(message)
+        | AppError::InternalError(message)
+        | AppError::Unauthorized(message)
+        | AppError::PreconditionFailed(message)
+        | AppError::PreconditionRequired(message)
+        | AppError::UnsupportedMediaType(message)
+        | AppError::TooManyRequests { message, .. } => (Some(message), Vec::new()),
+    };
+
+    BulkItemResult {
+        index,
+        op: op.to_string(),
+        status,
+        id,
+        part: None,
+        message,
+        errors,
+    }
+}
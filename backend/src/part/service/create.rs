@@ -1,30 +1,71 @@
+use crate::audit::domain::{AuditAction, AuditEntity};
+use crate::audit::service::record_audit;
 use crate::auth::domain::Claims;
 use crate::errors::app_error::AppError;
+use crate::errors::conflict::map_unique_violation;
 use crate::errors::validation::extract_validation_errors;
-use crate::part::domain::{NewPart, Part};
+use crate::part::domain::{NewPart, Part, RevisionScheme};
 
-use sqlx::PgPool;
+use sqlx::{Connection, PgConnection, PgPool};
 use tracing::{error, info};
 use uuid::Uuid;
 use validator::Validate;
 
+use super::numbering::{consume_reservation, ensure_part_number_available, next_part_numbers};
+use super::revision::insert_revision;
+
+/// 部品を作成する。品番が省略された場合は kind の採番ルールで自動採番する。
 pub async fn create_part(
     claims: Claims,
     pool: &PgPool,
     new_part: NewPart,
 ) -> Result<Part, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("DB insert failed".to_string())
+    })?;
+
+    let part = insert_part(&mut tx, user_id, new_part).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing part insertion: {}", e);
+        AppError::DatabaseError("DB insert failed".to_string())
+    })?;
+
+    info!("Part created successfully: {}", part.id);
+    Ok(part)
+}
+
+/// トランザクション内で部品と初期リビジョンを作成する
+pub async fn insert_part(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    mut new_part: NewPart,
+) -> Result<Part, AppError> {
+    if new_part.part_number.trim().is_empty() {
+        let mut numbers = next_part_numbers(conn, new_part.kind.as_deref(), 1).await?;
+        new_part.part_number = numbers.remove(0);
+    }
+
     new_part
         .validate()
         .map_err(|e| AppError::ValidationError(extract_validation_errors(e)))?;
 
-    let user_id = Uuid::parse_str(&claims.sub)
-        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+    ensure_part_number_available(conn, &new_part.part_number, None, user_id).await?;
 
+    // 一意制約違反のあとで既存の部品を引けるよう、セーブポイント内で実行する
+    let mut savepoint = conn.begin().await.map_err(|e| {
+        error!("DB error during creating savepoint: {}", e);
+        AppError::DatabaseError("DB insert failed".to_string())
+    })?;
     let part = sqlx::query_as!(
         Part,
         r#"INSERT INTO parts (id, part_number, name, description, kind, created_by)
            VALUES ($1, $2, $3, $4, $5, $6)
-           RETURNING id, part_number, name, description, kind, created_at, created_by, updated_at"#,
+           RETURNING id, part_number, name, description, kind, status, created_at, created_by, updated_at, version"#,
         Uuid::new_v4(),
         new_part.part_number,
         new_part.name,
@@ -32,13 +73,44 @@ pub async fn create_part(
         new_part.kind,
         user_id
     )
-    .fetch_one(pool)
-    .await
-    .map_err(|e| {
-        error!("DB error during part insertion: {}", e);
-        AppError::DatabaseError("DB insert failed".to_string())
-    })?;
+    .fetch_one(&mut *savepoint)
+    .await;
+
+    let part = match part {
+        Ok(part) => {
+            savepoint.commit().await.map_err(|e| {
+                error!("DB error during releasing savepoint: {}", e);
+                AppError::DatabaseError("DB insert failed".to_string())
+            })?;
+            part
+        }
+        Err(e) => {
+            savepoint.rollback().await.map_err(|e| {
+                error!("DB error during rolling back savepoint: {}", e);
+                AppError::DatabaseError("DB insert failed".to_string())
+            })?;
+            return Err(map_unique_violation(conn, e, |e| {
+                error!("DB error during part insertion: {}", e);
+                AppError::DatabaseError("DB insert failed".to_string())
+            })
+            .await);
+        }
+    };
+
+    consume_reservation(conn, &part.part_number, part.id).await?;
+
+    let initial = RevisionScheme::from_env().initial();
+    insert_revision(conn, part.id, &initial, &new_part, user_id).await?;
+
+    record_audit(
+        conn,
+        AuditEntity::Part,
+        part.id,
+        AuditAction::Create,
+        Some(user_id),
+        None,
+    )
+    .await?;
 
-    info!("Part created successfully: {}", part.id);
     Ok(part)
 }
@@ -1,21 +1,69 @@
-use crate::{auth::domain::Claims, errors::app_error::AppError};
+use crate::{
+    audit::domain::{AuditAction, AuditEntity},
+    audit::service::{record_audit, snapshot},
+    auth::domain::Claims,
+    errors::{app_error::AppError, conflict::conflict_error},
+    responses::etag::{IfMatch, lock_and_check_version},
+};
 
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use tracing::{error, info};
 use uuid::Uuid;
 
 use super::auth::ensure_admin_or_owner;
+use super::lifecycle::ensure_not_locked;
 
-pub async fn delete_part(claims: Claims, pool: &PgPool, id: Uuid) -> Result<(), AppError> {
-    ensure_admin_or_owner(claims, pool, id).await?;
+pub async fn delete_part(
+    claims: Claims,
+    pool: &PgPool,
+    id: Uuid,
+    if_match: IfMatch,
+) -> Result<(), AppError> {
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to delete part".to_string())
+    })?;
 
-    let result = sqlx::query!(r#"DELETE FROM parts WHERE id = $1"#, id)
-        .execute(pool)
-        .await
-        .map_err(|e| {
-            error!("DB error during deleting part: {}", e);
-            AppError::DatabaseError("Failed to delete part".to_string())
-        })?;
+    remove_part(&mut tx, &claims, id, if_match).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing part deletion: {}", e);
+        AppError::DatabaseError("Failed to delete part".to_string())
+    })?;
+    Ok(())
+}
+
+/// トランザクション内で `delete_part` の権限確認・論理削除を行う
+pub async fn remove_part(
+    conn: &mut PgConnection,
+    claims: &Claims,
+    id: Uuid,
+    if_match: IfMatch,
+) -> Result<(), AppError> {
+    ensure_admin_or_owner(claims.clone(), &mut *conn, id).await?;
+    ensure_not_locked(claims, &mut *conn, id).await?;
+
+    lock_and_check_version(conn, "parts", id, &if_match).await?;
+    ensure_not_used_in_bom(conn, id).await?;
+    let before = snapshot(&mut *conn, AuditEntity::Part, id).await?;
+
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    // 論理削除してゴミ箱に移す (完全削除は `purge_trash`)
+    let result = sqlx::query!(
+        r#"UPDATE parts
+        SET deleted_at = NOW(), deleted_by = $2
+        WHERE id = $1 AND deleted_at IS NULL"#,
+        id,
+        user_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during deleting part: {}", e);
+        AppError::DatabaseError("Failed to delete part".to_string())
+    })?;
 
     if result.rows_affected() == 0 {
         info!("Part not found for deletion: {}", id);
@@ -24,7 +72,51 @@ pub async fn delete_part(claims: Claims, pool: &PgPool, id: Uuid) -> Result<(),
             id
         )))
     } else {
-        info!("Part deleted successfully: {}", id);
+        record_audit(
+            conn,
+            AuditEntity::Part,
+            id,
+            AuditAction::Delete,
+            Some(user_id),
+            before,
+        )
+        .await?;
+        info!("Part moved to trash: {}", id);
         Ok(())
     }
 }
+
+/// ゴミ箱にない部品の構成に子として使われていれば 409 を返す (使用先の親部品の id を付ける)
+async fn ensure_not_used_in_bom(conn: &mut PgConnection, id: Uuid) -> Result<(), AppError> {
+    let parent = sqlx::query!(
+        r#"SELECT p.id, p.part_number
+        FROM bom_lines b
+        JOIN parts p ON p.id = b.parent_id AND p.deleted_at IS NULL
+        WHERE b.child_id = $1
+        ORDER BY p.part_number
+        LIMIT 1
+        "#,
+        id
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during checking where-used: {}", e);
+        AppError::DatabaseError("Failed to delete part".to_string())
+    })?;
+
+    match parent {
+        Some(parent) => {
+            info!("Part {} is still used in BOM of {}", id, parent.id);
+            Err(conflict_error(
+                "id",
+                &format!(
+                    "Part is used in the BOM of {}; remove it from the BOM first",
+                    parent.part_number
+                ),
+                Some(parent.id),
+            ))
+        }
+        None => Ok(()),
+    }
+}
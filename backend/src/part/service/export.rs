@@ -0,0 +1,52 @@
+use crate::errors::app_error::AppError;
+use crate::part::domain::{Part, PartListQuery};
+use crate::responses::export::{EXPORT_CHUNK, RowSender};
+
+use sqlx::{PgPool, QueryBuilder};
+use tracing::{error, info};
+
+use super::get::{push_after, push_filters, push_order};
+
+/// 一覧と同じ絞り込み・並び順の部品をページングせずに 1 件ずつ送る。
+/// `EXPORT_CHUNK` 件ずつキーセット方式で読み、読み終えるたびに接続をプールに返す
+pub async fn export_parts(pool: PgPool, filter: PartListQuery, sender: RowSender<Part>) {
+    let mut last: Option<Part> = None;
+    let mut exported = 0;
+    loop {
+        let mut select = QueryBuilder::new(
+            "SELECT id, part_number, name, description, kind, status, created_at, created_by, updated_at, version FROM parts",
+        );
+        push_filters(&mut select, &filter);
+        if let Some(last) = &last {
+            push_after(&mut select, &filter, last);
+        }
+        push_order(&mut select, &filter);
+        select.push(" LIMIT ").push_bind(EXPORT_CHUNK);
+
+        let parts = match select.build_query_as::<Part>().fetch_all(&pool).await {
+            Ok(parts) => parts,
+            Err(e) => {
+                error!("DB error during exporting parts: {}", e);
+                let _ = sender
+                    .send(Err(AppError::DatabaseError("DB select failed".to_string())))
+                    .await;
+                return;
+            }
+        };
+        let done = (parts.len() as i64) < EXPORT_CHUNK;
+        last = parts.last().cloned();
+
+        for part in parts {
+            // 受信側が閉じていればクライアントが切断している
+            if sender.send(Ok(part)).await.is_err() {
+                return;
+            }
+            exported += 1;
+        }
+        if done {
+            break;
+        }
+    }
+
+    info!("Exported {} parts", exported);
+}
@@ -1,34 +1,162 @@
 use crate::errors::app_error::AppError;
-use crate::part::domain::Part;
+use crate::part::domain::{Part, PartListQuery, PartSortColumn};
+use crate::responses::pagination::{PageQuery, SortOrder};
 
-use sqlx::PgPool;
+use sqlx::{Encode, PgPool, Postgres, QueryBuilder, Type};
 use tracing::{error, info};
 use uuid::Uuid;
 
-pub async fn get_parts(pool: &PgPool) -> Result<Vec<Part>, AppError> {
-    let parts = sqlx::query_as!(
-        Part,
-        r#"SELECT id, part_number, name, description, kind, created_at, created_by, updated_at
-        FROM parts
-        "#
-    )
-    .fetch_all(pool)
-    .await
-    .map_err(|e| {
-        error!("DB error during fetching parts: {}", e);
-        AppError::DatabaseError("DB select failed".to_string())
-    })?;
+/// 一覧の絞り込み条件を WHERE 句として追加する
+pub fn push_filters(builder: &mut QueryBuilder<'_, Postgres>, filter: &PartListQuery) {
+    builder.push(" WHERE deleted_at IS NULL");
+    if let Some(kind) = &filter.kind {
+        builder.push(" AND kind = ").push_bind(kind.clone());
+    }
+    if let Some(status) = &filter.status {
+        builder.push(" AND status = ").push_bind(status.clone());
+    }
+    if let Some(created_by) = filter.created_by {
+        builder.push(" AND created_by = ").push_bind(created_by);
+    }
+    if let Some(from) = filter.created_from {
+        builder.push(" AND created_at >= ").push_bind(from);
+    }
+    if let Some(to) = filter.created_to {
+        builder.push(" AND created_at < ").push_bind(to);
+    }
+    if let Some(from) = filter.updated_from {
+        builder.push(" AND updated_at >= ").push_bind(from);
+    }
+    if let Some(to) = filter.updated_to {
+        builder.push(" AND updated_at < ").push_bind(to);
+    }
+    if let Some(prefix) = &filter.name_prefix {
+        let escaped = prefix
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        builder
+            .push(" AND name LIKE ")
+            .push_bind(format!("{}%", escaped));
+    }
+}
+
+/// 一覧の並び順を ORDER BY 句として追加する
+pub fn push_order(builder: &mut QueryBuilder<'_, Postgres>, filter: &PartListQuery) {
+    builder
+        .push(" ORDER BY ")
+        .push(filter.sort.as_sql())
+        .push(" ")
+        .push(filter.order.as_sql())
+        .push(", id");
+}
+
+/// `push_order` の並び順で `last` より後の部品に絞る条件を追加する (キーセット方式のページング)
+pub fn push_after(builder: &mut QueryBuilder<'_, Postgres>, filter: &PartListQuery, last: &Part) {
+    match filter.sort {
+        PartSortColumn::PartNumber => {
+            push_keyset(builder, filter, Some(last.part_number.clone()), last.id)
+        }
+        PartSortColumn::Name => push_keyset(builder, filter, Some(last.name.clone()), last.id),
+        PartSortColumn::Kind => push_keyset(builder, filter, last.kind.clone(), last.id),
+        PartSortColumn::Status => push_keyset(builder, filter, Some(last.status.clone()), last.id),
+        PartSortColumn::CreatedAt => push_keyset(builder, filter, last.created_at, last.id),
+        PartSortColumn::UpdatedAt => push_keyset(builder, filter, last.updated_at, last.id),
+    }
+}
+
+/// PostgreSQL は NULL を昇順では最後、降順では最初に並べる
+fn push_keyset<'args, T>(
+    builder: &mut QueryBuilder<'args, Postgres>,
+    filter: &PartListQuery,
+    value: Option<T>,
+    id: Uuid,
+) where
+    T: 'args + Encode<'args, Postgres> + Type<Postgres> + Clone,
+{
+    let column = filter.sort.as_sql();
+    match (filter.order, value) {
+        (SortOrder::Asc, Some(value)) => {
+            builder
+                .push(format!(" AND ({} > ", column))
+                .push_bind(value.clone())
+                .push(format!(" OR ({} = ", column))
+                .push_bind(value)
+                .push(" AND id > ")
+                .push_bind(id)
+                .push(format!(") OR {} IS NULL)", column));
+        }
+        (SortOrder::Asc, None) => {
+            builder
+                .push(format!(" AND {} IS NULL AND id > ", column))
+                .push_bind(id);
+        }
+        (SortOrder::Desc, Some(value)) => {
+            builder
+                .push(format!(" AND ({} < ", column))
+                .push_bind(value.clone())
+                .push(format!(" OR ({} = ", column))
+                .push_bind(value)
+                .push(" AND id > ")
+                .push_bind(id)
+                .push("))");
+        }
+        (SortOrder::Desc, None) => {
+            builder
+                .push(format!(" AND ({} IS NOT NULL OR id > ", column))
+                .push_bind(id)
+                .push(")");
+        }
+    }
+}
+
+pub async fn get_parts(
+    pool: &PgPool,
+    filter: &PartListQuery,
+    page: &PageQuery,
+) -> Result<(Vec<Part>, i64), AppError> {
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM parts");
+    push_filters(&mut count, filter);
+
+    let total: i64 = count
+        .build_query_scalar()
+        .fetch_one(pool)
+        .await
+        .map_err(|e| {
+            error!("DB error during counting parts: {}", e);
+            AppError::DatabaseError("DB select failed".to_string())
+        })?;
+
+    let mut select = QueryBuilder::new(
+        "SELECT id, part_number, name, description, kind, status, created_at, created_by, updated_at, version FROM parts",
+    );
+    push_filters(&mut select, filter);
+    push_order(&mut select, filter);
+    select
+        .push(" LIMIT ")
+        .push_bind(page.per_page())
+        .push(" OFFSET ")
+        .push_bind(page.offset());
+
+    let parts = select
+        .build_query_as::<Part>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| {
+            error!("DB error during fetching parts: {}", e);
+            AppError::DatabaseError("DB select failed".to_string())
+        })?;
 
-    info!("Fetched {} parts successfully", parts.len());
-    Ok(parts)
+    info!("Fetched {} of {} parts successfully", parts.len(), total);
+    Ok((parts, total))
 }
 
 pub async fn get_part(pool: &PgPool, id: Uuid) -> Result<Part, AppError> {
     let part = sqlx::query_as!(
         Part,
-        r#"SELECT id, part_number, name, description, kind, created_at, created_by, updated_at
+        r#"SELECT id, part_number, name, description, kind, status, created_at, created_by, updated_at, version
         FROM parts
-        WHERE id = $1
+        WHERE id = $1 AND deleted_at IS NULL
         "#,
         id
     )
@@ -0,0 +1,88 @@
+use crate::auth::domain::{Claims, Role};
+use crate::errors::app_error::AppError;
+use crate::part::domain::{Lifecycle, LifecycleState, LifecycleTransition};
+
+use sqlx::{PgExecutor, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+
+pub async fn get_lifecycle(pool: &PgPool) -> Result<Lifecycle, AppError> {
+    let states = sqlx::query_as!(
+        LifecycleState,
+        r#"SELECT name, label, locked, releases_revision
+        FROM lifecycle_states
+        ORDER BY sort_order, name
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching lifecycle states: {}", e);
+        AppError::DatabaseError("Failed to fetch lifecycle".to_string())
+    })?;
+
+    let transitions = sqlx::query_as!(
+        LifecycleTransition,
+        r#"SELECT from_state, to_state, required_role
+        FROM lifecycle_transitions
+        ORDER BY from_state, to_state
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching lifecycle transitions: {}", e);
+        AppError::DatabaseError("Failed to fetch lifecycle".to_string())
+    })?;
+
+    Ok(Lifecycle {
+        states,
+        transitions,
+    })
+}
+
+/// 部品がロックされた状態 (Released など) にあればその状態名を返す
+pub async fn locked_state(
+    executor: impl PgExecutor<'_>,
+    id: Uuid,
+) -> Result<Option<String>, AppError> {
+    let state = sqlx::query!(
+        r#"SELECT s.name, s.locked
+        FROM parts p
+        JOIN lifecycle_states s ON s.name = p.status
+        WHERE p.id = $1 AND p.deleted_at IS NULL
+        "#,
+        id
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(|e| {
+        error!("DB error during lifecycle check: {}", e);
+        AppError::DatabaseError("Lifecycle check failed".into())
+    })?
+    .ok_or_else(|| AppError::NotFound(format!("Part not found: {}", id)))?;
+
+    Ok(state.locked.then_some(state.name))
+}
+
+/// ロックされた状態の部品は管理者以外が変更できないようにする
+pub async fn ensure_not_locked(
+    claims: &Claims,
+    executor: impl PgExecutor<'_>,
+    id: Uuid,
+) -> Result<(), AppError> {
+    if claims.role == Role::Admin {
+        return Ok(());
+    }
+
+    match locked_state(executor, id).await? {
+        Some(state) => {
+            info!("Part {} is locked in state {}", id, state);
+            Err(AppError::Unauthorized(format!(
+                "Part in state '{}' cannot be modified.",
+                state
+            )))
+        }
+        None => Ok(()),
+    }
+}
@@ -1,10 +1,30 @@
 pub mod auth;
+pub mod bulk;
 pub mod create;
 pub mod delete;
+pub mod export;
 pub mod get;
+pub mod lifecycle;
+pub mod numbering;
+pub mod patch;
+pub mod revision;
+pub mod search;
+pub mod transition;
+pub mod trash;
 pub mod update;
+pub mod where_used;
 
+pub use bulk::bulk_parts;
 pub use create::create_part;
 pub use delete::delete_part;
+pub use export::export_parts;
 pub use get::{get_part, get_parts};
+pub use lifecycle::get_lifecycle;
+pub use numbering::{get_part_number_schemes, reserve_part_numbers, update_part_number_scheme};
+pub use patch::patch_part;
+pub use revision::{diff_revisions, get_revision, list_revisions, release_revision};
+pub use search::search_parts;
+pub use transition::transition_part;
+pub use trash::{get_trash, purge_trash, restore_part};
 pub use update::update_part;
+pub use where_used::get_where_used;
@@ -0,0 +1,260 @@
+use crate::auth::domain::{Claims, Role};
+use crate::errors::app_error::AppError;
+use crate::errors::conflict::conflict_error;
+use crate::errors::validation::extract_validation_errors;
+use crate::part::domain::{
+    PartNumberReservation, PartNumberScheme, ReservePartNumbers, UpdatePartNumberScheme,
+};
+
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+use validator::Validate;
+
+pub async fn get_part_number_schemes(pool: &PgPool) -> Result<Vec<PartNumberScheme>, AppError> {
+    sqlx::query_as!(
+        PartNumberScheme,
+        r#"SELECT kind, prefix, padding, check_digit, next_value, updated_at
+        FROM part_number_schemes
+        ORDER BY kind
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching part number schemes: {}", e);
+        AppError::DatabaseError("Failed to fetch part number schemes".to_string())
+    })
+}
+
+/// 採番ルールを登録または更新する (管理者のみ)
+pub async fn update_part_number_scheme(
+    claims: Claims,
+    pool: &PgPool,
+    kind: &str,
+    scheme: UpdatePartNumberScheme,
+) -> Result<PartNumberScheme, AppError> {
+    scheme
+        .validate()
+        .map_err(|e| AppError::ValidationError(extract_validation_errors(e)))?;
+
+    if claims.role != Role::Admin {
+        info!(
+            "User {} is not allowed to configure part numbering",
+            claims.sub
+        );
+        return Err(AppError::Unauthorized(
+            "Only admins can configure part numbering.".to_string(),
+        ));
+    }
+
+    let updated = sqlx::query_as!(
+        PartNumberScheme,
+        r#"INSERT INTO part_number_schemes (kind, prefix, padding, check_digit, next_value)
+           VALUES ($1, $2, $3, $4, COALESCE($5::bigint, 1))
+           ON CONFLICT (kind) DO UPDATE
+           SET prefix = EXCLUDED.prefix,
+               padding = EXCLUDED.padding,
+               check_digit = EXCLUDED.check_digit,
+               next_value = COALESCE($5::bigint, part_number_schemes.next_value),
+               updated_at = NOW()
+           RETURNING kind, prefix, padding, check_digit, next_value, updated_at"#,
+        kind,
+        scheme.prefix,
+        scheme.padding,
+        scheme.check_digit,
+        scheme.next_value
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during updating part number scheme: {}", e);
+        AppError::DatabaseError("Failed to update part number scheme".to_string())
+    })?;
+
+    info!("Part number scheme updated: {}", updated.kind);
+    Ok(updated)
+}
+
+/// kind の採番ルールで未使用の品番を `count` 件払い出す。
+/// 採番ルールの行をロックするため、同時に払い出しても重複しない。
+pub async fn next_part_numbers(
+    conn: &mut PgConnection,
+    kind: Option<&str>,
+    count: i64,
+) -> Result<Vec<String>, AppError> {
+    let scheme = sqlx::query_as!(
+        PartNumberScheme,
+        r#"SELECT kind, prefix, padding, check_digit, next_value, updated_at
+        FROM part_number_schemes
+        WHERE kind = $1 OR kind = $2
+        ORDER BY kind = $2
+        LIMIT 1
+        FOR UPDATE
+        "#,
+        kind.unwrap_or(PartNumberScheme::DEFAULT_KIND),
+        PartNumberScheme::DEFAULT_KIND
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching part number scheme: {}", e);
+        AppError::DatabaseError("Failed to generate part number".to_string())
+    })?
+    .ok_or_else(|| AppError::InternalError("Default part number scheme is missing".to_string()))?;
+
+    let mut numbers = Vec::new();
+    let mut value = scheme.next_value;
+    while (numbers.len() as i64) < count {
+        let candidate = scheme.format(value);
+        value += 1;
+        if !part_number_taken(&mut *conn, &candidate).await? {
+            numbers.push(candidate);
+        }
+    }
+
+    sqlx::query!(
+        r#"UPDATE part_number_schemes SET next_value = $1, updated_at = NOW() WHERE kind = $2"#,
+        value,
+        scheme.kind
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during advancing part number sequence: {}", e);
+        AppError::DatabaseError("Failed to generate part number".to_string())
+    })?;
+
+    Ok(numbers)
+}
+
+async fn part_number_taken(conn: &mut PgConnection, part_number: &str) -> Result<bool, AppError> {
+    sqlx::query_scalar!(
+        r#"SELECT (EXISTS (SELECT 1 FROM parts WHERE part_number = $1)
+                OR EXISTS (SELECT 1 FROM part_number_reservations WHERE part_number = $1)) AS "taken!""#,
+        part_number
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during checking part number: {}", e);
+        AppError::DatabaseError("Failed to check part number".to_string())
+    })
+}
+
+/// 品番が他の部品で使われておらず、他のユーザーに予約されていないことを確認する
+pub async fn ensure_part_number_available(
+    conn: &mut PgConnection,
+    part_number: &str,
+    part_id: Option<Uuid>,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    let found = sqlx::query!(
+        r#"SELECT
+            (SELECT id FROM parts WHERE part_number = $1 AND id IS DISTINCT FROM $2) AS used_by,
+            EXISTS (
+                SELECT 1 FROM part_number_reservations
+                WHERE part_number = $1 AND consumed_at IS NULL AND reserved_by <> $3
+            ) AS "reserved!"
+        "#,
+        part_number,
+        part_id,
+        user_id
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during checking part number: {}", e);
+        AppError::DatabaseError("Failed to check part number".to_string())
+    })?;
+
+    if let Some(existing_id) = found.used_by {
+        info!("Part number already exists: {}", part_number);
+        return Err(conflict_error(
+            "part_number",
+            &format!("Part number already exists: {}", part_number),
+            Some(existing_id),
+        ));
+    }
+    if found.reserved {
+        info!("Part number reserved by another user: {}", part_number);
+        return Err(conflict_error(
+            "part_number",
+            &format!("Part number is reserved by another user: {}", part_number),
+            None,
+        ));
+    }
+    Ok(())
+}
+
+/// 予約済みの品番を部品に割り当てて使用済みにする
+pub async fn consume_reservation(
+    conn: &mut PgConnection,
+    part_number: &str,
+    part_id: Uuid,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"UPDATE part_number_reservations
+        SET part_id = $1, consumed_at = NOW()
+        WHERE part_number = $2 AND consumed_at IS NULL
+        "#,
+        part_id,
+        part_number
+    )
+    .execute(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during consuming part number reservation: {}", e);
+        AppError::DatabaseError("Failed to consume part number reservation".to_string())
+    })?;
+    Ok(())
+}
+
+pub async fn reserve_part_numbers(
+    claims: Claims,
+    pool: &PgPool,
+    request: ReservePartNumbers,
+) -> Result<Vec<PartNumberReservation>, AppError> {
+    request
+        .validate()
+        .map_err(|e| AppError::ValidationError(extract_validation_errors(e)))?;
+
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to reserve part numbers".to_string())
+    })?;
+
+    let numbers =
+        next_part_numbers(&mut tx, request.kind.as_deref(), request.count.unwrap_or(1)).await?;
+
+    let reservations = sqlx::query_as!(
+        PartNumberReservation,
+        r#"INSERT INTO part_number_reservations (part_number, kind, reserved_by)
+           SELECT n, $2, $3 FROM UNNEST($1::text[]) AS n
+           RETURNING part_number, kind, reserved_by, reserved_at, part_id, consumed_at"#,
+        &numbers,
+        request.kind,
+        user_id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during reserving part numbers: {}", e);
+        AppError::DatabaseError("Failed to reserve part numbers".to_string())
+    })?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing part number reservation: {}", e);
+        AppError::DatabaseError("Failed to reserve part numbers".to_string())
+    })?;
+
+    info!(
+        "Reserved {} part numbers for user {}",
+        reservations.len(),
+        user_id
+    );
+    Ok(reservations)
+}
@@ -0,0 +1,95 @@
+use crate::auth::domain::Claims;
+use crate::eco::service::item::find_open_eco_item;
+use crate::errors::app_error::AppError;
+use crate::errors::conflict::conflict_error;
+use crate::errors::validation::field_validation_error;
+use crate::part::domain::{NewPart, Part};
+use crate::patch::domain::{PatchDocument, PatchError};
+use crate::responses::etag::IfMatch;
+
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::get::get_part;
+use super::lifecycle::locked_state;
+use super::update::update_part;
+
+const PATCHABLE_FIELDS: [&str; 4] = ["part_number", "name", "description", "kind"];
+
+/// 現在の内容にパッチを適用し、結果を `update_part` と同じ検証・権限確認で保存する。
+/// ロックされた部品では、作成中の ECO に記録された変更内容にパッチを適用する
+pub async fn patch_part(
+    claims: Claims,
+    pool: &PgPool,
+    id: Uuid,
+    patch: PatchDocument,
+    if_match: IfMatch,
+) -> Result<Part, AppError> {
+    let current = get_part(pool, id).await?;
+    let base = pending_change(pool, id).await?.unwrap_or(NewPart {
+        part_number: current.part_number,
+        name: current.name,
+        description: current.description,
+        kind: current.kind,
+    });
+
+    let mut document = serde_json::to_value(base)
+        .map_err(|e| AppError::InternalError(format!("Failed to serialize part: {}", e)))?;
+
+    patch.apply(&mut document).map_err(|e| match e {
+        PatchError::InvalidPath(path) => {
+            AppError::ValidationError(field_validation_error(&path, "Invalid patch path"))
+        }
+        PatchError::TestFailed(path) => conflict_error(&path, "Patch test operation failed", None),
+    })?;
+
+    let Value::Object(fields) = &document else {
+        return Err(AppError::ValidationError(field_validation_error(
+            "body",
+            "Patched part must be an object",
+        )));
+    };
+    if let Some(field) = fields
+        .keys()
+        .find(|key| !PATCHABLE_FIELDS.contains(&key.as_str()))
+    {
+        return Err(AppError::ValidationError(field_validation_error(
+            field,
+            "Field cannot be patched",
+        )));
+    }
+
+    let patched: NewPart = serde_json::from_value(document)
+        .map_err(|e| AppError::ValidationError(field_validation_error("body", &e.to_string())))?;
+
+    // 読み込んだ内容にパッチを当てたので、`*` 指定でも読み込んだバージョンに対してのみ保存する
+    let if_match = if if_match.matches(current.version) {
+        IfMatch::for_version(current.version)
+    } else {
+        if_match
+    };
+
+    info!("Applying patch to part {}", id);
+    update_part(claims, pool, id, patched, if_match).await
+}
+
+/// ロックされた部品を含む作成中の ECO があれば、その変更内容を返す
+async fn pending_change(pool: &PgPool, id: Uuid) -> Result<Option<NewPart>, AppError> {
+    let mut conn = pool.acquire().await.map_err(|e| {
+        error!("DB error during acquiring connection: {}", e);
+        AppError::DatabaseError("Failed to fetch part".to_string())
+    })?;
+    if locked_state(&mut *conn, id).await?.is_none() {
+        return Ok(None);
+    }
+    Ok(find_open_eco_item(&mut conn, id)
+        .await?
+        .map(|item| NewPart {
+            part_number: item.part_number,
+            name: item.name,
+            description: item.description,
+            kind: item.kind,
+        }))
+}
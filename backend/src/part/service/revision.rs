@@ -0,0 +1,283 @@
+use crate::audit::domain::{AuditAction, AuditEntity};
+use crate::audit::service::{record_audit, snapshot};
+use crate::auth::domain::Claims;
+use crate::errors::app_error::AppError;
+use crate::part::domain::{NewPart, PartRevision, RevisionDiff, RevisionScheme, RevisionStatus};
+
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::auth::ensure_admin_or_owner;
+
+/// リビジョンのない部品 (リビジョン導入前からある部品) に、初版の作業中リビジョンを作成する。
+/// 初版の記号は `REVISION_SCHEME` に従うため、マイグレーションではなく起動時に行う
+pub async fn backfill_initial_revisions(pool: &PgPool) -> Result<u64, AppError> {
+    let result = sqlx::query!(
+        r#"INSERT INTO part_revisions (part_id, revision, part_number, name, description, kind, created_at, created_by)
+        SELECT id, $1, part_number, name, description, kind, created_at, created_by
+        FROM parts p
+        WHERE NOT EXISTS (SELECT 1 FROM part_revisions r WHERE r.part_id = p.id)
+        "#,
+        RevisionScheme::from_env().initial()
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during backfilling revisions: {}", e);
+        AppError::DatabaseError("Failed to backfill revisions".to_string())
+    })?;
+    Ok(result.rows_affected())
+}
+
+pub async fn insert_revision(
+    conn: &mut PgConnection,
+    part_id: Uuid,
+    revision: &str,
+    new_part: &NewPart,
+    user_id: Uuid,
+) -> Result<PartRevision, AppError> {
+    sqlx::query_as!(
+        PartRevision,
+        r#"INSERT INTO part_revisions (part_id, revision, part_number, name, description, kind, created_by)
+           VALUES ($1, $2, $3, $4, $5, $6, $7)
+           RETURNING id, part_id, revision, part_number, name, description, kind, status,
+                     created_at, created_by, released_at, released_by"#,
+        part_id,
+        revision,
+        new_part.part_number,
+        new_part.name,
+        new_part.description,
+        new_part.kind,
+        user_id
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during revision insertion: {}", e);
+        AppError::DatabaseError("Failed to create revision".to_string())
+    })
+}
+
+/// 最新のリビジョンを行ロック付きで取得する
+pub async fn lock_latest_revision(
+    conn: &mut PgConnection,
+    part_id: Uuid,
+) -> Result<Option<PartRevision>, AppError> {
+    sqlx::query_as!(
+        PartRevision,
+        r#"SELECT id, part_id, revision, part_number, name, description, kind, status,
+                  created_at, created_by, released_at, released_by
+        FROM part_revisions
+        WHERE part_id = $1
+        ORDER BY created_at DESC, length(revision) DESC, revision DESC
+        LIMIT 1
+        FOR UPDATE
+        "#,
+        part_id
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching latest revision: {}", e);
+        AppError::DatabaseError("Failed to fetch revision".to_string())
+    })
+}
+
+pub async fn update_working_revision(
+    conn: &mut PgConnection,
+    id: Uuid,
+    new_part: &NewPart,
+) -> Result<PartRevision, AppError> {
+    sqlx::query_as!(
+        PartRevision,
+        r#"UPDATE part_revisions
+        SET part_number = $1,
+            name = $2,
+            description = $3,
+            kind = $4
+        WHERE id = $5 AND status = 'working'
+        RETURNING id, part_id, revision, part_number, name, description, kind, status,
+                  created_at, created_by, released_at, released_by
+        "#,
+        new_part.part_number,
+        new_part.name,
+        new_part.description,
+        new_part.kind,
+        id
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during updating revision: {}", e);
+        AppError::DatabaseError("Failed to update revision".to_string())
+    })
+}
+
+pub async fn list_revisions(pool: &PgPool, part_id: Uuid) -> Result<Vec<PartRevision>, AppError> {
+    let revisions = sqlx::query_as!(
+        PartRevision,
+        r#"SELECT id, part_id, revision, part_number, name, description, kind, status,
+                  created_at, created_by, released_at, released_by
+        FROM part_revisions
+        WHERE part_id = $1
+          AND EXISTS (SELECT 1 FROM parts WHERE id = $1 AND deleted_at IS NULL)
+        ORDER BY created_at, length(revision), revision
+        "#,
+        part_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching revisions: {}", e);
+        AppError::DatabaseError("Failed to fetch revisions".to_string())
+    })?;
+
+    if revisions.is_empty() {
+        info!("Part not found: {}", part_id);
+        return Err(AppError::NotFound(format!("Part not found: {}", part_id)));
+    }
+
+    info!(
+        "Fetched {} revisions of part {} successfully",
+        revisions.len(),
+        part_id
+    );
+    Ok(revisions)
+}
+
+pub async fn get_revision(
+    pool: &PgPool,
+    part_id: Uuid,
+    revision: &str,
+) -> Result<PartRevision, AppError> {
+    let found = sqlx::query_as!(
+        PartRevision,
+        r#"SELECT id, part_id, revision, part_number, name, description, kind, status,
+                  created_at, created_by, released_at, released_by
+        FROM part_revisions
+        WHERE part_id = $1 AND revision = $2
+          AND EXISTS (SELECT 1 FROM parts WHERE id = $1 AND deleted_at IS NULL)
+        "#,
+        part_id,
+        revision
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching revision: {}", e);
+        AppError::DatabaseError("Failed to fetch revision".to_string())
+    })?;
+
+    match found {
+        Some(found) => {
+            info!("Revision found: {} {}", part_id, found.revision);
+            Ok(found)
+        }
+        None => {
+            info!("Revision not found: {} {}", part_id, revision);
+            Err(AppError::NotFound(format!(
+                "Revision not found: {} {}",
+                part_id, revision
+            )))
+        }
+    }
+}
+
+pub async fn diff_revisions(
+    pool: &PgPool,
+    part_id: Uuid,
+    from: &str,
+    to: &str,
+) -> Result<RevisionDiff, AppError> {
+    let from = get_revision(pool, part_id, from).await?;
+    let to = get_revision(pool, part_id, to).await?;
+    Ok(RevisionDiff::between(&from, &to))
+}
+
+pub async fn release_revision(
+    claims: Claims,
+    pool: &PgPool,
+    part_id: Uuid,
+    revision: &str,
+) -> Result<PartRevision, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to release revision".to_string())
+    })?;
+
+    ensure_admin_or_owner(claims, &mut *tx, part_id).await?;
+
+    let working = sqlx::query_scalar!(
+        r#"SELECT id FROM part_revisions
+        WHERE part_id = $1 AND revision = $2 AND status = $3
+        FOR UPDATE
+        "#,
+        part_id,
+        revision,
+        RevisionStatus::Working.as_str()
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching working revision: {}", e);
+        AppError::DatabaseError("Failed to release revision".to_string())
+    })?
+    .ok_or_else(|| {
+        info!("Working revision not found: {} {}", part_id, revision);
+        AppError::NotFound(format!(
+            "Working revision not found: {} {}",
+            part_id, revision
+        ))
+    })?;
+
+    let before = snapshot(&mut tx, AuditEntity::PartRevision, working).await?;
+    let released = mark_revision_released(&mut tx, working, user_id).await?;
+    record_audit(
+        &mut tx,
+        AuditEntity::PartRevision,
+        released.id,
+        AuditAction::Release,
+        Some(user_id),
+        before,
+    )
+    .await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing revision release: {}", e);
+        AppError::DatabaseError("Failed to release revision".to_string())
+    })?;
+
+    info!("Revision released: {} {}", part_id, released.revision);
+    Ok(released)
+}
+
+pub async fn mark_revision_released(
+    conn: &mut PgConnection,
+    id: Uuid,
+    user_id: Uuid,
+) -> Result<PartRevision, AppError> {
+    sqlx::query_as!(
+        PartRevision,
+        r#"UPDATE part_revisions
+        SET status = $1,
+            released_at = NOW(),
+            released_by = $2
+        WHERE id = $3
+        RETURNING id, part_id, revision, part_number, name, description, kind, status,
+                  created_at, created_by, released_at, released_by
+        "#,
+        RevisionStatus::Released.as_str(),
+        user_id,
+        id
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        error!("DB error during releasing revision: {}", e);
+        AppError::DatabaseError("Failed to release revision".to_string())
+    })
+}
@@ -0,0 +1,100 @@
+use crate::errors::app_error::AppError;
+use crate::errors::validation::{extract_validation_errors, field_validation_error};
+use crate::part::domain::{
+    Part, PartSearchHit, PartSearchQuery, SearchHighlight, highlight_snippet,
+};
+
+use sqlx::PgPool;
+use tracing::{error, info};
+use validator::Validate;
+
+const DEFAULT_SEARCH_LIMIT: i64 = 20;
+
+/// 全文検索 (tsvector) と部分一致 (トライグラム) を組み合わせて部品を検索する
+pub async fn search_parts(
+    pool: &PgPool,
+    query: PartSearchQuery,
+) -> Result<Vec<PartSearchHit>, AppError> {
+    query
+        .validate()
+        .map_err(|e| AppError::ValidationError(extract_validation_errors(e)))?;
+
+    let q = query.q.trim();
+    if q.is_empty() {
+        return Err(AppError::ValidationError(field_validation_error(
+            "q",
+            "q must not be empty",
+        )));
+    }
+    let escaped = q
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    let rows = sqlx::query!(
+        r#"SELECT id, part_number, name, description, kind, status, created_at, created_by, updated_at, version,
+               score.rank AS "rank!"
+        FROM parts,
+            LATERAL (SELECT (ts_rank(search_vector, plainto_tsquery('simple', $1)) * 2
+                + word_similarity($1, part_number)
+                + word_similarity($1, name)
+                + CASE WHEN part_number ILIKE $2 || '%' THEN 1 ELSE 0 END
+                + CASE WHEN name ILIKE '%' || $2 || '%' THEN 0.5 ELSE 0 END)::real AS rank) score
+        WHERE deleted_at IS NULL
+          AND (search_vector @@ plainto_tsquery('simple', $1)
+               OR part_number ILIKE '%' || $2 || '%'
+               OR name ILIKE '%' || $2 || '%'
+               OR description ILIKE '%' || $2 || '%')
+        ORDER BY rank DESC, part_number
+        LIMIT $3
+        "#,
+        q,
+        escaped,
+        query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT)
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during searching parts: {}", e);
+        AppError::DatabaseError("Failed to search parts".to_string())
+    })?;
+
+    let hits: Vec<PartSearchHit> = rows
+        .into_iter()
+        .map(|row| {
+            let highlights = [
+                ("part_number", Some(row.part_number.as_str())),
+                ("name", Some(row.name.as_str())),
+                ("description", row.description.as_deref()),
+            ]
+            .into_iter()
+            .filter_map(|(field, text)| {
+                highlight_snippet(text?, q).map(|snippet| SearchHighlight {
+                    field: field.to_string(),
+                    snippet,
+                })
+            })
+            .collect();
+
+            PartSearchHit {
+                part: Part {
+                    id: row.id,
+                    part_number: row.part_number,
+                    name: row.name,
+                    description: row.description,
+                    kind: row.kind,
+                    status: row.status,
+                    created_at: row.created_at,
+                    created_by: row.created_by,
+                    updated_at: row.updated_at,
+                    version: row.version,
+                },
+                rank: row.rank,
+                highlights,
+            }
+        })
+        .collect();
+
+    info!("Found {} parts for query {:?}", hits.len(), q);
+    Ok(hits)
+}
@@ -0,0 +1,140 @@
+use crate::audit::domain::{AuditAction, AuditEntity};
+use crate::audit::service::{record_audit, snapshot};
+use crate::auth::domain::{Claims, Role};
+use crate::errors::app_error::AppError;
+use crate::errors::validation::{extract_validation_errors, field_validation_error};
+use crate::part::domain::{Part, RevisionStatus, TransitionRequest};
+
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+use validator::Validate;
+
+pub async fn transition_part(
+    claims: Claims,
+    pool: &PgPool,
+    id: Uuid,
+    request: TransitionRequest,
+) -> Result<Part, AppError> {
+    request
+        .validate()
+        .map_err(|e| AppError::ValidationError(extract_validation_errors(e)))?;
+
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to transition part".to_string())
+    })?;
+
+    let current = sqlx::query!(
+        r#"SELECT status, created_by FROM parts WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"#,
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching part status: {}", e);
+        AppError::DatabaseError("Failed to transition part".to_string())
+    })?
+    .ok_or_else(|| AppError::NotFound(format!("Part not found: {}", id)))?;
+
+    let transition = sqlx::query!(
+        r#"SELECT t.required_role, s.releases_revision
+        FROM lifecycle_transitions t
+        JOIN lifecycle_states s ON s.name = t.to_state
+        WHERE t.from_state = $1 AND t.to_state = $2
+        "#,
+        current.status,
+        request.to
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching lifecycle transition: {}", e);
+        AppError::DatabaseError("Failed to transition part".to_string())
+    })?
+    .ok_or_else(|| {
+        AppError::ValidationError(field_validation_error(
+            "to",
+            &format!(
+                "Transition from '{}' to '{}' is not allowed",
+                current.status, request.to
+            ),
+        ))
+    })?;
+
+    let required_role = Role::from(transition.required_role.as_str());
+    if !claims.role.satisfies(&required_role) {
+        return Err(AppError::Unauthorized(format!(
+            "Role '{}' is required for this transition.",
+            transition.required_role
+        )));
+    }
+    if claims.role != Role::Admin && current.created_by != Some(user_id) {
+        return Err(AppError::Unauthorized("You do not own this part.".into()));
+    }
+
+    let before = snapshot(&mut tx, AuditEntity::Part, id).await?;
+
+    if transition.releases_revision {
+        sqlx::query!(
+            r#"UPDATE part_revisions
+            SET status = $1,
+                released_at = NOW(),
+                released_by = $2
+            WHERE part_id = $3 AND status = $4
+            "#,
+            RevisionStatus::Released.as_str(),
+            user_id,
+            id,
+            RevisionStatus::Working.as_str()
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("DB error during releasing revision: {}", e);
+            AppError::DatabaseError("Failed to transition part".to_string())
+        })?;
+    }
+
+    let part = sqlx::query_as!(
+        Part,
+        r#"UPDATE parts
+        SET status = $1,
+            updated_at = NOW()
+        WHERE id = $2
+        RETURNING id, part_number, name, description, kind, status, created_at, created_by, updated_at, version
+        "#,
+        request.to,
+        id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during updating part status: {}", e);
+        AppError::DatabaseError("Failed to transition part".to_string())
+    })?;
+
+    record_audit(
+        &mut tx,
+        AuditEntity::Part,
+        id,
+        AuditAction::Transition,
+        Some(user_id),
+        before,
+    )
+    .await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing transition: {}", e);
+        AppError::DatabaseError("Failed to transition part".to_string())
+    })?;
+
+    info!(
+        "Part {} transitioned from {} to {}",
+        id, current.status, part.status
+    );
+    Ok(part)
+}
@@ -0,0 +1,228 @@
+use crate::audit::domain::{AuditAction, AuditEntity};
+use crate::audit::request_id::current_request_id;
+use crate::audit::service::{record_audit, snapshot};
+use crate::auth::domain::{Claims, Role};
+use crate::errors::app_error::AppError;
+use crate::part::domain::{Part, PurgeResult, RevisionStatus, TrashedPart, trash_retention_days};
+use crate::responses::pagination::PageQuery;
+
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// ゴミ箱の部品一覧。管理者以外は自分が作成または削除した部品のみ
+pub async fn get_trash(
+    claims: Claims,
+    pool: &PgPool,
+    page: &PageQuery,
+) -> Result<(Vec<TrashedPart>, i64), AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+    let is_admin = claims.role == Role::Admin;
+
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM parts
+        WHERE deleted_at IS NOT NULL AND ($1 OR created_by = $2 OR deleted_by = $2)"#,
+        is_admin,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during counting trashed parts: {}", e);
+        AppError::DatabaseError("DB select failed".to_string())
+    })?;
+
+    let rows = sqlx::query!(
+        r#"SELECT id, part_number, name, description, kind, status, created_at, created_by, updated_at, version,
+                  deleted_at AS "deleted_at!", deleted_by,
+                  deleted_at + make_interval(days => $3) AS "purge_after!"
+        FROM parts
+        WHERE deleted_at IS NOT NULL AND ($1 OR created_by = $2 OR deleted_by = $2)
+        ORDER BY deleted_at DESC, id
+        LIMIT $4 OFFSET $5
+        "#,
+        is_admin,
+        user_id,
+        trash_retention_days(),
+        page.per_page(),
+        page.offset()
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching trashed parts: {}", e);
+        AppError::DatabaseError("DB select failed".to_string())
+    })?;
+
+    let parts = rows
+        .into_iter()
+        .map(|row| TrashedPart {
+            part: Part {
+                id: row.id,
+                part_number: row.part_number,
+                name: row.name,
+                description: row.description,
+                kind: row.kind,
+                status: row.status,
+                created_at: row.created_at,
+                created_by: row.created_by,
+                updated_at: row.updated_at,
+                version: row.version,
+            },
+            deleted_at: row.deleted_at,
+            deleted_by: row.deleted_by,
+            purge_after: row.purge_after,
+        })
+        .collect::<Vec<_>>();
+
+    info!("Fetched {} of {} trashed parts", parts.len(), total);
+    Ok((parts, total))
+}
+
+/// ゴミ箱の部品を元に戻す (作成者か管理者のみ)
+pub async fn restore_part(claims: Claims, pool: &PgPool, id: Uuid) -> Result<Part, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to restore part".to_string())
+    })?;
+
+    let owner = sqlx::query_scalar!(
+        r#"SELECT created_by FROM parts WHERE id = $1 AND deleted_at IS NOT NULL FOR UPDATE"#,
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during restoring part: {}", e);
+        AppError::DatabaseError("Failed to restore part".to_string())
+    })?
+    .ok_or_else(|| AppError::NotFound(format!("Part not found in trash: {}", id)))?;
+
+    if claims.role != Role::Admin && owner != Some(user_id) {
+        info!("User {} is not allowed to restore part {}", claims.sub, id);
+        return Err(AppError::Unauthorized("You do not own this part.".into()));
+    }
+
+    let before = snapshot(&mut tx, AuditEntity::Part, id).await?;
+
+    let part = sqlx::query_as!(
+        Part,
+        r#"UPDATE parts
+        SET deleted_at = NULL, deleted_by = NULL, updated_at = NOW()
+        WHERE id = $1
+        RETURNING id, part_number, name, description, kind, status, created_at, created_by, updated_at, version
+        "#,
+        id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during restoring part: {}", e);
+        AppError::DatabaseError("Failed to restore part".to_string())
+    })?;
+
+    record_audit(
+        &mut tx,
+        AuditEntity::Part,
+        id,
+        AuditAction::Restore,
+        Some(user_id),
+        before,
+    )
+    .await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing part restore: {}", e);
+        AppError::DatabaseError("Failed to restore part".to_string())
+    })?;
+
+    info!("Part restored from trash: {}", id);
+    Ok(part)
+}
+
+/// 保持期間を過ぎたゴミ箱の部品を完全に削除する (管理者のみ)。
+/// 部品自身の構成行と作業中のリビジョンも削除され、削除前の行は監査ログに残る。
+/// 他の部品の構成に使われている部品や、リリース済みのリビジョン・ECO・ECR の記録がある部品は残す。
+pub async fn purge_trash(claims: Claims, pool: &PgPool) -> Result<PurgeResult, AppError> {
+    if claims.role != Role::Admin {
+        info!("User {} is not allowed to purge trash", claims.sub);
+        return Err(AppError::Unauthorized(
+            "Only admins can purge the trash.".to_string(),
+        ));
+    }
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to purge trash".to_string())
+    })?;
+
+    let retention_days = trash_retention_days();
+    let expired = sqlx::query!(
+        r#"SELECT p.id,
+                  (EXISTS (SELECT 1 FROM bom_lines b WHERE b.child_id = p.id)
+                   OR EXISTS (SELECT 1 FROM part_revisions r WHERE r.part_id = p.id AND r.status = $2)
+                   OR EXISTS (SELECT 1 FROM eco_items i WHERE i.part_id = p.id)
+                   OR EXISTS (SELECT 1 FROM ecr_parts e WHERE e.part_id = p.id)) AS "referenced!"
+        FROM parts p
+        WHERE p.deleted_at IS NOT NULL AND p.deleted_at < NOW() - make_interval(days => $1)
+        FOR UPDATE OF p
+        "#,
+        retention_days,
+        RevisionStatus::Released.as_str()
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during fetching expired trash: {}", e);
+        AppError::DatabaseError("Failed to purge trash".to_string())
+    })?;
+    let (kept, purgeable): (Vec<_>, Vec<_>) = expired.into_iter().partition(|row| row.referenced);
+    let kept: Vec<Uuid> = kept.into_iter().map(|row| row.id).collect();
+    let purgeable: Vec<Uuid> = purgeable.into_iter().map(|row| row.id).collect();
+
+    let purged = sqlx::query_scalar!(
+        r#"WITH purged AS (
+            DELETE FROM parts
+            WHERE id = ANY($1)
+            RETURNING *
+        )
+        INSERT INTO audit_logs (entity, entity_id, action, actor_id, request_id, before)
+        SELECT $2, id, $3, $4, $5, to_jsonb(purged) - $6::text[]
+        FROM purged
+        RETURNING entity_id"#,
+        &purgeable,
+        AuditEntity::Part.as_str(),
+        AuditAction::Purge.as_str(),
+        user_id,
+        current_request_id(),
+        AuditEntity::Part.hidden_columns() as &[&str]
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("DB error during purging trash: {}", e);
+        AppError::DatabaseError("Failed to purge trash".to_string())
+    })?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing trash purge: {}", e);
+        AppError::DatabaseError("Failed to purge trash".to_string())
+    })?;
+
+    info!(
+        "Purged {} parts from trash ({} kept because they are still referenced)",
+        purged.len(),
+        kept.len()
+    );
+    Ok(PurgeResult {
+        retention_days,
+        purged,
+        kept,
+    })
+}
@@ -1,27 +1,135 @@
+use crate::audit::domain::{AuditAction, AuditEntity};
+use crate::audit::service::{record_audit, snapshot};
 use crate::auth::domain::Claims;
+use crate::eco::service::item::{find_open_eco_item, sync_eco_item};
 use crate::errors::app_error::AppError;
+use crate::errors::conflict::map_unique_violation;
 use crate::errors::validation::extract_validation_errors;
-use crate::part::domain::{NewPart, Part};
+use crate::part::domain::{NewPart, Part, RevisionScheme, RevisionStatus};
+use crate::responses::etag::{IfMatch, lock_and_check_version};
 
-use sqlx::PgPool;
+use sqlx::{Connection, PgConnection, PgPool};
 use tracing::{error, info};
 use uuid::Uuid;
 use validator::Validate;
 
 use super::auth::ensure_admin_or_owner;
+use super::lifecycle::locked_state;
+use super::numbering::{consume_reservation, ensure_part_number_available};
+use super::revision::{insert_revision, lock_latest_revision, update_working_revision};
 
+/// 作業中リビジョンを編集する。最新リビジョンがリリース済みなら次のリビジョンを作成する。
+/// ロックされた状態の部品は、部品を含む作成中の ECO がある場合のみ編集でき、
+/// 編集内容は ECO の変更内容にだけ記録される (部品への反映は ECO の承認時。部品はバージョンのみ進む)。
+/// `If-Match` が現在のバージョンと一致しない場合は 412 を返す。
 pub async fn update_part(
     claims: Claims,
     pool: &PgPool,
     id: Uuid,
     updated_part: NewPart,
+    if_match: IfMatch,
+) -> Result<Part, AppError> {
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("DB error during starting transaction: {}", e);
+        AppError::DatabaseError("Failed to update part".to_string())
+    })?;
+
+    let part = apply_part_update(&mut tx, &claims, id, updated_part, if_match).await?;
+
+    tx.commit().await.map_err(|e| {
+        error!("DB error during committing part update: {}", e);
+        AppError::DatabaseError("Failed to update part".to_string())
+    })?;
+    Ok(part)
+}
+
+/// トランザクション内で `update_part` の検証・権限確認・更新を行う
+pub async fn apply_part_update(
+    conn: &mut PgConnection,
+    claims: &Claims,
+    id: Uuid,
+    updated_part: NewPart,
+    if_match: IfMatch,
 ) -> Result<Part, AppError> {
     updated_part
         .validate()
         .map_err(|e| AppError::ValidationError(extract_validation_errors(e)))?;
 
-    ensure_admin_or_owner(claims, pool, id).await?;
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|e| AppError::InternalError(format!("Invalid UUID in claims: {}", e)))?;
+
+    ensure_admin_or_owner(claims.clone(), &mut *conn, id).await?;
+    let locked = locked_state(&mut *conn, id).await?;
+
+    lock_and_check_version(conn, "parts", id, &if_match).await?;
+
+    let eco_item = match locked {
+        Some(state) => Some(find_open_eco_item(conn, id).await?.ok_or_else(|| {
+            info!("Part {} is locked in state {} without open ECO", id, state);
+            AppError::Unauthorized(format!(
+                "Part in state '{}' can only be changed through an open ECO.",
+                state
+            ))
+        })?),
+        None => None,
+    };
+
+    ensure_part_number_available(conn, &updated_part.part_number, Some(id), user_id).await?;
 
+    if let Some(item) = &eco_item {
+        let before = snapshot(&mut *conn, AuditEntity::EcoItem, item.id).await?;
+        sync_eco_item(conn, item.id, &updated_part).await?;
+        record_audit(
+            &mut *conn,
+            AuditEntity::EcoItem,
+            item.id,
+            AuditAction::Update,
+            Some(user_id),
+            before,
+        )
+        .await?;
+        info!(
+            "Change to locked part {} recorded in ECO item {}",
+            id, item.id
+        );
+        // 部品の内容は変わらないが、同じ ETag を持つ他の編集を 412 にするためバージョンを進める
+        return sqlx::query_as!(
+            Part,
+            r#"UPDATE parts
+            SET version = version + 1
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING id, part_number, name, description, kind, status, created_at, created_by, updated_at, version
+            "#,
+            id
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| {
+            error!("DB error during updating part version: {}", e);
+            AppError::DatabaseError("Failed to update part".to_string())
+        });
+    }
+
+    let before = snapshot(&mut *conn, AuditEntity::Part, id).await?;
+
+    let latest = lock_latest_revision(conn, id).await?.ok_or_else(|| {
+        info!("Part not found for update: {}", id);
+        AppError::NotFound(format!("Part not found for update: {}", id))
+    })?;
+
+    let revision = match RevisionStatus::from(latest.status.as_str()) {
+        RevisionStatus::Working => update_working_revision(conn, latest.id, &updated_part).await?,
+        RevisionStatus::Released => {
+            let next = RevisionScheme::from_env().next(&latest.revision);
+            insert_revision(conn, id, &next, &updated_part, user_id).await?
+        }
+    };
+
+    // 一意制約違反のあとで既存の部品を引けるよう、セーブポイント内で実行する
+    let mut savepoint = conn.begin().await.map_err(|e| {
+        error!("DB error during creating savepoint: {}", e);
+        AppError::DatabaseError("Failed to update part".to_string())
+    })?;
     let part = sqlx::query_as!(
         Part,
         r#"UPDATE parts
@@ -30,8 +138,8 @@ pub async fn update_part(
             description = $3,
             kind = $4,
             updated_at = NOW()
-        WHERE id = $5
-        RETURNING id, part_number, name, description, kind, created_at, created_by, updated_at
+        WHERE id = $5 AND deleted_at IS NULL
+        RETURNING id, part_number, name, description, kind, status, created_at, created_by, updated_at, version
         "#,
         updated_part.part_number,
         updated_part.name,
@@ -39,16 +147,46 @@ pub async fn update_part(
         updated_part.kind,
         id
     )
-    .fetch_optional(pool)
-    .await
-    .map_err(|e| {
-        error!("DB error during updating part: {}", e);
-        AppError::DatabaseError("Failed to update part".to_string())
-    })?;
+    .fetch_optional(&mut *savepoint)
+    .await;
+
+    let part = match part {
+        Ok(part) => {
+            savepoint.commit().await.map_err(|e| {
+                error!("DB error during releasing savepoint: {}", e);
+                AppError::DatabaseError("Failed to update part".to_string())
+            })?;
+            part
+        }
+        Err(e) => {
+            savepoint.rollback().await.map_err(|e| {
+                error!("DB error during rolling back savepoint: {}", e);
+                AppError::DatabaseError("Failed to update part".to_string())
+            })?;
+            return Err(map_unique_violation(conn, e, |e| {
+                error!("DB error during updating part: {}", e);
+                AppError::DatabaseError("Failed to update part".to_string())
+            })
+            .await);
+        }
+    };
 
     match part {
         Some(part) => {
-            info!("Part updated successfully: {}", part.id);
+            consume_reservation(&mut *conn, &part.part_number, part.id).await?;
+            record_audit(
+                conn,
+                AuditEntity::Part,
+                part.id,
+                AuditAction::Update,
+                Some(user_id),
+                before,
+            )
+            .await?;
+            info!(
+                "Part updated successfully: {} (revision {})",
+                part.id, revision.revision
+            );
             Ok(part)
         }
         None => {
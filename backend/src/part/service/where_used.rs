@@ -0,0 +1,82 @@
+use crate::errors::app_error::AppError;
+use crate::part::domain::{WhereUsed, WhereUsedLink, WhereUsedMode, build_where_used_paths};
+
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::get::get_part;
+
+async fn fetch_parent_links(
+    pool: &PgPool,
+    part_id: Uuid,
+) -> Result<Vec<WhereUsedLink>, sqlx::Error> {
+    sqlx::query_as!(
+        WhereUsedLink,
+        r#"SELECT b.id AS line_id, b.parent_id, b.child_id,
+                  p.part_number AS parent_part_number, p.name AS parent_name,
+                  b.quantity, b.unit_of_measure, b.find_number
+        FROM bom_lines b
+        JOIN parts p ON p.id = b.parent_id
+        WHERE b.child_id = $1 AND p.deleted_at IS NULL
+        "#,
+        part_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+async fn fetch_ancestor_links(
+    pool: &PgPool,
+    part_id: Uuid,
+) -> Result<Vec<WhereUsedLink>, sqlx::Error> {
+    sqlx::query_as!(
+        WhereUsedLink,
+        r#"WITH RECURSIVE ancestors AS (
+            SELECT b.id, b.parent_id FROM bom_lines b
+            JOIN parts p ON p.id = b.parent_id AND p.deleted_at IS NULL
+            WHERE b.child_id = $1
+            UNION
+            SELECT b.id, b.parent_id FROM bom_lines b
+            JOIN parts p ON p.id = b.parent_id AND p.deleted_at IS NULL
+            JOIN ancestors a ON b.child_id = a.parent_id
+        )
+        SELECT b.id AS line_id, b.parent_id, b.child_id,
+               p.part_number AS parent_part_number, p.name AS parent_name,
+               b.quantity, b.unit_of_measure, b.find_number
+        FROM bom_lines b
+        JOIN parts p ON p.id = b.parent_id
+        WHERE b.id IN (SELECT id FROM ancestors)
+        "#,
+        part_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_where_used(
+    pool: &PgPool,
+    part_id: Uuid,
+    mode: WhereUsedMode,
+) -> Result<WhereUsed, AppError> {
+    let part = get_part(pool, part_id).await?;
+
+    let links = match mode {
+        WhereUsedMode::Single => fetch_parent_links(pool, part_id).await,
+        WhereUsedMode::Recursive => fetch_ancestor_links(pool, part_id).await,
+    }
+    .map_err(|e| {
+        error!("DB error during fetching where-used: {}", e);
+        AppError::DatabaseError("Failed to fetch where-used".to_string())
+    })?;
+
+    let paths = build_where_used_paths(part_id, &links, mode);
+
+    info!("Found {} where-used paths of part {}", paths.len(), part_id);
+    Ok(WhereUsed {
+        part_id: part.id,
+        part_number: part.part_number,
+        name: part.name,
+        paths,
+    })
+}
@@ -0,0 +1,193 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use utoipa::ToSchema;
+
+/// JSON Patch (RFC 6902) の操作
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+/// PATCH のリクエスト本文
+#[derive(Debug, Clone)]
+pub enum PatchDocument {
+    /// `application/merge-patch+json` (RFC 7396)
+    Merge(Value),
+    /// `application/json-patch+json` (RFC 6902)
+    Json(Vec<PatchOperation>),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PatchError {
+    /// パスが存在しない、または不正
+    InvalidPath(String),
+    /// test 操作の値が一致しない
+    TestFailed(String),
+}
+
+impl PatchDocument {
+    pub fn apply(&self, target: &mut Value) -> Result<(), PatchError> {
+        match self {
+            PatchDocument::Merge(patch) => {
+                merge_patch(target, patch);
+                Ok(())
+            }
+            PatchDocument::Json(operations) => json_patch(target, operations),
+        }
+    }
+}
+
+/// RFC 7396 の MergePatch。`null` はメンバーの削除を表す
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// RFC 6902 の操作を順に適用する。途中で失敗した場合 `target` は変更しない
+pub fn json_patch(target: &mut Value, operations: &[PatchOperation]) -> Result<(), PatchError> {
+    let mut patched = target.clone();
+    for operation in operations {
+        match operation {
+            PatchOperation::Add { path, value } => add(&mut patched, path, value.clone())?,
+            PatchOperation::Remove { path } => {
+                remove(&mut patched, path)?;
+            }
+            PatchOperation::Replace { path, value } => {
+                *patched
+                    .pointer_mut(path)
+                    .ok_or_else(|| PatchError::InvalidPath(path.clone()))? = value.clone();
+            }
+            PatchOperation::Move { from, path } => {
+                let value = remove(&mut patched, from)?;
+                add(&mut patched, path, value)?;
+            }
+            PatchOperation::Copy { from, path } => {
+                let value = patched
+                    .pointer(from)
+                    .cloned()
+                    .ok_or_else(|| PatchError::InvalidPath(from.clone()))?;
+                add(&mut patched, path, value)?;
+            }
+            PatchOperation::Test { path, value } => {
+                if patched.pointer(path) != Some(value) {
+                    return Err(PatchError::TestFailed(path.clone()));
+                }
+            }
+        }
+    }
+    *target = patched;
+    Ok(())
+}
+
+/// JSON Pointer を親のポインタと末尾のトークンに分ける
+fn split_pointer(path: &str) -> Result<(&str, String), PatchError> {
+    let (parent, last) = path
+        .rsplit_once('/')
+        .ok_or_else(|| PatchError::InvalidPath(path.to_string()))?;
+    Ok((parent, last.replace("~1", "/").replace("~0", "~")))
+}
+
+fn add(target: &mut Value, path: &str, value: Value) -> Result<(), PatchError> {
+    if path.is_empty() {
+        *target = value;
+        return Ok(());
+    }
+    let (parent, key) = split_pointer(path)?;
+    let invalid = || PatchError::InvalidPath(path.to_string());
+    match target.pointer_mut(parent).ok_or_else(invalid)? {
+        Value::Object(map) => {
+            map.insert(key, value);
+        }
+        Value::Array(array) => {
+            let index = if key == "-" {
+                array.len()
+            } else {
+                key.parse::<usize>().map_err(|_| invalid())?
+            };
+            if index > array.len() {
+                return Err(invalid());
+            }
+            array.insert(index, value);
+        }
+        _ => return Err(invalid()),
+    }
+    Ok(())
+}
+
+fn remove(target: &mut Value, path: &str) -> Result<Value, PatchError> {
+    let (parent, key) = split_pointer(path)?;
+    let invalid = || PatchError::InvalidPath(path.to_string());
+    match target.pointer_mut(parent).ok_or_else(invalid)? {
+        Value::Object(map) => map.remove(&key).ok_or_else(invalid),
+        Value::Array(array) => {
+            let index = key.parse::<usize>().map_err(|_| invalid())?;
+            if index >= array.len() {
+                return Err(invalid());
+            }
+            Ok(array.remove(index))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{PatchError, PatchOperation, json_patch, merge_patch};
+
+    #[test]
+    fn test_merge_patch() {
+        let mut target = json!({"name": "ボルト", "description": "大型用", "kind": "部品"});
+        merge_patch(&mut target, &json!({"description": "小型用", "kind": null}));
+        assert_eq!(target, json!({"name": "ボルト", "description": "小型用"}));
+    }
+
+    #[test]
+    fn test_json_patch() {
+        let mut target = json!({"name": "ボルト", "kind": "部品", "tags": ["a"]});
+        let operations: Vec<PatchOperation> = serde_json::from_value(json!([
+            {"op": "test", "path": "/name", "value": "ボルト"},
+            {"op": "replace", "path": "/name", "value": "ナット"},
+            {"op": "move", "from": "/kind", "path": "/description"},
+            {"op": "add", "path": "/tags/-", "value": "b"},
+        ]))
+        .unwrap();
+        json_patch(&mut target, &operations).unwrap();
+        assert_eq!(
+            target,
+            json!({"name": "ナット", "description": "部品", "tags": ["a", "b"]})
+        );
+
+        let failing: Vec<PatchOperation> = serde_json::from_value(json!([
+            {"op": "replace", "path": "/name", "value": "x"},
+            {"op": "test", "path": "/name", "value": "ボルト"},
+        ]))
+        .unwrap();
+        assert_eq!(
+            json_patch(&mut target, &failing),
+            Err(PatchError::TestFailed("/name".to_string()))
+        );
+        assert_eq!(target["name"], "ナット");
+    }
+}
@@ -0,0 +1,46 @@
+use axum::body::Bytes;
+use axum::extract::{FromRequest, Request};
+use axum::http::header::CONTENT_TYPE;
+
+use crate::errors::app_error::AppError;
+use crate::errors::validation::field_validation_error;
+
+use super::domain::{PatchDocument, PatchOperation};
+
+pub const MERGE_PATCH_JSON: &str = "application/merge-patch+json";
+pub const JSON_PATCH_JSON: &str = "application/json-patch+json";
+
+/// `Content-Type` に応じて本文を Merge Patch / JSON Patch として読み込む
+impl<S: Send + Sync> FromRequest<S> for PatchDocument {
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let content_type = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+
+        let body = Bytes::from_request(req, state).await.map_err(|e| {
+            AppError::ValidationError(field_validation_error("body", &e.body_text()))
+        })?;
+        let invalid = |e: serde_json::Error| {
+            AppError::ValidationError(field_validation_error("body", &e.to_string()))
+        };
+
+        match content_type.as_str() {
+            MERGE_PATCH_JSON => Ok(PatchDocument::Merge(
+                serde_json::from_slice(&body).map_err(invalid)?,
+            )),
+            JSON_PATCH_JSON => Ok(PatchDocument::Json(
+                serde_json::from_slice::<Vec<PatchOperation>>(&body).map_err(invalid)?,
+            )),
+            other => Err(AppError::UnsupportedMediaType(format!(
+                "Unsupported Content-Type '{}': use {} or {}",
+                other, MERGE_PATCH_JSON, JSON_PATCH_JSON
+            ))),
+        }
+    }
+}
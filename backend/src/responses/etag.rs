@@ -0,0 +1,139 @@
+use std::convert::Infallible;
+
+use axum::extract::FromRequestParts;
+use axum::http::HeaderValue;
+use axum::http::header::{ETAG, IF_MATCH};
+use axum::http::request::Parts;
+use axum::response::{IntoResponseParts, ResponseParts};
+use sqlx::PgConnection;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::errors::app_error::AppError;
+
+/// `version` 列を持ち、楽観的排他制御の対象になるリソース
+pub trait Versioned {
+    fn version(&self) -> i64;
+}
+
+/// バージョンを `ETag` ヘッダーとして返す
+pub struct ETag(pub i64);
+
+impl ETag {
+    pub fn of(resource: &impl Versioned) -> Self {
+        ETag(resource.version())
+    }
+
+    fn value(&self) -> String {
+        format!("\"{}\"", self.0)
+    }
+}
+
+impl IntoResponseParts for ETag {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        if let Ok(value) = HeaderValue::from_str(&self.value()) {
+            res.headers_mut().insert(ETAG, value);
+        }
+        Ok(res)
+    }
+}
+
+/// リクエストの `If-Match` ヘッダー。検証はサービス層で権限確認の後に行う。
+#[derive(Debug, Clone, Default)]
+pub struct IfMatch(Option<Vec<String>>);
+
+impl IfMatch {
+    /// `If-Match` は強い比較を使うため、弱い ETag (`W/"3"`) はそのまま残し、どのバージョンにも一致させない
+    pub fn new(header: Option<&str>) -> Self {
+        IfMatch(header.map(|value| {
+            value
+                .split(',')
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect()
+        }))
+    }
+
+    /// 指定したバージョンにのみ一致する `If-Match`
+    pub fn for_version(version: i64) -> Self {
+        IfMatch(Some(vec![ETag(version).value()]))
+    }
+
+    pub fn matches(&self, version: i64) -> bool {
+        let current = ETag(version).value();
+        self.0
+            .as_ref()
+            .is_some_and(|tags| tags.iter().any(|tag| tag == "*" || *tag == current))
+    }
+
+    /// `If-Match` がなければ 428、現在のバージョンと一致しなければ 412 を返す
+    pub fn ensure(&self, version: i64) -> Result<(), AppError> {
+        if self.0.is_none() {
+            return Err(AppError::PreconditionRequired(
+                "If-Match header is required".to_string(),
+            ));
+        }
+        if !self.matches(version) {
+            info!("If-Match mismatch: current version {}", version);
+            return Err(AppError::PreconditionFailed(format!(
+                "Resource has been modified (current ETag {})",
+                ETag(version).value()
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(IfMatch::new(
+            parts
+                .headers
+                .get(IF_MATCH)
+                .and_then(|value| value.to_str().ok()),
+        ))
+    }
+}
+
+/// `table` の行を行ロック付きで取得し、`If-Match` と現在のバージョンを照合する。
+/// `table` は呼び出し側で固定したテーブル名のみを渡すこと。
+pub async fn lock_and_check_version(
+    conn: &mut PgConnection,
+    table: &'static str,
+    id: Uuid,
+    if_match: &IfMatch,
+) -> Result<i64, AppError> {
+    let sql = format!("SELECT version FROM {} WHERE id = $1 FOR UPDATE", table);
+    let version = sqlx::query_scalar::<_, i64>(&sql)
+        .bind(id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| {
+            error!("DB error during fetching version of {}: {}", table, e);
+            AppError::DatabaseError("Failed to check version".to_string())
+        })?
+        .ok_or_else(|| AppError::NotFound(format!("Resource not found: {}", id)))?;
+
+    if_match.ensure(version)?;
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IfMatch;
+
+    #[test]
+    fn test_if_match() {
+        assert!(IfMatch::new(Some("\"3\"")).matches(3));
+        assert!(!IfMatch::new(Some("\"1\", W/\"3\"")).matches(3));
+        assert!(IfMatch::new(Some("W/\"1\", \"3\"")).matches(3));
+        assert!(IfMatch::new(Some("*")).matches(7));
+        assert!(!IfMatch::new(Some("\"2\"")).matches(3));
+        assert!(IfMatch::new(None).ensure(3).is_err());
+        assert!(IfMatch::new(Some("\"2\"")).ensure(3).is_err());
+    }
+}
@@ -0,0 +1,301 @@
+use std::fs::File;
+use std::future::Future;
+use std::io::{self, Seek};
+
+use axum::body::Body;
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::response::{IntoResponse, Response};
+use futures_util::{Stream, StreamExt, stream};
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio_util::io::ReaderStream;
+use tracing::error;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::errors::app_error::AppError;
+use crate::errors::validation::field_validation_error;
+
+/// 送信待ちにできる行数。クライアントの受信が遅ければ DB からの読み出しも待つ
+const EXPORT_BUFFER: usize = 256;
+/// DB から一度に読む行数。読み終えるたびに接続をプールに返す
+pub const EXPORT_CHUNK: i64 = 1000;
+/// XLSX の 1 シートに書ける行数 (見出し行を除く)
+const XLSX_MAX_ROWS: u32 = 1_048_575;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Xlsx,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+            ExportFormat::Json => "application/json",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ExportQuery {
+    /// csv (default), xlsx or json
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// 表形式で書き出すセルの値
+pub enum ExportValue {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+impl ExportValue {
+    fn to_text(&self) -> String {
+        match self {
+            ExportValue::Text(text) => escape_formula(text),
+            ExportValue::Number(number) => number.to_string(),
+            ExportValue::Empty => String::new(),
+        }
+    }
+}
+
+/// 表計算ソフトが数式として解釈する文字で始まる値は、先頭に `'` を付けて文字列として扱わせる
+fn escape_formula(text: &str) -> String {
+    if text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", text)
+    } else {
+        text.to_string()
+    }
+}
+
+impl From<String> for ExportValue {
+    fn from(value: String) -> Self {
+        ExportValue::Text(value)
+    }
+}
+
+impl From<f64> for ExportValue {
+    fn from(value: f64) -> Self {
+        ExportValue::Number(value)
+    }
+}
+
+impl From<i64> for ExportValue {
+    fn from(value: i64) -> Self {
+        ExportValue::Number(value as f64)
+    }
+}
+
+impl From<i32> for ExportValue {
+    fn from(value: i32) -> Self {
+        ExportValue::Number(value.into())
+    }
+}
+
+impl<T: Into<ExportValue>> From<Option<T>> for ExportValue {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or(ExportValue::Empty)
+    }
+}
+
+/// CSV / XLSX では `values` を 1 行に、JSON ではそのままシリアライズして書き出す
+pub trait ExportRecord: Serialize + Send + 'static {
+    fn headers() -> &'static [&'static str];
+    fn values(&self) -> Vec<ExportValue>;
+}
+
+pub type RowSender<T> = mpsc::Sender<Result<T, AppError>>;
+
+/// `produce` が別タスクで送る行を `format` で書き出す。
+/// CSV と JSON は届いた行から順に送るので、全件をメモリに載せない。
+/// XLSX は zip の構造上ファイル全体ができてから送るが、メモリではなく一時ファイルに書いてから読み出す。
+pub async fn export_response<T, F, Fut>(
+    format: ExportFormat,
+    file_stem: &str,
+    produce: F,
+) -> Result<Response, AppError>
+where
+    T: ExportRecord,
+    F: FnOnce(RowSender<T>) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel(EXPORT_BUFFER);
+    tokio::spawn(produce(sender));
+    let rows = stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|row| (row, receiver))
+    });
+
+    let body = match format {
+        ExportFormat::Csv => Body::from_stream(csv_stream(rows)),
+        ExportFormat::Json => Body::from_stream(json_stream(rows)),
+        ExportFormat::Xlsx => {
+            let file = tokio::fs::File::from_std(write_xlsx(rows).await?);
+            Body::from_stream(ReaderStream::new(file))
+        }
+    };
+
+    let file_name: String = file_stem
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "-_.".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    Ok((
+        [
+            (CONTENT_TYPE, format.content_type().to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"{}.{}\"",
+                    file_name,
+                    format.extension()
+                ),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Excel で文字化けしないよう先頭に UTF-8 BOM を付ける
+fn csv_stream<T: ExportRecord>(
+    rows: impl Stream<Item = Result<T, AppError>>,
+) -> impl Stream<Item = Result<Vec<u8>, io::Error>> {
+    let header = csv_line(T::headers().iter().map(|header| header.to_string())).map(|line| {
+        let mut bytes = "\u{feff}".as_bytes().to_vec();
+        bytes.extend(line);
+        bytes
+    });
+
+    stream::once(async move { header }).chain(rows.map(|row| {
+        let row = row.map_err(abort_stream)?;
+        csv_line(row.values().iter().map(ExportValue::to_text))
+    }))
+}
+
+fn csv_line(fields: impl IntoIterator<Item = String>) -> Result<Vec<u8>, io::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(fields)?;
+    writer
+        .into_inner()
+        .map_err(|e| io::Error::other(e.to_string()))
+}
+
+fn json_stream<T: ExportRecord>(
+    rows: impl Stream<Item = Result<T, AppError>>,
+) -> impl Stream<Item = Result<Vec<u8>, io::Error>> {
+    stream::once(async { Ok(b"[".to_vec()) })
+        .chain(rows.enumerate().map(|(index, row)| {
+            let row = row.map_err(abort_stream)?;
+            let mut bytes = if index == 0 {
+                Vec::new()
+            } else {
+                b",".to_vec()
+            };
+            serde_json::to_writer(&mut bytes, &row)?;
+            Ok(bytes)
+        }))
+        .chain(stream::once(async { Ok(b"]".to_vec()) }))
+}
+
+async fn write_xlsx<T: ExportRecord>(
+    rows: impl Stream<Item = Result<T, AppError>>,
+) -> Result<File, AppError> {
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet_with_constant_memory();
+    let bold = Format::new().set_bold();
+    sheet.set_freeze_panes(1, 0).map_err(xlsx_error)?;
+    for (col, header) in T::headers().iter().enumerate() {
+        sheet
+            .write_string_with_format(0, col as u16, *header, &bold)
+            .map_err(xlsx_error)?;
+    }
+
+    let mut rows = std::pin::pin!(rows);
+    let mut row_index = 0;
+    while let Some(row) = rows.next().await {
+        let row = row?;
+        row_index += 1;
+        if row_index > XLSX_MAX_ROWS {
+            return Err(AppError::ValidationError(field_validation_error(
+                "format",
+                "Too many rows for an XLSX sheet; export as csv or json",
+            )));
+        }
+        for (col, value) in row.values().into_iter().enumerate() {
+            match value {
+                ExportValue::Text(text) => {
+                    sheet.write_string(row_index, col as u16, escape_formula(&text))
+                }
+                ExportValue::Number(number) => sheet.write_number(row_index, col as u16, number),
+                ExportValue::Empty => continue,
+            }
+            .map_err(xlsx_error)?;
+        }
+    }
+
+    // 閉じると消える一時ファイルに書き出す
+    tokio::task::spawn_blocking(move || {
+        let mut file = tempfile::tempfile().map_err(xlsx_io_error)?;
+        workbook.save_to_writer(&mut file).map_err(xlsx_error)?;
+        file.rewind().map_err(xlsx_io_error)?;
+        Ok(file)
+    })
+    .await
+    .map_err(|e| {
+        error!("XLSX export task failed: {}", e);
+        AppError::InternalError("Failed to write XLSX".to_string())
+    })?
+}
+
+fn xlsx_io_error(e: io::Error) -> AppError {
+    error!("Failed to write XLSX export file: {}", e);
+    AppError::InternalError("Failed to write XLSX".to_string())
+}
+
+fn xlsx_error(e: XlsxError) -> AppError {
+    error!("Failed to write XLSX export: {}", e);
+    AppError::InternalError("Failed to write XLSX".to_string())
+}
+
+/// 送信を始めた後のエラーはステータスで返せないため、接続を切って途中で終わったことを伝える
+fn abort_stream(e: AppError) -> io::Error {
+    error!("Export aborted: {:?}", e);
+    io::Error::other("export aborted")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::escape_formula;
+
+    #[test]
+    fn test_escape_formula() {
+        assert_eq!(escape_formula("=HYPERLINK(\"x\")"), "'=HYPERLINK(\"x\")");
+        assert_eq!(escape_formula("+1"), "'+1");
+        assert_eq!(escape_formula("-2+3"), "'-2+3");
+        assert_eq!(escape_formula("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(escape_formula("BLT-000422"), "BLT-000422");
+        assert_eq!(escape_formula(""), "");
+    }
+}
@@ -1,2 +1,5 @@
 pub mod error;
+pub mod etag;
+pub mod export;
+pub mod pagination;
 pub mod success;
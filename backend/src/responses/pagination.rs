@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+pub const DEFAULT_PER_PAGE: i64 = 50;
+pub const MAX_PER_PAGE: i64 = 200;
+/// オフセットの計算が i64 に収まるページ番号の上限
+const MAX_PAGE: i64 = i64::MAX / MAX_PER_PAGE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, IntoParams)]
+pub struct PageQuery {
+    /// 1 始まりのページ番号 (default 1)
+    pub page: Option<i64>,
+    /// 1ページあたりの件数 (default 50, max 200)
+    pub per_page: Option<i64>,
+}
+
+impl PageQuery {
+    pub fn page(&self) -> i64 {
+        self.page.unwrap_or(1).clamp(1, MAX_PAGE)
+    }
+
+    pub fn per_page(&self) -> i64 {
+        self.per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .clamp(1, MAX_PER_PAGE)
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page() - 1) * self.per_page()
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Pagination {
+    pub page: i64,
+    pub per_page: i64,
+    pub total: i64,
+    pub total_pages: i64,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+impl Pagination {
+    /// `path` と元のクエリ文字列から前後ページへのリンクを組み立てる
+    pub fn new(page: &PageQuery, total: i64, path: &str, raw_query: Option<&str>) -> Self {
+        let (page_no, per_page) = (page.page(), page.per_page());
+        let total_pages = (total + per_page - 1) / per_page;
+
+        let link = |target: i64| {
+            let mut params: Vec<&str> = raw_query
+                .unwrap_or_default()
+                .split('&')
+                .filter(|p| !p.is_empty() && !p.starts_with("page="))
+                .collect();
+            let page_param = format!("page={}", target);
+            params.push(&page_param);
+            format!("{}?{}", path, params.join("&"))
+        };
+
+        Pagination {
+            page: page_no,
+            per_page,
+            total,
+            total_pages,
+            next: (page_no < total_pages).then(|| link(page_no + 1)),
+            prev: (page_no > 1).then(|| link((page_no - 1).min(total_pages.max(1)))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PageQuery, Pagination};
+
+    #[test]
+    fn test_pagination_links_keep_filters() {
+        let page = PageQuery {
+            page: Some(2),
+            per_page: Some(10),
+        };
+        let pagination = Pagination::new(&page, 35, "/parts", Some("kind=bolt&page=2&per_page=10"));
+
+        assert_eq!(pagination.total_pages, 4);
+        assert_eq!(
+            pagination.next.as_deref(),
+            Some("/parts?kind=bolt&per_page=10&page=3")
+        );
+        assert_eq!(
+            pagination.prev.as_deref(),
+            Some("/parts?kind=bolt&per_page=10&page=1")
+        );
+    }
+
+    #[test]
+    fn test_huge_page_does_not_overflow() {
+        let page = PageQuery {
+            page: Some(i64::MAX),
+            per_page: Some(200),
+        };
+        assert!(page.offset() > 0);
+        let pagination = Pagination::new(&page, 3, "/parts", None);
+        assert!(pagination.next.is_none());
+    }
+
+    #[test]
+    fn test_pagination_last_page_has_no_next() {
+        let page = PageQuery {
+            page: None,
+            per_page: Some(500),
+        };
+        let pagination = Pagination::new(&page, 3, "/parts", None);
+
+        assert_eq!(pagination.per_page, 200);
+        assert_eq!(pagination.total_pages, 1);
+        assert!(pagination.next.is_none());
+        assert!(pagination.prev.is_none());
+    }
+}
@@ -2,12 +2,16 @@ use axum::http::StatusCode;
 use serde::Serialize;
 use utoipa::ToSchema;
 
+use super::pagination::Pagination;
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct SuccessResponse<T> {
     #[schema(example = true)]
     pub success: bool,
     pub code: u16,
     pub data: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
 }
 
 impl<T> SuccessResponse<T> {
@@ -16,6 +20,7 @@ impl<T> SuccessResponse<T> {
             success: true,
             code: StatusCode::OK.as_u16(),
             data,
+            pagination: None,
         }
     }
 
@@ -24,6 +29,7 @@ impl<T> SuccessResponse<T> {
             success: true,
             code: StatusCode::CREATED.as_u16(),
             data,
+            pagination: None,
         }
     }
 
@@ -35,6 +41,26 @@ impl<T> SuccessResponse<T> {
             success: true,
             code: StatusCode::NO_CONTENT.as_u16(),
             data: T::default(),
+            pagination: None,
+        }
+    }
+
+    /// 一部の操作が失敗した一括処理の結果
+    pub fn multi_status(data: T) -> Self {
+        SuccessResponse {
+            success: true,
+            code: StatusCode::MULTI_STATUS.as_u16(),
+            data,
+            pagination: None,
+        }
+    }
+
+    pub fn paginated(data: T, pagination: Pagination) -> Self {
+        SuccessResponse {
+            success: true,
+            code: StatusCode::OK.as_u16(),
+            data,
+            pagination: Some(pagination),
         }
     }
 }
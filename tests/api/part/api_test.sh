@@ -3,6 +3,7 @@ set -e
 
 API_URL="http://backend:3000"
 ORIGIN_HEADER="Origin: http://localhost:5173"
+ADMIN_PASSWORD="${ADMIN_INITIAL_PASSWORD:-admin-test-password}"
 
 echo "=== 🧪 Running health check ==="
 curl -sf "$API_URL/healthz" | grep "OK" >/dev/null || {
@@ -44,6 +45,11 @@ echo "✅ Login successful"
 
 AUTH_HEADER="Authorization: Bearer $token"
 
+# 部品の現在の ETag を取得する (更新・削除には If-Match が必要)
+etag_of() {
+  curl -s -o /dev/null -D - "$API_URL/parts/$1" -H "$2" | tr -d '\r' | awk 'tolower($1) == "etag:" { print $2 }'
+}
+
 echo "=== 🧪 Creating a part -2- ==="
 part_res=$(curl -s -X POST "$API_URL/parts" \
   -H "Content-Type: application/json" \
@@ -60,13 +66,28 @@ if [ "$part_id_1" == "null" ] || [ -z "$part_id_1" ]; then
 fi
 echo "✅ Part created with ID: $part_id_1"
 
-echo "=== 🧪 Creating a part -2- ==="
-part_res=$(curl -s -X POST "$API_URL/parts" \
+echo "=== 🧪 Creating a part with duplicate part_number ==="
+dup_res=$(curl -s -X POST "$API_URL/parts" \
   -H "Content-Type: application/json" \
   -H "$AUTH_HEADER" \
   -H "$ORIGIN_HEADER" \
   -d '{"part_number":"XYZ-789","name":"ボルト","description":"大型用","kind":"部品"}')
 
+echo "$dup_res" | jq .
+dup_code=$(echo "$dup_res" | jq -r '.code')
+if [ "$dup_code" != "409" ]; then
+  echo "❌ Expected conflict for duplicate part_number, got: $dup_code"
+  exit 1
+fi
+echo "✅ Duplicate part_number rejected"
+
+echo "=== 🧪 Creating a part -2- (auto-numbered) ==="
+part_res=$(curl -s -X POST "$API_URL/parts" \
+  -H "Content-Type: application/json" \
+  -H "$AUTH_HEADER" \
+  -H "$ORIGIN_HEADER" \
+  -d '{"name":"ボルト","description":"大型用","kind":"部品"}')
+
 echo "$part_res" | jq .
 part_id_2=$(echo "$part_res" | jq -r '.data.id')
 
@@ -98,11 +119,11 @@ echo "✅ Validation for update worked"
 echo "=== 🧪 Signing up as second user ==="
 signup_res2=$(curl -s -X POST "$API_URL/signup" \
   -H "Content-Type: application/json" \
-  -d '{"login_name":"otheruser","password":"pass456"}')
+  -d '{"login_name":"otheruser","password":"password456"}')
 
 login_res2=$(curl -s -X POST "$API_URL/login" \
   -H "Content-Type: application/json" \
-  -d '{"login_name":"otheruser","password":"pass456"}')
+  -d '{"login_name":"otheruser","password":"password456"}')
 
 token2=$(echo "$login_res2" | jq -r '.data.token')
 AUTH_HEADER2="Authorization: Bearer $token2"
@@ -118,16 +139,27 @@ if [ "$code" != "401" ]; then
 fi
 echo "✅ Unauthorized delete blocked"
 
+echo "=== 🧪 Deleting part with stale ETag ==="
+stale_delete=$(curl -s -X DELETE "$API_URL/parts/$part_id_1" \
+  -H "$AUTH_HEADER" -H 'If-Match: "0"')
+
+code=$(echo "$stale_delete" | jq -r '.code')
+if [ "$code" != "412" ]; then
+  echo "❌ Delete with stale ETag should fail, got: $code"
+  exit 1
+fi
+echo "✅ Stale ETag rejected"
+
 echo "=== 🧪 Deleting part ==="
 delete_res=$(curl -s -X DELETE "$API_URL/parts/$part_id_1" \
-  -H "$AUTH_HEADER")
+  -H "$AUTH_HEADER" -H "If-Match: $(etag_of "$part_id_1" "$AUTH_HEADER")")
 echo "$delete_res" | jq .
 echo "✅ Part deleted"
 
 echo "=== 🧪 Logging in as admin ==="
 admin_login_res=$(curl -s -X POST "$API_URL/login" \
   -H "Content-Type: application/json" \
-  -d '{"login_name":"admin","password":"admin"}')
+  -d "{\"login_name\":\"admin\",\"password\":\"$ADMIN_PASSWORD\"}")
 
 echo "$admin_login_res" | jq .
 admin_token=$(echo "$admin_login_res" | jq -r '.data.token')
@@ -143,6 +175,7 @@ echo "=== 🧪 Admin updating another user's part ==="
 admin_update=$(curl -s -X PUT "$API_URL/parts/$part_id_2" \
   -H "Content-Type: application/json" \
   -H "$ADMIN_AUTH_HEADER" -H "$ORIGIN_HEADER" \
+  -H "If-Match: $(etag_of "$part_id_2" "$ADMIN_AUTH_HEADER")" \
   -d '{"part_number":"ADM-002","name":"ボルト(管理者修正)","description":"管理者更新","kind":"管理用"}')
 
 echo "$admin_update" | jq .
@@ -153,9 +186,52 @@ if [ "$admin_update_code" != "200" ]; then
 fi
 echo "✅ Admin was able to update part"
 
+echo "=== 🧪 Patching a released part twice through an ECO ==="
+eco_part_id=$(curl -s -X POST "$API_URL/parts" \
+  -H "Content-Type: application/json" -H "$ADMIN_AUTH_HEADER" \
+  -d '{"part_number":"ECO-PATCH-1","name":"シャフト","description":"初版","kind":"部品"}' | jq -r '.data.id')
+for state in in_review released; do
+  curl -s -X POST "$API_URL/parts/$eco_part_id/transition" \
+    -H "Content-Type: application/json" -H "$ADMIN_AUTH_HEADER" \
+    -d "{\"to\":\"$state\"}" >/dev/null
+done
+eco_id=$(curl -s -X POST "$API_URL/ecos" \
+  -H "Content-Type: application/json" -H "$ADMIN_AUTH_HEADER" \
+  -d '{"title":"シャフトの変更"}' | jq -r '.data.id')
+curl -s -X POST "$API_URL/ecos/$eco_id/items" \
+  -H "Content-Type: application/json" -H "$ADMIN_AUTH_HEADER" \
+  -d "{\"part_id\":\"$eco_part_id\",\"part_number\":\"ECO-PATCH-1\",\"name\":\"シャフト\",\"description\":\"初版\",\"kind\":\"部品\"}" >/dev/null
+
+stale_etag=$(etag_of "$eco_part_id" "$ADMIN_AUTH_HEADER")
+curl -s -X PATCH "$API_URL/parts/$eco_part_id" \
+  -H "Content-Type: application/merge-patch+json" -H "$ADMIN_AUTH_HEADER" \
+  -H "If-Match: $stale_etag" -d '{"name":"シャフト改"}' >/dev/null
+second_patch=$(curl -s -X PATCH "$API_URL/parts/$eco_part_id" \
+  -H "Content-Type: application/json-patch+json" -H "$ADMIN_AUTH_HEADER" \
+  -H "If-Match: $(etag_of "$eco_part_id" "$ADMIN_AUTH_HEADER")" \
+  -d '[{"op":"test","path":"/name","value":"シャフト改"},{"op":"replace","path":"/description","value":"第2版"}]')
+echo "$second_patch" | jq .
+if [ "$(echo "$second_patch" | jq -r '.code')" != "200" ]; then
+  echo "❌ Second patch should apply to the proposed change"
+  exit 1
+fi
+proposed=$(curl -s "$API_URL/ecos/$eco_id" -H "$ADMIN_AUTH_HEADER" | jq -c '.data.items[0] | [.name, .description]')
+if [ "$proposed" != '["シャフト改","第2版"]' ]; then
+  echo "❌ ECO should keep both patches, got: $proposed"
+  exit 1
+fi
+stale_patch_code=$(curl -s -X PATCH "$API_URL/parts/$eco_part_id" \
+  -H "Content-Type: application/merge-patch+json" -H "$ADMIN_AUTH_HEADER" \
+  -H "If-Match: $stale_etag" -d '{"kind":"組立"}' | jq -r '.code')
+if [ "$stale_patch_code" != "412" ]; then
+  echo "❌ Patch with stale ETag should return 412, got: $stale_patch_code"
+  exit 1
+fi
+echo "✅ Both patches were recorded in the ECO"
+
 echo "=== 🧪 Admin deleting another user's part ==="
 admin_delete=$(curl -s -X DELETE "$API_URL/parts/$part_id_2" \
-  -H "$ADMIN_AUTH_HEADER")
+  -H "$ADMIN_AUTH_HEADER" -H "If-Match: $(etag_of "$part_id_2" "$ADMIN_AUTH_HEADER")")
 
 echo "$admin_delete" | jq .
 admin_delete_code=$(echo "$admin_delete" | jq -r '.code')